@@ -1,6 +1,9 @@
 use gtk::{prelude::*, Application, ApplicationWindow, Button, Orientation};
 use std::{future::pending, thread, time::Duration};
-use tachy_reaccy::prelude::*;
+use tachy_reaccy::{
+    persist::{JsonFileStorage, PersistOptions, PersistedSignal},
+    prelude::*,
+};
 use tachydom::view::{keyed::keyed, strings::StrState, Mountable, Render};
 use tachygtk::{button, r#box, Box_, Element, ElementState, TachyGtk};
 mod tachygtk;
@@ -32,7 +35,14 @@ fn main() {
 }
 
 fn ui() -> Box_<impl Render<TachyGtk>> {
-    let value = Signal::new(0);
+    // the count is kept between runs, and saved once it has stopped changing
+    // for a second
+    let value = PersistedSignal::new_with_options(
+        "value",
+        JsonFileStorage::new("counter.json"),
+        0,
+        PersistOptions::new().debounce(Duration::from_secs(1)),
+    );
     let rows = Signal::new(vec![1, 2, 3, 4, 5]);
 
     Effect::new(move |_| {
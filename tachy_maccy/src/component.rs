@@ -138,18 +138,24 @@ impl ToTokens for Model {
         let props_serialized_name = format_ident!("{name}PropsSerialized");
         let trace_name = format!("<{name} />");
 
-        let is_island_with_children = *is_island
-            && props.iter().any(|prop| prop.name.ident == "children");
-        let is_island_with_other_props = *is_island
-            && ((is_island_with_children && props.len() > 1)
-                || (!is_island_with_children && !props.is_empty()));
-
-        let prop_builder_fields =
-            prop_builder_fields(vis, props, is_island_with_other_props);
-        let props_serializer = if is_island_with_other_props {
+        // the rest of the page is static HTML, so there is nowhere for the
+        // children of an island to be hydrated from
+        if *is_island {
+            if let Some(children) =
+                props.iter().find(|prop| prop.name.ident == "children")
+            {
+                abort!(children.name.ident, "Islands cannot take `children`.");
+            }
+        }
+        let is_island_with_props = *is_island && !props.is_empty();
+
+        let prop_builder_fields = prop_builder_fields(vis, props);
+        let props_serializer = if is_island_with_props {
             let fields = prop_serializer_fields(vis, props);
             quote! {
-                #[derive(::leptos::serde::Deserialize)]
+                #[doc(hidden)]
+                #[derive(::tachys::serde::Deserialize)]
+                #[serde(crate = "::tachys::serde")]
                 #vis struct #props_serialized_name {
                     #fields
                 }
@@ -204,40 +210,29 @@ impl ToTokens for Model {
         let hydrate_fn_name =
             Ident::new(&format!("_island_{}", component_id), name.span());
 
-        let island_serialize_props = if is_island_with_other_props {
+        let island_serialize_props = if is_island_with_props {
             quote! {
-                let _leptos_ser_props = ::leptos::serde_json::to_string(&props).expect("couldn't serialize island props");
+                let __island_props = ::tachys::serde_json::to_string(&props)
+                    .expect("couldn't serialize island props");
             }
         } else {
             quote! {}
         };
-        let island_serialized_props = if is_island_with_other_props {
+        let island_with_props = if is_island_with_props {
             quote! {
-                .attr("data-props", _leptos_ser_props)
+                .with_props(__island_props)
             }
         } else {
             quote! {}
         };
 
         let body_name = unmodified_fn_name_from_fn_name(&body_name);
-        let body_expr = if *is_island {
-            quote! {
-                ::leptos::SharedContext::with_hydration(move || {
-                    #body_name(#prop_names)
-                })
-            }
-        } else {
-            quote! {
-                #body_name(#prop_names)
-            }
-        };
-
         let component = quote! {
             ::tachys::tachy_reaccy::untrack(
                 move || {
                     #tracing_guard_expr
                     #tracing_props_expr
-                    #body_expr
+                    #body_name(#prop_names)
                 }
             )
         };
@@ -245,18 +240,19 @@ impl ToTokens for Model {
         // add island wrapper if island
         let component = if *is_island {
             quote! {
-                {
-                    ::leptos::leptos_dom::html::custom(
-                        ::leptos::leptos_dom::html::Custom::new("leptos-island"),
-                    )
-                    .attr("data-component", #component_id)
-                    .attr("data-hkc", ::leptos::leptos_dom::HydrationCtx::peek_always().to_string())
-                    #island_serialized_props
-                    .child(#component)
-                }
+                ::tachys::tachydom::islands::Island::new(
+                    #component_id,
+                    move || #component
+                )
+                #island_with_props
             }
         } else {
-            component
+            // each component is created under its own owner, so the IDs of the
+            // data it creates depend on its place in the view (islands already
+            // have one)
+            quote! {
+                ::tachys::tachy_reaccy::Owner::with_child(move || #component)
+            }
         };
 
         let props_arg = if no_props {
@@ -270,29 +266,11 @@ impl ToTokens for Model {
         let destructure_props = if no_props {
             quote! {}
         } else {
-            let wrapped_children = if is_island_with_children
-                && cfg!(feature = "ssr")
-            {
-                quote! {
-                    let children = Box::new(|| ::leptos::Fragment::lazy(|| vec![
-                        ::leptos::SharedContext::with_hydration(move || {
-                            ::leptos::leptos_dom::html::custom(
-                                ::leptos::leptos_dom::html::Custom::new("leptos-children"),
-                            )
-                            .child(::leptos::SharedContext::no_hydration(children))
-                            .into_view()
-                        })
-                    ]));
-                }
-            } else {
-                quote! {}
-            };
             quote! {
                 #island_serialize_props
                 let #props_name {
                     #prop_names
                 } = props;
-                #wrapped_children
             }
         };
 
@@ -335,102 +313,44 @@ impl ToTokens for Model {
             #component
         };
 
-        let binding = if *is_island && cfg!(feature = "hydrate") {
-            let island_props = if is_island_with_children
-                || is_island_with_other_props
-            {
-                let (destructure, prop_builders) = if is_island_with_other_props
-                {
-                    let prop_names = props
-                        .iter()
-                        .filter_map(|prop| {
-                            if prop.name.ident == "children" {
-                                None
-                            } else {
-                                let name = &prop.name.ident;
-                                Some(quote! { #name, })
-                            }
-                        })
-                        .collect::<TokenStream>();
-                    let destructure = quote! {
-                        let #props_serialized_name {
-                            #prop_names
-                        } = props;
-                    };
-                    let prop_builders = props
-                        .iter()
-                        .filter_map(|prop| {
-                            if prop.name.ident == "children" {
-                                None
-                            } else {
-                                let name = &prop.name.ident;
-                                Some(quote! {
-                                    .#name(#name)
-                                })
-                            }
+        // creates the view of an island on the client, from the props that
+        // were serialized on the server
+        let binding = if *is_island {
+            let deserialize_props = if is_island_with_props {
+                quote! {
+                    let #props_serialized_name {
+                        #prop_names
+                    } = props
+                        .and_then(|props| {
+                            ::tachys::serde_json::from_str(props).ok()
                         })
-                        .collect::<TokenStream>();
-                    (destructure, prop_builders)
-                } else {
-                    (quote! {}, quote! {})
-                };
-                let children = if is_island_with_children {
-                    quote! {
-                        .children(Box::new(move || ::leptos::Fragment::lazy(|| vec![
-                            ::leptos::SharedContext::with_hydration(move || {
-                                ::leptos::leptos_dom::html::custom(
-                                    ::leptos::leptos_dom::html::Custom::new("leptos-children"),
-                                )
-                                .prop("$$owner", ::leptos::Owner::current().map(|n| n.as_ffi()))
-                                .into_view()
-                        })])))
-                    }
-                } else {
-                    quote! {}
-                };
-
-                quote! {{
-                    #destructure
-                    #props_name::builder()
-                        #prop_builders
-                        #children
-                        .build()
-                }}
+                        .expect("couldn't deserialize island props");
+                }
             } else {
-                quote! {}
-            };
-            let deserialize_island_props = if is_island_with_other_props {
                 quote! {
-                    let props = el.dataset().get(::leptos::wasm_bindgen::intern("props"))
-                        .and_then(|data| ::leptos::serde_json::from_str::<#props_serialized_name>(&data).ok())
-                        .expect("could not deserialize props");
+                    _ = props;
                 }
-            } else {
-                quote! {}
             };
 
             quote! {
-                #[::leptos::wasm_bindgen::prelude::wasm_bindgen]
+                #[doc(hidden)]
                 #[allow(non_snake_case)]
-                pub fn #hydrate_fn_name(el: ::leptos::web_sys::HtmlElement) {
-                    if let Some(Ok(key)) = el.dataset().get(::leptos::wasm_bindgen::intern("hkc")).map(|key| std::str::FromStr::from_str(&key)) {
-                        ::leptos::leptos_dom::HydrationCtx::continue_from(key);
-                    }
-                    #deserialize_island_props
-                    _ = ::leptos::run_as_child(move || {
-                        ::leptos::SharedContext::register_island(&el);
-                        ::leptos::leptos_dom::mount_to_with_stop_hydrating(el, false, move || {
-                            #name(#island_props)
-                        })
-                    });
+                #vis fn #hydrate_fn_name(props: Option<&str>) #ret {
+                    #deserialize_props
+                    ::tachys::tachy_reaccy::untrack(
+                        move || #body_name(#prop_names)
+                    )
                 }
             }
         } else {
             quote! {}
         };
 
-        let props_derive_serialize = if is_island_with_other_props {
-            quote! { , ::leptos::serde::Serialize }
+        let props_derive_serialize = if is_island_with_props {
+            quote! {
+                #[derive(::tachys::serde::Serialize)]
+                #[serde(crate = "::tachys::serde")]
+            }
         } else {
             quote! {}
         };
@@ -441,7 +361,8 @@ impl ToTokens for Model {
             #docs
             #[doc = ""]
             #component_fn_prop_docs
-            #[derive(::tachys::typed_builder_macro::TypedBuilder #props_derive_serialize)]
+            #[derive(::tachys::typed_builder_macro::TypedBuilder)]
+            #props_derive_serialize
             //#[builder(doc)]
             #[builder(crate_module_path=::tachys::typed_builder)]
             #[allow(non_snake_case)]
@@ -856,11 +777,7 @@ impl ToTokens for TypedBuilderOpts {
     }
 }
 
-fn prop_builder_fields(
-    vis: &Visibility,
-    props: &[Prop],
-    is_island_with_other_props: bool,
-) -> TokenStream {
+fn prop_builder_fields(vis: &Visibility, props: &[Prop]) -> TokenStream {
     props
         .iter()
         .map(|prop| {
@@ -882,12 +799,6 @@ fn prop_builder_fields(
             } else {
                 quote!()
             };
-            let skip_children_serde =
-                if is_island_with_other_props && name.ident == "children" {
-                    quote!(#[serde(skip)])
-                } else {
-                    quote!()
-                };
 
             let PatIdent { ident, by_ref, .. } = &name;
 
@@ -896,7 +807,6 @@ fn prop_builder_fields(
                 #builder_docs
                 #builder_attrs
                 #allow_missing_docs
-                #skip_children_serde
                 #vis #by_ref #ident: #ty,
             }
         })
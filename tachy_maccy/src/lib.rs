@@ -1,4 +1,5 @@
 mod component;
+mod server;
 mod view;
 use crate::component::unmodified_fn_name_from_fn_name;
 use component::DummyModel;
@@ -65,10 +66,82 @@ pub fn component(
     _args: proc_macro::TokenStream,
     s: TokenStream,
 ) -> TokenStream {
+    component_macro(s, false)
+}
+
+/// Defines a component that is hydrated on its own, as an island in a page
+/// that is otherwise static HTML.
+///
+/// On the server, the island is rendered inside a `<tachy-island>` element
+/// that records its name and serialized props, so its props must implement
+/// `Serialize` and `Deserialize`. Islands cannot take `children`.
+///
+/// The macro also defines a hidden `_island_{Name}` function, which creates
+/// the view of the island from its serialized props and can be registered
+/// in an `IslandRegistry` on the client.
+#[proc_macro_error::proc_macro_error]
+#[proc_macro_attribute]
+pub fn island(_args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
+    component_macro(s, true)
+}
+
+/// Defines a server function: an `async` function that only runs on the
+/// server, and that the client calls over HTTP.
+///
+/// The function must return `Result<T, ServerFnError>`. Its arguments are
+/// bundled into a struct named after the function in `PascalCase`, which
+/// implements `ServerFn` and can be registered in a `ServerFnRegistry` on the
+/// server. With the `ssr` feature of the calling crate enabled, the function
+/// runs its body directly; otherwise, it serializes its arguments and POSTs
+/// them to the server with the installed `Client`.
+///
+/// The calling crate must therefore declare an `ssr` feature, and enable it
+/// in its server build:
+///
+/// ```toml
+/// [features]
+/// ssr = []
+/// ```
+///
+/// Without it, `#[cfg(feature = "ssr")]` in the generated code is reported
+/// as an unexpected `cfg`, and the function always calls the server.
+///
+/// The attribute takes these optional arguments:
+/// - `prefix`: the path that the server function is served under, by
+///   default `"/api"`.
+/// - `endpoint`: the rest of the path, by default the name of the function.
+/// - `encoding`: how the arguments and the return value are serialized, one
+///   of `"SerdeJson"` (the default), `"Str"`, `"Miniserde"` or `"Rkyv"`. The
+///   return value must support the same encoding. `"Miniserde"` and `"Rkyv"`
+///   derive the traits of those crates for the arguments, so the calling
+///   crate must depend on `miniserde` or `rkyv` itself, and enable the
+///   feature of the same name on `tachy_reaccy`.
+///
+/// ```ignore
+/// #[server(encoding = "Str")]
+/// pub async fn double(n: i32) -> Result<i32, ServerFnError> {
+///     Ok(n * 2)
+/// }
+/// ```
+#[proc_macro_error::proc_macro_error]
+#[proc_macro_attribute]
+pub fn server(args: TokenStream, s: TokenStream) -> TokenStream {
+    let args = match syn::parse::<server::ServerFnArgs>(args) {
+        Ok(args) => args,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    match syn::parse::<syn::ItemFn>(s) {
+        Ok(body) => server::Model::new(args, body).into_token_stream().into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn component_macro(s: TokenStream, island: bool) -> TokenStream {
     let mut dummy = syn::parse::<DummyModel>(s.clone());
     let parse_result = syn::parse::<component::Model>(s);
 
     if let (Ok(ref mut unexpanded), Ok(model)) = (&mut dummy, parse_result) {
+        let model = if island { model.is_island() } else { model };
         let expanded = model.into_token_stream();
         if !matches!(unexpanded.vis, Visibility::Public(_)) {
             unexpanded.vis = Visibility::Public(Pub {
@@ -0,0 +1,314 @@
+use convert_case::{Case::Pascal, Casing};
+use proc_macro2::{Ident, TokenStream};
+use proc_macro_error::abort;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Expr, ExprLit, FnArg, GenericArgument, ItemFn, Lit, MetaNameValue, Pat,
+    PathArguments, ReturnType, Token, Type,
+};
+
+/// The arguments of the `#[server]` attribute, e.g.
+/// `#[server(prefix = "/api", endpoint = "story", encoding = "SerdeJson")]`.
+pub struct ServerFnArgs {
+    prefix: String,
+    endpoint: Option<String>,
+    encoding: Encoding,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Str,
+    SerdeJson,
+    Miniserde,
+    Rkyv,
+}
+
+impl Parse for ServerFnArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = ServerFnArgs {
+            prefix: "/api".to_string(),
+            endpoint: None,
+            encoding: Encoding::SerdeJson,
+        };
+        let pairs =
+            Punctuated::<MetaNameValue, Token![,]>::parse_terminated(input)?;
+        for pair in pairs {
+            let value = match &pair.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(value),
+                    ..
+                }) => value.value(),
+                other => {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        "expected a string literal",
+                    ))
+                }
+            };
+            if pair.path.is_ident("prefix") {
+                args.prefix = value;
+            } else if pair.path.is_ident("endpoint") {
+                args.endpoint = Some(value);
+            } else if pair.path.is_ident("encoding") {
+                args.encoding = match value.as_str() {
+                    "Str" => Encoding::Str,
+                    "SerdeJson" => Encoding::SerdeJson,
+                    "Miniserde" => Encoding::Miniserde,
+                    "Rkyv" => Encoding::Rkyv,
+                    _ => {
+                        return Err(syn::Error::new(
+                            pair.value.span(),
+                            "expected one of \"Str\", \"SerdeJson\", \
+                             \"Miniserde\" or \"Rkyv\"",
+                        ))
+                    }
+                };
+            } else {
+                return Err(syn::Error::new(
+                    pair.path.span(),
+                    "expected `prefix`, `endpoint` or `encoding`",
+                ));
+            }
+        }
+        Ok(args)
+    }
+}
+
+pub struct Model {
+    args: ServerFnArgs,
+    body: ItemFn,
+    fields: Vec<(Ident, Type)>,
+    output: Type,
+}
+
+impl Model {
+    pub fn new(args: ServerFnArgs, body: ItemFn) -> Self {
+        if body.sig.asyncness.is_none() {
+            abort!(body.sig.fn_token, "server functions must be `async`");
+        }
+        if !body.sig.generics.params.is_empty() {
+            abort!(
+                body.sig.generics,
+                "server functions cannot have generic parameters"
+            );
+        }
+
+        let fields = body
+            .sig
+            .inputs
+            .iter()
+            .map(|arg| match arg {
+                FnArg::Receiver(_) => {
+                    abort!(arg, "receiver not allowed in server functions")
+                }
+                FnArg::Typed(pat_type) => match &*pat_type.pat {
+                    Pat::Ident(pat) if pat.by_ref.is_none() => {
+                        (pat.ident.clone(), (*pat_type.ty).clone())
+                    }
+                    _ => abort!(
+                        pat_type.pat,
+                        "the arguments of server functions must be simple \
+                         identifiers"
+                    ),
+                },
+            })
+            .collect::<Vec<_>>();
+        if args.encoding == Encoding::Str && fields.len() != 1 {
+            abort!(
+                body.sig.inputs,
+                "server functions with the `Str` encoding must take exactly \
+                 one argument"
+            );
+        }
+
+        let output = server_fn_output(&body.sig.output);
+        Self {
+            args,
+            body,
+            fields,
+            output,
+        }
+    }
+}
+
+// the `T` in `Result<T, ServerFnError>`
+fn server_fn_output(ret: &ReturnType) -> Type {
+    let ReturnType::Type(_, ty) = ret else {
+        abort!(
+            ret,
+            "server functions must return `Result<T, ServerFnError>`"
+        );
+    };
+    let ok = match &**ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .filter(|segment| segment.ident == "Result")
+            .and_then(|segment| match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args.args.first(),
+                _ => None,
+            })
+            .and_then(|arg| match arg {
+                GenericArgument::Type(ok) => Some(ok.clone()),
+                _ => None,
+            }),
+        _ => None,
+    };
+    ok.unwrap_or_else(|| {
+        abort!(
+            ty,
+            "server functions must return `Result<T, ServerFnError>`"
+        )
+    })
+}
+
+impl ToTokens for Model {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            args,
+            body,
+            fields,
+            output,
+        } = self;
+        let vis = &body.vis;
+        let attrs = &body.attrs;
+        let sig = &body.sig;
+        let fn_name = &sig.ident;
+        let ret = &sig.output;
+        let block = &body.block;
+        let inputs = &sig.inputs;
+
+        let struct_name =
+            format_ident!("{}", fn_name.to_string().to_case(Pascal));
+        let body_name = format_ident!("__{}", fn_name);
+        let path = format!(
+            "{}/{}",
+            args.prefix.trim_end_matches('/'),
+            args.endpoint.clone().unwrap_or_else(|| fn_name.to_string())
+        );
+        let field_names =
+            fields.iter().map(|(name, _)| name).collect::<Vec<_>>();
+        let field_types = fields.iter().map(|(_, ty)| ty);
+        let struct_doc = format!(
+            " The arguments of the server function [`{fn_name}`], which is \
+             served at `{path}`."
+        );
+
+        let (encoding, derives, impls) = match args.encoding {
+            Encoding::Str => {
+                let (name, ty) = &fields[0];
+                (
+                    quote! { Str },
+                    quote! {},
+                    quote! {
+                        impl ::std::fmt::Display for #struct_name {
+                            fn fmt(
+                                &self,
+                                f: &mut ::std::fmt::Formatter<'_>,
+                            ) -> ::std::fmt::Result {
+                                ::std::fmt::Display::fmt(&self.#name, f)
+                            }
+                        }
+
+                        impl ::std::str::FromStr for #struct_name {
+                            type Err = <#ty as ::std::str::FromStr>::Err;
+
+                            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                                Ok(Self { #name: s.parse()? })
+                            }
+                        }
+                    },
+                )
+            }
+            Encoding::SerdeJson => (
+                quote! { SerdeJson },
+                quote! {
+                    #[derive(
+                        ::tachys::serde::Serialize,
+                        ::tachys::serde::Deserialize
+                    )]
+                    #[serde(crate = "::tachys::serde")]
+                },
+                quote! {},
+            ),
+            // miniserde's derives always refer to `::miniserde`, so these two
+            // encodings use the calling crate's own dependency
+            Encoding::Miniserde => (
+                quote! { Miniserde },
+                quote! {
+                    #[derive(::miniserde::Serialize, ::miniserde::Deserialize)]
+                },
+                quote! {},
+            ),
+            Encoding::Rkyv => (
+                quote! { Rkyv },
+                quote! {
+                    #[derive(
+                        ::rkyv::Archive,
+                        ::rkyv::Serialize,
+                        ::rkyv::Deserialize
+                    )]
+                    #[archive(check_bytes)]
+                },
+                quote! {},
+            ),
+        };
+
+        tokens.extend(quote! {
+            #[doc = #struct_doc]
+            #derives
+            #[derive(Debug, Clone)]
+            #vis struct #struct_name {
+                #(pub #field_names: #field_types),*
+            }
+
+            #impls
+
+            impl ::tachys::tachy_reaccy::server_fn::ServerFn for #struct_name {
+                const PATH: &'static str = #path;
+                type Encoding = ::tachys::tachy_reaccy::serialization::#encoding;
+                type Output = #output;
+
+                fn run_body(
+                    self,
+                ) -> ::tachys::tachy_reaccy::server_fn::ServerFnFuture<#output> {
+                    #[cfg(feature = "ssr")]
+                    {
+                        let Self { #(#field_names),* } = self;
+                        Box::pin(#body_name(#(#field_names),*))
+                    }
+                    #[cfg(not(feature = "ssr"))]
+                    {
+                        unreachable!(
+                            "server functions can only be run on the server"
+                        )
+                    }
+                }
+            }
+
+            #(#attrs)*
+            #vis async fn #fn_name(#inputs) #ret {
+                #[cfg(feature = "ssr")]
+                {
+                    #body_name(#(#field_names),*).await
+                }
+                #[cfg(not(feature = "ssr"))]
+                {
+                    ::tachys::tachy_reaccy::server_fn::call(#struct_name {
+                        #(#field_names),*
+                    })
+                    .await
+                }
+            }
+
+            #[cfg(feature = "ssr")]
+            #[doc(hidden)]
+            #[allow(clippy::too_many_arguments)]
+            async fn #body_name(#inputs) #ret #block
+        });
+    }
+}
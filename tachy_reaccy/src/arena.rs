@@ -1,7 +1,9 @@
+#[cfg(debug_assertions)]
+use crate::graph::NodeEntry;
 #[cfg(feature = "web")]
 use crate::shared_context::HydrateSharedContext;
 use crate::{
-    shared_context::{SharedContext, SsrSharedContext},
+    shared_context::{SerializedDataId, SharedContext, SsrSharedContext},
     signal_traits::{
         DefinedAt, SignalUpdateUntracked, SignalWithUntracked, Trigger,
     },
@@ -98,6 +100,52 @@ pub struct Owner {
 
 impl Owner {
     pub fn new() -> Self {
+        Self::new_with_position(Placement::Next)
+    }
+
+    /// Creates a new child of the current owner for an effect, memo or other
+    /// derived value, which does not take a position among its siblings.
+    ///
+    /// Instead, the owners and data created under it are positioned as if
+    /// they were created under the current owner. On the server, views are
+    /// rendered inline, while on the client they are wrapped in effects, so
+    /// this keeps the positions, and so the [`SerializedDataId`]s, the same
+    /// on both sides.
+    pub(crate) fn new_transparent() -> Self {
+        Self::new_with_position(Placement::Shared)
+    }
+
+    /// Creates a new child of the current owner at the given position in the
+    /// tree of owners, rather than at the next position among its siblings.
+    ///
+    /// This is used to hydrate part of a page on its own, like an island: the
+    /// client never creates the rest of the tree, so it uses the
+    /// [`position`](Owner::position) that the server recorded instead.
+    pub fn new_at(position: Vec<u16>) -> Self {
+        Self::new_with_position(Placement::At(position))
+    }
+
+    /// Runs the function under a new child of the current owner, at the next
+    /// position among its siblings, or directly if there is no current owner.
+    ///
+    /// The child is kept alive until the current owner is cleaned up. Each
+    /// component, and each reactive part of a view, is created like this, so
+    /// the data created in one of them is identified by its place in the view,
+    /// and a branch that creates more or less data on the client than on the
+    /// server does not change the IDs of data anywhere else.
+    pub fn with_child<T>(fun: impl FnOnce() -> T) -> T {
+        if Owner::current().is_none() {
+            return fun();
+        }
+        let owner = Owner::new();
+        Owner::on_cleanup({
+            let owner = owner.clone();
+            move || drop(owner)
+        });
+        owner.with(fun)
+    }
+
+    fn new_with_position(placement: Placement) -> Self {
         let (parent, shared_context) = {
             OWNER
                 .with(|o| {
@@ -107,17 +155,76 @@ impl Owner {
                 })
                 .unzip()
         };
+        let parent = parent.and_then(|parent| parent.upgrade());
+        let shares_position = matches!(placement, Placement::Shared);
+        let position = match placement {
+            Placement::At(position) => position,
+            Placement::Next => parent
+                .as_ref()
+                .map(|parent| positioned(parent).write().next_child_position())
+                .unwrap_or_default(),
+            Placement::Shared => parent
+                .as_ref()
+                .map(|parent| parent.read().position.clone())
+                .unwrap_or_default(),
+        };
+        let inner = Arc::new(RwLock::new(OwnerInner {
+            parent: parent.as_ref().map(Arc::downgrade),
+            position,
+            shares_position,
+            next_child: 0,
+            next_data: 0,
+            nodes: Default::default(),
+            contexts: Default::default(),
+            cleanups: Default::default(),
+            children: Default::default(),
+            #[cfg(debug_assertions)]
+            graph_nodes: Default::default(),
+        }));
+        if let Some(parent) = parent {
+            parent.write().add_child(Arc::downgrade(&inner));
+        }
         Self {
-            inner: Arc::new(RwLock::new(OwnerInner {
-                parent,
-                nodes: Default::default(),
-                contexts: Default::default(),
-                cleanups: Default::default(),
-            })),
+            inner,
             shared_context: shared_context.flatten(),
         }
     }
 
+    /// The position of this owner in the tree of owners: the index of each of
+    /// its ancestors, and then of itself, among the children of its parent.
+    ///
+    /// Because it does not depend on what has been created elsewhere in the
+    /// tree, the same owner has the same position on the server and on the
+    /// client. The owners of effects and memos, which may only exist on the
+    /// client, share the position of their parent instead of taking one.
+    pub fn position(&self) -> Vec<u16> {
+        self.inner.read().position.clone()
+    }
+
+    /// Returns the ID for the next piece of data serialized from the server
+    /// to the client under this owner: its position, followed by the index
+    /// of the data among the data created under it.
+    pub(crate) fn next_data_id(&self) -> SerializedDataId {
+        let positioned = positioned(&self.inner);
+        let mut inner = positioned.write();
+        let mut id = inner.position.clone();
+        id.push(inner.next_data);
+        inner.next_data = inner.next_data.wrapping_add(1);
+        SerializedDataId::new(id)
+    }
+
+    /// Disposes of this owner and all of its descendants, depth-first.
+    ///
+    /// For each owner, starting with the most deeply nested, this runs the
+    /// callbacks registered with [`on_cleanup`] and disposes of any values
+    /// that were stored in the arena while it was the current owner. Effects
+    /// that were created under this owner stop running.
+    ///
+    /// The owner can continue to be used after it has been disposed.
+    pub fn dispose(&self) {
+        dispose(&self.inner);
+    }
+
     pub fn with<T>(&self, fun: impl FnOnce() -> T) -> T {
         let prev = {
             OWNER.with(|o| {
@@ -131,17 +238,12 @@ impl Owner {
         val
     }
 
+    /// Cleans up this owner, as when it is disposed, and then runs the
+    /// function with it as the current owner. This is used to rerun effects
+    /// and memos, so that whatever they created during their last run,
+    /// including any child owners, is cleaned up first.
     pub fn with_cleanup<T>(&self, fun: impl FnOnce() -> T) -> T {
-        let (cleanups, nodes) = {
-            let mut lock = self.inner.write();
-            (mem::take(&mut lock.cleanups), mem::take(&mut lock.nodes))
-        };
-        for cleanup in cleanups {
-            cleanup();
-        }
-        for node in nodes {
-            _ = MAP.write().remove(node);
-        }
+        dispose(&self.inner);
 
         self.with(fun)
     }
@@ -160,6 +262,8 @@ impl Owner {
         }
     }
 
+    /// Registers a function to run when the current owner is cleaned up or
+    /// disposed. See [`on_cleanup`].
     pub fn on_cleanup(fun: impl FnOnce() + Send + Sync + 'static) {
         if let Some(owner) = Owner::current() {
             owner.inner.write().cleanups.push(Box::new(fun));
@@ -175,33 +279,168 @@ impl Owner {
     }
 }
 
+/// Registers a function to run when the current [`Owner`] is cleaned up.
+///
+/// This happens before an effect or memo reruns, and when its owner is
+/// disposed or dropped. It can be used to release timers, subscriptions and
+/// other resources that were acquired by a component or effect.
+///
+/// Does nothing if there is no current owner.
+pub fn on_cleanup(fun: impl FnOnce() + Send + Sync + 'static) {
+    Owner::on_cleanup(fun)
+}
+
+/// Where a new owner is placed in the tree of owners.
+enum Placement {
+    /// At the next position among the children of its parent.
+    Next,
+    /// At the given position.
+    At(Vec<u16>),
+    /// At the position of its parent, which also places its children.
+    Shared,
+}
+
+/// Returns the owner that places the children and data of this one: itself,
+/// or, if it shares the position of its parent, its nearest ancestor that
+/// does not.
+fn positioned(inner: &Arc<RwLock<OwnerInner>>) -> Arc<RwLock<OwnerInner>> {
+    let mut current = Arc::clone(inner);
+    loop {
+        let parent = {
+            let lock = current.read();
+            if !lock.shares_position {
+                break;
+            }
+            lock.parent.as_ref().and_then(Weak::upgrade)
+        };
+        match parent {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+    current
+}
+
 #[derive(Default)]
 pub(crate) struct OwnerInner {
     pub parent: Option<Weak<RwLock<OwnerInner>>>,
+    position: Vec<u16>,
+    shares_position: bool,
+    next_child: u16,
+    next_data: u16,
     nodes: Vec<NodeId>,
     pub contexts: FxHashMap<TypeId, Box<dyn Any + Send + Sync>>,
     pub cleanups: Vec<Box<dyn FnOnce() + Send + Sync>>,
+    children: Vec<Weak<RwLock<OwnerInner>>>,
+    // the reactive nodes created under this owner, for debugging
+    #[cfg(debug_assertions)]
+    graph_nodes: Vec<Weak<NodeEntry>>,
+}
+
+impl OwnerInner {
+    fn next_child_position(&mut self) -> Vec<u16> {
+        let mut position = self.position.clone();
+        position.push(self.next_child);
+        self.next_child = self.next_child.wrapping_add(1);
+        position
+    }
+
+    fn add_child(&mut self, child: Weak<RwLock<OwnerInner>>) {
+        // children are only held weakly, so forget those that have been dropped
+        self.children.retain(|child| child.strong_count() > 0);
+        self.children.push(child);
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Owner {
+    pub(crate) fn add_graph_node(&self, node: &Arc<NodeEntry>) {
+        let mut lock = self.inner.write();
+        // forget dead nodes before growing, rather than on every insertion
+        if lock.graph_nodes.len() == lock.graph_nodes.capacity() {
+            lock.graph_nodes.retain(|node| {
+                node.upgrade().map(|node| node.is_alive()).unwrap_or(false)
+            });
+        }
+        lock.graph_nodes.push(Arc::downgrade(node));
+    }
+
+    /// Returns the live reactive nodes created under this owner or any of
+    /// its descendants.
+    pub(crate) fn graph_nodes(&self) -> Vec<Arc<NodeEntry>> {
+        fn collect(
+            inner: &RwLock<OwnerInner>,
+            nodes: &mut Vec<Arc<NodeEntry>>,
+        ) {
+            let lock = inner.read();
+            nodes.extend(
+                lock.graph_nodes
+                    .iter()
+                    .filter_map(Weak::upgrade)
+                    .filter(|node| node.is_alive()),
+            );
+            for child in lock.children.iter().filter_map(Weak::upgrade) {
+                collect(&child, nodes);
+            }
+        }
+
+        let mut nodes = Vec::new();
+        collect(&self.inner, &mut nodes);
+        nodes
+    }
+}
+
+fn dispose(inner: &RwLock<OwnerInner>) {
+    let (children, cleanups, nodes) = {
+        let mut lock = inner.write();
+        (
+            mem::take(&mut lock.children),
+            mem::take(&mut lock.cleanups),
+            mem::take(&mut lock.nodes),
+        )
+    };
+    dispose_children(children);
+    run_cleanups(cleanups, nodes);
+}
+
+fn dispose_children(children: Vec<Weak<RwLock<OwnerInner>>>) {
+    for child in children {
+        if let Some(child) = child.upgrade() {
+            dispose(&child);
+        }
+    }
+}
+
+fn run_cleanups(
+    cleanups: Vec<Box<dyn FnOnce() + Send + Sync>>,
+    nodes: Vec<NodeId>,
+) {
+    for cleanup in cleanups {
+        cleanup();
+    }
+    for node in nodes {
+        _ = MAP.write().remove(node);
+    }
 }
 
 impl Debug for OwnerInner {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("OwnerInner")
             .field("parent", &self.parent)
+            .field("position", &self.position)
+            .field("shares_position", &self.shares_position)
             .field("nodes", &self.nodes)
             .field("contexts", &self.contexts)
             .field("cleanups", &self.cleanups.len())
+            .field("children", &self.children.len())
             .finish()
     }
 }
 
 impl Drop for OwnerInner {
     fn drop(&mut self) {
-        for cleanup in mem::take(&mut self.cleanups) {
-            cleanup();
-        }
-        for node in mem::take(&mut self.nodes) {
-            _ = MAP.write().remove(node);
-        }
+        dispose_children(mem::take(&mut self.children));
+        run_cleanups(mem::take(&mut self.cleanups), mem::take(&mut self.nodes));
     }
 }
 
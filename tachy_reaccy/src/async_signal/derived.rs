@@ -1,6 +1,7 @@
-use super::{AsyncState, ScopedFuture};
+use super::{AsyncError, AsyncState, ScopedFuture};
 use crate::{
     arena::{Owner, Stored, StoredData},
+    graph::{self, NodeKind},
     notify::{channel, Sender},
     prelude::{DefinedAt, SignalWithUntracked},
     source::{
@@ -10,10 +11,13 @@ use crate::{
     spawn::{spawn, spawn_local},
     unwrap_signal,
 };
-use futures::{FutureExt, StreamExt};
+use futures::{
+    future::{AbortHandle, Abortable},
+    FutureExt, StreamExt,
+};
 use parking_lot::RwLock;
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Display},
     future::{Future, IntoFuture},
     mem,
     panic::Location,
@@ -61,27 +65,48 @@ struct ArcAsyncDerivedInner {
     subscribers: SubscriberSet,
     // when a source changes, notifying this will cause the async work to rerun
     notifier: Sender,
+    // aborts the future that is currently running, if any
+    abort: Option<AbortHandle>,
+    // incremented whenever a new future starts, so that a future that
+    // finishes after it has been superseded doesn't overwrite the new value
+    version: usize,
+}
+
+impl Drop for ArcAsyncDerivedInner {
+    fn drop(&mut self) {
+        if let Some(abort) = self.abort.take() {
+            abort.abort();
+        }
+    }
 }
 
 // This implemented creating a derived async signal.
 // It needs to be implemented as a macro because it needs to be flexible over
 // whether `fun` returns a `Future` that is `Send + Sync`. Doing it as a function would,
 // as far as I can tell, require repeating most of the function body.
+//
+// `$complete` turns the output of each run into the new state, which lets
+// resources report errors as well as values.
 macro_rules! spawn_derived {
-    ($spawner:ident, $initial:ident, $fun:ident) => {{
+    ($spawner:ident, $initial:ident, $fun:ident, $complete:expr) => {{
         let (mut notifier, mut rx) = channel();
 
         // begin loading eagerly but asynchronously, if not already loaded
         if matches!($initial, AsyncState::Loading) {
             notifier.notify();
         }
-        let is_ready = matches!($initial, AsyncState::Complete(_));
+        let is_ready = matches!(
+            $initial,
+            AsyncState::Complete(_) | AsyncState::Error(_)
+        );
 
         let inner = Arc::new(RwLock::new(ArcAsyncDerivedInner {
-            owner: Owner::new(),
+            owner: Owner::new_transparent(),
             notifier,
             sources: SourceSet::new(),
             subscribers: SubscriberSet::new(),
+            abort: None,
+            version: 0,
         }));
         let value = Arc::new(RwLock::new($initial));
         let wakers = Arc::new(RwLock::new(Vec::new()));
@@ -94,6 +119,13 @@ macro_rules! spawn_derived {
             inner: Arc::clone(&inner),
         };
         let any_subscriber = this.to_any_subscriber();
+        graph::register(
+            NodeKind::AsyncDerived,
+            this.defined_at(),
+            None,
+            Some(this.to_any_source()),
+            Some(any_subscriber.clone()),
+        );
 
         // if it's immediately available, poll once
         // this means either
@@ -122,12 +154,27 @@ macro_rules! spawn_derived {
                                 any_subscriber
                                     .with_observer(|| ScopedFuture::new($fun()))
                             });
+                            #[cfg(feature = "testing")]
+                            crate::testing::record_run(&any_subscriber);
+
+                            // cancel the previous run, if it's still going
+                            let (abort, registration) = AbortHandle::new_pair();
+                            let version = {
+                                let mut inner = inner.write();
+                                if let Some(prev) = inner.abort.replace(abort) {
+                                    prev.abort();
+                                }
+                                inner.version += 1;
+                                inner.version
+                            };
 
-                            // update state from Complete to Reloading
+                            // update state from Complete to Reloading, keeping
+                            // the last value if the previous run was reloading
                             {
                                 let mut value = value.write();
                                 // if it's initial Loading, it will just reset to Loading
-                                if let AsyncState::Complete(old) =
+                                if let AsyncState::Complete(old)
+                                | AsyncState::Reloading(old) =
                                     mem::take(&mut *value)
                                 {
                                     *value = AsyncState::Reloading(old);
@@ -139,19 +186,45 @@ macro_rules! spawn_derived {
                                 sub.mark_check();
                             }
 
-                            // generate and assign new value
-                            let new_value = fut.await;
-                            *value.write() = AsyncState::Complete(new_value);
+                            // run the new future alongside this loop, so that
+                            // it can be aborted if a source changes again
+                            let fut = Abortable::new(fut, registration);
+                            let value = Arc::downgrade(&value);
+                            let inner = Arc::downgrade(&inner);
+                            let wakers = Arc::downgrade(&wakers);
+                            $spawner(async move {
+                                let Ok(new_value) = fut.await else {
+                                    return;
+                                };
+                                let (Some(value), Some(inner), Some(wakers)) =
+                                    (value.upgrade(), inner.upgrade(), wakers.upgrade())
+                                else {
+                                    return;
+                                };
+
+                                // discard the value if a newer run has started
+                                // since, even if this one wasn't aborted in time
+                                {
+                                    let mut lock = inner.write();
+                                    if lock.version != version {
+                                        return;
+                                    }
+                                    lock.abort = None;
+                                }
+
+                                // assign new value
+                                *value.write() = $complete(new_value);
 
-                            // notify reactive subscribers that we're not loading any more
-                            for sub in (&inner.read().subscribers).into_iter() {
-                                sub.mark_check();
-                            }
+                                // notify reactive subscribers that we're not loading any more
+                                for sub in (&inner.read().subscribers).into_iter() {
+                                    sub.mark_check();
+                                }
 
-                            // notify async .awaiters
-                            for waker in mem::take(&mut *wakers.write()) {
-                                waker.wake();
-                            }
+                                // notify async .awaiters
+                                for waker in mem::take(&mut *wakers.write()) {
+                                    waker.wake();
+                                }
+                            });
                         }
                         _ => break,
                     }
@@ -196,7 +269,46 @@ impl<T: 'static> ArcAsyncDerived<T> {
         T: Send + Sync + 'static,
         Fut: Future<Output = T> + Send + Sync + 'static,
     {
-        spawn_derived!(spawn, initial_value, fun)
+        spawn_derived!(spawn, initial_value, fun, AsyncState::Complete)
+    }
+
+    /// Like [`ArcAsyncDerived::new`], but the async work can fail. A run that
+    /// returns `Err` sets the state to [`AsyncState::Error`], with an
+    /// [`AsyncError::Failed`] holding the error's message.
+    #[track_caller]
+    pub fn new_fallible<Fut, E>(
+        fun: impl Fn() -> Fut + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + Sync + 'static,
+        E: Display,
+    {
+        Self::new_with_initial_state(AsyncState::Loading, move || {
+            let fut = fun();
+            async move {
+                match fut.await {
+                    Ok(value) => AsyncState::Complete(value),
+                    Err(e) => {
+                        AsyncState::Error(AsyncError::Failed(e.to_string()))
+                    }
+                }
+            }
+        })
+    }
+
+    /// Like [`ArcAsyncDerived::new_with_initial`], but each run resolves to
+    /// the new state of the signal, rather than to its value.
+    #[track_caller]
+    pub(crate) fn new_with_initial_state<Fut>(
+        initial_value: AsyncState<T>,
+        fun: impl Fn() -> Fut + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+        Fut: Future<Output = AsyncState<T>> + Send + Sync + 'static,
+    {
+        spawn_derived!(spawn, initial_value, fun, |state| state)
     }
 
     #[track_caller]
@@ -217,7 +329,14 @@ impl<T: 'static> ArcAsyncDerived<T> {
         T: 'static,
         Fut: Future<Output = T> + 'static,
     {
-        spawn_derived!(spawn_local, initial_value, fun)
+        spawn_derived!(spawn_local, initial_value, fun, AsyncState::Complete)
+    }
+
+    /// Reruns the async function, even if none of its sources have changed.
+    ///
+    /// If the previous run hasn't finished yet, it is cancelled.
+    pub fn refetch(&self) {
+        self.inner.write().notifier.notify();
     }
 
     pub fn ready(&self) -> AsyncDerivedReadyFuture<T> {
@@ -227,6 +346,17 @@ impl<T: 'static> ArcAsyncDerived<T> {
             wakers: Arc::clone(&self.wakers),
         }
     }
+
+    /// Returns a [`Future`] that resolves with the value once it has loaded,
+    /// or with the error if it failed to load. Awaiting the signal directly
+    /// waits for a value instead.
+    pub fn result(&self) -> AsyncDerivedResultFuture<T> {
+        AsyncDerivedResultFuture {
+            source: self.to_any_source(),
+            value: Arc::clone(&self.value),
+            wakers: Arc::clone(&self.wakers),
+        }
+    }
 }
 
 impl<T> SignalWithUntracked for ArcAsyncDerived<T> {
@@ -335,6 +465,10 @@ impl Source for RwLock<ArcAsyncDerivedInner> {
     fn clear_subscribers(&self) {
         self.write().subscribers.take();
     }
+
+    fn subscribers(&self) -> Vec<AnySubscriber> {
+        (&self.read().subscribers).into_iter().cloned().collect()
+    }
 }
 
 impl Subscriber for RwLock<ArcAsyncDerivedInner> {
@@ -345,10 +479,14 @@ impl Subscriber for RwLock<ArcAsyncDerivedInner> {
     fn clear_sources(&self, subscriber: &AnySubscriber) {
         self.write().sources.clear_sources(subscriber);
     }
+
+    fn sources(&self) -> Vec<AnySource> {
+        (&self.read().sources).into_iter().cloned().collect()
+    }
 }
 
 /// A [`Future`] that is ready when an [`ArcAsyncDerived`] is finished loading or reloading,
-/// but does not contain its value.
+/// or has failed to load, but does not contain its value.
 pub struct AsyncDerivedReadyFuture<T> {
     source: AnySource,
     value: Arc<RwLock<AsyncState<T>>>,
@@ -366,13 +504,16 @@ impl<T: 'static> Future for AsyncDerivedReadyFuture<T> {
                 self.wakers.write().push(waker.clone());
                 Poll::Pending
             }
-            AsyncState::Complete(_) => Poll::Ready(()),
+            AsyncState::Complete(_) | AsyncState::Error(_) => Poll::Ready(()),
         }
     }
 }
 
 /// A [`Future`] that is ready when an [`ArcAsyncDerived`] is finished loading or reloading,
 /// and contains its value.
+///
+/// If the value failed to load, this waits until it is loaded again. Use
+/// [`ArcAsyncDerived::result`] to resolve with the error instead.
 pub struct AsyncDerivedFuture<T> {
     source: AnySource,
     value: Arc<RwLock<AsyncState<T>>>,
@@ -399,7 +540,9 @@ impl<T: Clone + 'static> Future for AsyncDerivedFuture<T> {
         let waker = cx.waker();
         self.source.track();
         match &*self.value.read() {
-            AsyncState::Loading | AsyncState::Reloading(_) => {
+            AsyncState::Loading
+            | AsyncState::Reloading(_)
+            | AsyncState::Error(_) => {
                 self.wakers.write().push(waker.clone());
                 Poll::Pending
             }
@@ -408,6 +551,31 @@ impl<T: Clone + 'static> Future for AsyncDerivedFuture<T> {
     }
 }
 
+/// A [`Future`] that is ready when an [`ArcAsyncDerived`] is finished loading or reloading,
+/// or has failed to load, and contains its value or the error.
+pub struct AsyncDerivedResultFuture<T> {
+    source: AnySource,
+    value: Arc<RwLock<AsyncState<T>>>,
+    wakers: Arc<RwLock<Vec<Waker>>>,
+}
+
+impl<T: Clone + 'static> Future for AsyncDerivedResultFuture<T> {
+    type Output = Result<T, AsyncError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let waker = cx.waker();
+        self.source.track();
+        match &*self.value.read() {
+            AsyncState::Loading | AsyncState::Reloading(_) => {
+                self.wakers.write().push(waker.clone());
+                Poll::Pending
+            }
+            AsyncState::Complete(value) => Poll::Ready(Ok(value.clone())),
+            AsyncState::Error(e) => Poll::Ready(Err(e.clone())),
+        }
+    }
+}
+
 pub struct AsyncDerived<T: Send + Sync + 'static> {
     inner: Stored<ArcAsyncDerived<T>>,
 }
@@ -425,6 +593,7 @@ impl<T: Send + Sync + 'static> StoredData for AsyncDerived<T> {
 }
 
 impl<T: Send + Sync + 'static> AsyncDerived<T> {
+    #[track_caller]
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "debug", skip_all,)
@@ -439,6 +608,7 @@ impl<T: Send + Sync + 'static> AsyncDerived<T> {
         }
     }
 
+    #[track_caller]
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "debug", skip_all,)
@@ -459,6 +629,40 @@ impl<T: Send + Sync + 'static> AsyncDerived<T> {
         }
     }
 
+    /// Like [`AsyncDerived::new`], but the async work can fail. See
+    /// [`ArcAsyncDerived::new_fallible`].
+    #[track_caller]
+    pub fn new_fallible<Fut, E>(
+        fun: impl Fn() -> Fut + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + Sync + 'static,
+        E: Display,
+    {
+        Self {
+            inner: Stored::new(ArcAsyncDerived::new_fallible(fun)),
+        }
+    }
+
+    #[track_caller]
+    pub(crate) fn new_with_initial_state<Fut>(
+        initial_value: AsyncState<T>,
+        fun: impl Fn() -> Fut + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+        Fut: Future<Output = AsyncState<T>> + Send + Sync + 'static,
+    {
+        Self {
+            inner: Stored::new(ArcAsyncDerived::new_with_initial_state(
+                initial_value,
+                fun,
+            )),
+        }
+    }
+
+    #[track_caller]
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "debug", skip_all,)
@@ -473,6 +677,7 @@ impl<T: Send + Sync + 'static> AsyncDerived<T> {
         }
     }
 
+    #[track_caller]
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "debug", skip_all,)
@@ -493,11 +698,28 @@ impl<T: Send + Sync + 'static> AsyncDerived<T> {
         }
     }
 
+    /// Reruns the async function, even if none of its sources have changed.
+    ///
+    /// If the previous run hasn't finished yet, it is cancelled.
+    #[track_caller]
+    pub fn refetch(&self) {
+        let this = self.inner.get().unwrap_or_else(unwrap_signal!(self));
+        this.refetch()
+    }
+
     #[track_caller]
     pub fn ready(&self) -> AsyncDerivedReadyFuture<T> {
         let this = self.inner.get().unwrap_or_else(unwrap_signal!(self));
         this.ready()
     }
+
+    /// Returns a [`Future`] that resolves with the value or the error. See
+    /// [`ArcAsyncDerived::result`].
+    #[track_caller]
+    pub fn result(&self) -> AsyncDerivedResultFuture<T> {
+        let this = self.inner.get().unwrap_or_else(unwrap_signal!(self));
+        this.result()
+    }
 }
 
 impl<T: Send + Sync + 'static> Copy for AsyncDerived<T> {}
@@ -1,6 +1,10 @@
 mod derived;
 mod resource;
-use crate::{arena::Owner, source::AnySubscriber, Observer};
+mod stream_resource;
+use crate::{
+    arena::Owner, shared_context::SerializationError, source::AnySubscriber,
+    Observer,
+};
 pub use derived::*;
 use futures::Future;
 use pin_project_lite::pin_project;
@@ -9,6 +13,8 @@ use std::{
     pin::Pin,
     task::{Context, Poll},
 };
+pub use stream_resource::*;
+use thiserror::Error;
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub enum AsyncState<T> {
@@ -16,16 +22,39 @@ pub enum AsyncState<T> {
     Loading,
     Complete(T),
     Reloading(T),
+    /// The value could not be loaded. Running the async work again (for
+    /// example, because one of its sources changed) resets this to `Loading`.
+    Error(AsyncError),
+}
+
+/// The reason an async value could not be loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AsyncError {
+    /// The value was loaded on the server, but the server could not serialize
+    /// it to send it to the client.
+    #[error("the server could not serialize this value: {0}")]
+    Serialization(#[from] SerializationError),
+    /// The async work itself failed, with this message. See
+    /// [`ArcAsyncDerived::new_fallible`].
+    #[error("{0}")]
+    Failed(String),
 }
 
 impl<T> AsyncState<T> {
     pub fn current_value(&self) -> Option<&T> {
         match &self {
-            AsyncState::Loading => None,
+            AsyncState::Loading | AsyncState::Error(_) => None,
             AsyncState::Complete(val) | AsyncState::Reloading(val) => Some(val),
         }
     }
 
+    pub fn error(&self) -> Option<&AsyncError> {
+        match &self {
+            AsyncState::Error(err) => Some(err),
+            _ => None,
+        }
+    }
+
     pub fn loading(&self) -> bool {
         matches!(&self, AsyncState::Loading | AsyncState::Reloading(_))
     }
@@ -1,6 +1,8 @@
 use super::{ArcAsyncDerived, AsyncDerived, AsyncDerivedFuture, AsyncState};
 #[cfg(feature = "miniserde")]
 use crate::serialization::Miniserde;
+#[cfg(feature = "postcard")]
+use crate::serialization::Postcard;
 #[cfg(feature = "rkyv")]
 use crate::serialization::Rkyv;
 #[cfg(feature = "serde-lite")]
@@ -9,10 +11,12 @@ use crate::{
     arena::Owner,
     prelude::SignalWithUntracked,
     serialization::{SerdeJson, SerializableData, Serializer, Str},
-    shared_context::SerializedDataId,
+    shared_context::{SerializationError, SerializedDataId},
+    PinnedFuture,
 };
 use core::{fmt::Debug, marker::PhantomData};
 use futures::Future;
+use parking_lot::Mutex;
 use std::{future::IntoFuture, ops::Deref};
 
 pub struct ArcResource<T, Ser> {
@@ -95,9 +99,9 @@ where
 }
 
 #[cfg(feature = "rkyv")]
-impl<T> ArcResource<T, SerdeLite>
+impl<T> ArcResource<T, Rkyv>
 where
-    T: SerializableData<SerdeLite>,
+    T: SerializableData<Rkyv>,
     T::SerErr: Debug,
     T::DeErr: Debug,
 {
@@ -110,6 +114,24 @@ where
     }
 }
 
+#[cfg(feature = "postcard")]
+impl<T> ArcResource<T, Postcard>
+where
+    T: SerializableData<Postcard>,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_postcard<Fut>(
+        fun: impl Fn() -> Fut + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+        Fut: Future<Output = T> + Send + Sync + 'static,
+    {
+        ArcResource::new_with_encoding(fun)
+    }
+}
+
 impl<T, Ser> ArcResource<T, Ser>
 where
     Ser: Serializer,
@@ -129,8 +151,9 @@ where
             .unwrap_or_default();
 
         let initial = Self::initial_value(&id);
+        let fun = hydrate_or_fetch::<T, Ser, Fut>(&id, &initial, fun);
 
-        let data = ArcAsyncDerived::new_with_initial(initial, fun);
+        let data = ArcAsyncDerived::new_with_initial_state(initial, fun);
 
         if let Some(shared_context) = Owner::shared_context() {
             let value = data.clone();
@@ -140,12 +163,15 @@ where
                 id,
                 Box::pin(async move {
                     ready_fut.await;
-                    value
-                        .with_untracked(|data| match &data {
-                            AsyncState::Complete(val) => val.ser(),
-                            _ => unreachable!(),
-                        })
-                        .unwrap() // TODO handle
+                    value.with_untracked(|data| match &data {
+                        AsyncState::Complete(val) => val
+                            .ser()
+                            .map_err(|e| SerializationError(format!("{e:?}"))),
+                        AsyncState::Error(e) => {
+                            Err(SerializationError(e.to_string()))
+                        }
+                        _ => unreachable!(),
+                    })
                 }),
             );
         }
@@ -163,6 +189,10 @@ where
             if let Some(shared_context) = Owner::shared_context() {
                 let value = shared_context.read_data(id);
                 if let Some(value) = value {
+                    let value = match value {
+                        Ok(value) => value,
+                        Err(e) => return AsyncState::Error(e.into()),
+                    };
                     match T::de(&value) {
                         Ok(value) => return AsyncState::Complete(value),
                         Err(e) => {
@@ -299,6 +329,24 @@ where
     }
 }
 
+#[cfg(feature = "postcard")]
+impl<T> Resource<T, Postcard>
+where
+    T: SerializableData<Postcard> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_postcard<Fut>(
+        fun: impl Fn() -> Fut + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+        Fut: Future<Output = T> + Send + Sync + 'static,
+    {
+        Resource::new_with_encoding(fun)
+    }
+}
+
 impl<T, Ser> Resource<T, Ser>
 where
     Ser: Serializer,
@@ -318,8 +366,9 @@ where
             .unwrap_or_default();
 
         let initial = Self::initial_value(&id);
+        let fun = hydrate_or_fetch::<T, Ser, Fut>(&id, &initial, fun);
 
-        let data = AsyncDerived::new_with_initial(initial, fun);
+        let data = AsyncDerived::new_with_initial_state(initial, fun);
 
         if let Some(shared_context) = Owner::shared_context() {
             let value = data;
@@ -329,12 +378,15 @@ where
                 id,
                 Box::pin(async move {
                     ready_fut.await;
-                    value
-                        .with_untracked(|data| match &data {
-                            AsyncState::Complete(val) => val.ser(),
-                            _ => unreachable!(),
-                        })
-                        .unwrap() // TODO handle
+                    value.with_untracked(|data| match &data {
+                        AsyncState::Complete(val) => val
+                            .ser()
+                            .map_err(|e| SerializationError(format!("{e:?}"))),
+                        AsyncState::Error(e) => {
+                            Err(SerializationError(e.to_string()))
+                        }
+                        _ => unreachable!(),
+                    })
                 }),
             );
         }
@@ -352,6 +404,10 @@ where
             if let Some(shared_context) = Owner::shared_context() {
                 let value = shared_context.read_data(id);
                 if let Some(value) = value {
+                    let value = match value {
+                        Ok(value) => value,
+                        Err(e) => return AsyncState::Error(e.into()),
+                    };
                     match T::de(&value) {
                         Ok(value) => return AsyncState::Complete(value),
                         Err(e) => {
@@ -378,3 +434,45 @@ where
         self.data.into_future()
     }
 }
+
+/// Wraps the async function of a resource, so that if the resource is still
+/// loading on the server while the client hydrates, its first run on the
+/// client waits for the server to stream its value, rather than running the
+/// async function again.
+fn hydrate_or_fetch<T, Ser, Fut>(
+    id: &SerializedDataId,
+    initial: &AsyncState<T>,
+    fun: impl Fn() -> Fut + Send + Sync + 'static,
+) -> impl Fn() -> PinnedFuture<AsyncState<T>> + Send + Sync + 'static
+where
+    Ser: Serializer,
+    T: SerializableData<Ser> + Send + Sync + 'static,
+    T::DeErr: Debug,
+    Fut: Future<Output = T> + Send + Sync + 'static,
+{
+    let pending = matches!(initial, AsyncState::Loading)
+        .then(|| Owner::shared_context()?.await_data(id))
+        .flatten();
+    let pending = Mutex::new(pending);
+
+    move || {
+        let pending = pending.lock().take();
+        // calling `fun` tracks the resource's sources, even when the server
+        // provides the value
+        let fut = fun();
+        Box::pin(async move {
+            if let Some(pending) = pending {
+                match pending.await {
+                    Ok(value) => match T::de(&value) {
+                        Ok(value) => return AsyncState::Complete(value),
+                        Err(e) => crate::log(&format!(
+                            "couldn't deserialize from {value:?}: {e:?}"
+                        )),
+                    },
+                    Err(e) => return AsyncState::Error(e.into()),
+                }
+            }
+            AsyncState::Complete(fut.await)
+        })
+    }
+}
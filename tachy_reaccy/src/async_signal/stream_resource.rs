@@ -0,0 +1,420 @@
+#[cfg(feature = "miniserde")]
+use crate::serialization::Miniserde;
+#[cfg(feature = "postcard")]
+use crate::serialization::Postcard;
+#[cfg(feature = "rkyv")]
+use crate::serialization::Rkyv;
+#[cfg(feature = "serde-lite")]
+use crate::serialization::SerdeLite;
+use crate::{
+    arena::{Owner, Stored},
+    prelude::{DefinedAt, SignalUpdate},
+    serialization::{SerdeJson, SerializableData, Serializer, Str},
+    shared_context::SerializationError,
+    signal::{ArcReadSignal, ArcRwSignal, ReadSignal},
+    spawn::spawn,
+    unwrap_signal, PinnedStream,
+};
+use core::{fmt::Debug, marker::PhantomData};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{AbortHandle, Abortable, Shared},
+    Future, FutureExt, Stream, StreamExt,
+};
+use std::ops::Deref;
+
+/// A reactive list of the items yielded by a [`Stream`], for data that
+/// arrives progressively, like search results or the tail of a log.
+///
+/// On the server, each item is serialized and sent to the client as soon as
+/// it arrives. A hydrating client replays the items that were sent by the
+/// server, and then adds the items yielded by its own stream, skipping as
+/// many of them as the server sent, which it already has.
+///
+/// To render the items into the server's HTML, wait for [`ready`] before
+/// rendering the list, for example in a `Suspend`. On the server, this waits
+/// for the stream to end; on a hydrating client, for the server's items to
+/// be replayed, so that hydration begins from the same list.
+///
+/// [`ready`]: ArcStreamResource::ready
+pub struct ArcStreamResource<T, Ser> {
+    ser: PhantomData<Ser>,
+    items: ArcReadSignal<Vec<T>>,
+    ready: Ready,
+}
+
+// resolves once the stream has ended or, on a hydrating client, once the
+// server's items have been replayed
+type Ready = Shared<oneshot::Receiver<()>>;
+
+impl<T, Ser> Clone for ArcStreamResource<T, Ser> {
+    fn clone(&self) -> Self {
+        Self {
+            ser: PhantomData,
+            items: self.items.clone(),
+            ready: self.ready.clone(),
+        }
+    }
+}
+
+impl<T, Ser> Deref for ArcStreamResource<T, Ser> {
+    type Target = ArcReadSignal<Vec<T>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<T> ArcStreamResource<T, Str>
+where
+    T: SerializableData<Str>,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new(stream: impl Stream<Item = T> + Send + 'static) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        ArcStreamResource::new_with_encoding(stream)
+    }
+}
+
+impl<T> ArcStreamResource<T, SerdeJson>
+where
+    T: SerializableData<SerdeJson>,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_serde(stream: impl Stream<Item = T> + Send + 'static) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        ArcStreamResource::new_with_encoding(stream)
+    }
+}
+
+#[cfg(feature = "miniserde")]
+impl<T> ArcStreamResource<T, Miniserde>
+where
+    T: SerializableData<Miniserde>,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_miniserde(stream: impl Stream<Item = T> + Send + 'static) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        ArcStreamResource::new_with_encoding(stream)
+    }
+}
+
+#[cfg(feature = "serde-lite")]
+impl<T> ArcStreamResource<T, SerdeLite>
+where
+    T: SerializableData<SerdeLite>,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_serde_lite(
+        stream: impl Stream<Item = T> + Send + 'static,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        ArcStreamResource::new_with_encoding(stream)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T> ArcStreamResource<T, Rkyv>
+where
+    T: SerializableData<Rkyv>,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_rkyv(stream: impl Stream<Item = T> + Send + 'static) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        ArcStreamResource::new_with_encoding(stream)
+    }
+}
+
+#[cfg(feature = "postcard")]
+impl<T> ArcStreamResource<T, Postcard>
+where
+    T: SerializableData<Postcard>,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_postcard(stream: impl Stream<Item = T> + Send + 'static) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        ArcStreamResource::new_with_encoding(stream)
+    }
+}
+
+impl<T, Ser> ArcStreamResource<T, Ser>
+where
+    Ser: Serializer,
+    T: SerializableData<Ser>,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_with_encoding(
+        stream: impl Stream<Item = T> + Send + 'static,
+    ) -> ArcStreamResource<T, Ser>
+    where
+        T: Send + Sync + 'static,
+    {
+        let id = Owner::shared_context()
+            .map(|sc| sc.next_id())
+            .unwrap_or_default();
+        let items = ArcRwSignal::new(Vec::new());
+
+        // the items that were streamed from the server, if hydrating
+        let server_items =
+            Owner::shared_context().and_then(|sc| sc.read_stream(&id));
+        // otherwise, each item is sent to the client as it arrives
+        let sent_items = server_items
+            .is_none()
+            .then(|| {
+                let sc = Owner::shared_context()?;
+                let (tx, rx) = mpsc::unbounded();
+                sc.write_stream(id, Box::pin(rx));
+                Some(tx)
+            })
+            .flatten();
+
+        // stop listening when the owner is cleaned up or disposed
+        let (abort, registration) = AbortHandle::new_pair();
+        Owner::on_cleanup(move || abort.abort());
+        let (set_ready, ready) = oneshot::channel();
+
+        let task = Abortable::new(
+            {
+                let items = items.clone();
+                async move {
+                    let mut stream = Box::pin(stream);
+                    let mut set_ready = Some(set_ready);
+                    if let Some(server_items) = server_items {
+                        let replayed =
+                            replay::<T, Ser>(server_items, &items).await;
+                        if let Some(set_ready) = set_ready.take() {
+                            _ = set_ready.send(());
+                        }
+                        // the first items of this stream are the ones the
+                        // server already sent
+                        for _ in 0..replayed {
+                            if stream.next().await.is_none() {
+                                return;
+                            }
+                        }
+                    }
+
+                    while let Some(item) = stream.next().await {
+                        if let Some(tx) = &sent_items {
+                            _ = tx.unbounded_send(item.ser().map_err(|e| {
+                                SerializationError(format!("{e:?}"))
+                            }));
+                        }
+                        items.update(|items| items.push(item));
+                    }
+                    if let Some(set_ready) = set_ready {
+                        _ = set_ready.send(());
+                    }
+                }
+            },
+            registration,
+        );
+        spawn(async move {
+            _ = task.await;
+        });
+
+        ArcStreamResource {
+            ser: PhantomData,
+            items: items.read_only(),
+            ready: ready.shared(),
+        }
+    }
+}
+
+impl<T, Ser> ArcStreamResource<T, Ser> {
+    /// Returns a [`Future`] that resolves once the stream has ended, or, on a
+    /// hydrating client, once the items sent by the server have been
+    /// replayed. It also resolves if the resource is disposed first.
+    pub fn ready(&self) -> impl Future<Output = ()> + Send + Sync + 'static {
+        self.ready.clone().map(|_| ())
+    }
+}
+
+// adds the items that were streamed from the server, in the order they were
+// sent, and returns how many the server sent
+async fn replay<T, Ser>(
+    mut server_items: PinnedStream<Result<String, SerializationError>>,
+    items: &ArcRwSignal<Vec<T>>,
+) -> usize
+where
+    Ser: Serializer,
+    T: SerializableData<Ser>,
+    T::DeErr: Debug,
+{
+    let mut count = 0;
+    while let Some(item) = server_items.next().await {
+        count += 1;
+        match item {
+            Ok(item) => match T::de(&item) {
+                Ok(item) => items.update(|items| items.push(item)),
+                Err(e) => crate::log(&format!(
+                    "couldn't deserialize from {item:?}: {e:?}"
+                )),
+            },
+            Err(e) => crate::log(&format!(
+                "the server could not serialize an item: {e}"
+            )),
+        }
+    }
+    count
+}
+
+/// An arena-allocated [`ArcStreamResource`].
+pub struct StreamResource<T, Ser>
+where
+    T: Send + Sync + 'static,
+{
+    ser: PhantomData<Ser>,
+    items: ReadSignal<Vec<T>>,
+    ready: Stored<Ready>,
+}
+
+impl<T: Send + Sync + 'static, Ser> Copy for StreamResource<T, Ser> {}
+
+impl<T: Send + Sync + 'static, Ser> Clone for StreamResource<T, Ser> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, Ser> Deref for StreamResource<T, Ser>
+where
+    T: Send + Sync + 'static,
+{
+    type Target = ReadSignal<Vec<T>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<T> StreamResource<T, Str>
+where
+    T: SerializableData<Str> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new(stream: impl Stream<Item = T> + Send + 'static) -> Self {
+        StreamResource::new_with_encoding(stream)
+    }
+}
+
+impl<T> StreamResource<T, SerdeJson>
+where
+    T: SerializableData<SerdeJson> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_serde(stream: impl Stream<Item = T> + Send + 'static) -> Self {
+        StreamResource::new_with_encoding(stream)
+    }
+}
+
+#[cfg(feature = "miniserde")]
+impl<T> StreamResource<T, Miniserde>
+where
+    T: SerializableData<Miniserde> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_miniserde(
+        stream: impl Stream<Item = T> + Send + 'static,
+    ) -> Self {
+        StreamResource::new_with_encoding(stream)
+    }
+}
+
+#[cfg(feature = "serde-lite")]
+impl<T> StreamResource<T, SerdeLite>
+where
+    T: SerializableData<SerdeLite> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_serde_lite(
+        stream: impl Stream<Item = T> + Send + 'static,
+    ) -> Self {
+        StreamResource::new_with_encoding(stream)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T> StreamResource<T, Rkyv>
+where
+    T: SerializableData<Rkyv> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_rkyv(stream: impl Stream<Item = T> + Send + 'static) -> Self {
+        StreamResource::new_with_encoding(stream)
+    }
+}
+
+#[cfg(feature = "postcard")]
+impl<T> StreamResource<T, Postcard>
+where
+    T: SerializableData<Postcard> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_postcard(
+        stream: impl Stream<Item = T> + Send + 'static,
+    ) -> Self {
+        StreamResource::new_with_encoding(stream)
+    }
+}
+
+impl<T, Ser> StreamResource<T, Ser>
+where
+    Ser: Serializer,
+    T: SerializableData<Ser> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    pub fn new_with_encoding(
+        stream: impl Stream<Item = T> + Send + 'static,
+    ) -> StreamResource<T, Ser> {
+        let ArcStreamResource { items, ready, .. } =
+            ArcStreamResource::<T, Ser>::new_with_encoding(stream);
+        StreamResource {
+            ser: PhantomData,
+            items: ReadSignal {
+                inner: Stored::new(items),
+            },
+            ready: Stored::new(ready),
+        }
+    }
+}
+
+impl<T, Ser> StreamResource<T, Ser>
+where
+    T: Send + Sync + 'static,
+{
+    /// Returns a [`Future`] that resolves once the stream has ended, or, on a
+    /// hydrating client, once the items sent by the server have been
+    /// replayed. See [`ArcStreamResource::ready`].
+    #[track_caller]
+    pub fn ready(&self) -> impl Future<Output = ()> + Send + Sync + 'static {
+        let ready = self.ready.get().unwrap_or_else(unwrap_signal!(self));
+        ready.map(|_| ())
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     arena::Owner,
+    graph::{self, NodeKind},
     notify::{channel, Sender},
     source::{
         AnySource, AnySubscriber, ReactiveNode, SourceSet, Subscriber,
@@ -11,6 +12,7 @@ use futures::StreamExt;
 use parking_lot::RwLock;
 use std::{
     mem,
+    panic::Location,
     sync::{Arc, Weak},
 };
 
@@ -49,12 +51,34 @@ macro_rules! spawn_effect {
         observer.notify();
 
         let value = Arc::new(RwLock::new(None));
-        let owner = Owner::new();
+        let owner = Owner::new_transparent();
         let inner = Arc::new(RwLock::new(EffectInner {
             owner: owner.clone(),
             observer,
             sources: SourceSet::new(),
         }));
+        graph::register(
+            NodeKind::Effect,
+            Some(Location::caller()),
+            None,
+            None,
+            Some(inner.to_any_subscriber()),
+        );
+
+        // stop running when the parent owner is cleaned up or disposed
+        Owner::on_cleanup({
+            let inner = Arc::downgrade(&inner);
+            move || {
+                if let Some(inner) = inner.upgrade() {
+                    let owner = {
+                        let mut inner = inner.write();
+                        inner.observer.close();
+                        inner.owner.clone()
+                    };
+                    owner.dispose();
+                }
+            }
+        });
 
         $spawner({
             let value = Arc::clone(&value);
@@ -69,6 +93,8 @@ macro_rules! spawn_effect {
                         subscriber.with_observer(|| $fun(old_value))
                     });
                     *value.write() = Some(new_value);
+                    #[cfg(feature = "testing")]
+                    crate::testing::record_run(&subscriber);
                 }
             }
         });
@@ -80,6 +106,7 @@ impl<T> Effect<T>
 where
     T: 'static,
 {
+    #[track_caller]
     pub fn new(mut fun: impl FnMut(Option<T>) -> T + 'static) -> Self {
         spawn_effect!(fun, spawn_local)
     }
@@ -96,6 +123,7 @@ impl<T> Effect<T>
 where
     T: Send + Sync + 'static,
 {
+    #[track_caller]
     pub fn new_sync(
         mut fun: impl FnMut(Option<T>) -> T + Send + Sync + 'static,
     ) -> Self {
@@ -149,4 +177,8 @@ impl Subscriber for RwLock<EffectInner> {
     fn clear_sources(&self, subscriber: &AnySubscriber) {
         self.write().sources.clear_sources(subscriber);
     }
+
+    fn sources(&self) -> Vec<AnySource> {
+        (&self.read().sources).into_iter().cloned().collect()
+    }
 }
@@ -0,0 +1,376 @@
+//! Introspection of the reactive graph, for debugging.
+//!
+//! In debug builds, every signal, trigger, memo, effect, async derived and
+//! store field is recorded when it is created, along with the [`Owner`] that
+//! was current at the time. [`ReactiveGraph::from_owner`] takes a snapshot of
+//! the nodes that belong to an owner and its descendants, and of any nodes
+//! they are connected to, which can then be exported to Graphviz with
+//! [`ReactiveGraph::to_dot`] or to JSON with [`ReactiveGraph::to_json`].
+//!
+//! ```
+//! use tachy_reaccy::{graph::ReactiveGraph, prelude::*, Owner};
+//!
+//! let owner = Owner::new();
+//! let (count, double) = owner.with(|| {
+//!     let count = RwSignal::new(1);
+//!     let double = Memo::new(move |_| count.get() * 2);
+//!     (count, double)
+//! });
+//! assert_eq!(double.get(), 2);
+//!
+//! let graph = ReactiveGraph::from_owner(&owner);
+//! assert_eq!(graph.nodes().len(), 2);
+//! assert_eq!(graph.edges().len(), 1);
+//! println!("{}", graph.to_dot());
+//! # _ = count;
+//! ```
+//!
+//! Nodes are only recorded when `debug_assertions` are enabled. In release
+//! builds, every graph is empty.
+
+pub use crate::source::ReactiveNodeState;
+use crate::{
+    arena::Owner,
+    source::{AnySource, AnySubscriber},
+};
+use std::{
+    fmt::{self, Write},
+    panic::Location,
+};
+#[cfg(debug_assertions)]
+use {
+    crate::source::{ReactiveNode, Source, Subscriber},
+    lazy_static::lazy_static,
+    parking_lot::RwLock,
+    rustc_hash::{FxHashMap, FxHashSet},
+    std::{collections::VecDeque, sync::Arc},
+};
+
+/// The kind of a node in the reactive graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Signal,
+    Trigger,
+    Memo,
+    Effect,
+    RenderEffect,
+    AsyncDerived,
+    StoreField,
+}
+
+impl NodeKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            NodeKind::Signal => "signal",
+            NodeKind::Trigger => "trigger",
+            NodeKind::Memo => "memo",
+            NodeKind::Effect => "effect",
+            NodeKind::RenderEffect => "render_effect",
+            NodeKind::AsyncDerived => "async_derived",
+            NodeKind::StoreField => "store_field",
+        }
+    }
+
+    fn shape(&self) -> &'static str {
+        match self {
+            NodeKind::Signal | NodeKind::Trigger | NodeKind::StoreField => {
+                "ellipse"
+            }
+            NodeKind::Memo | NodeKind::AsyncDerived => "box",
+            NodeKind::Effect | NodeKind::RenderEffect => "hexagon",
+        }
+    }
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A node in a [`ReactiveGraph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    /// The index of this node in [`ReactiveGraph::nodes`].
+    pub id: usize,
+    pub kind: NodeKind,
+    /// Where the node was created.
+    pub defined_at: Option<&'static Location<'static>>,
+    /// Additional information about the node, like the path of a store field.
+    pub label: Option<String>,
+    /// Whether the node's value was up to date when the graph was created.
+    pub state: ReactiveNodeState,
+}
+
+/// A dependency between two nodes in a [`ReactiveGraph`]: `subscriber` is
+/// notified when `source` changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphEdge {
+    pub source: usize,
+    pub subscriber: usize,
+}
+
+/// A snapshot of part of the reactive graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReactiveGraph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+impl ReactiveGraph {
+    /// Creates a snapshot of the nodes that were created under the given
+    /// owner or any of its descendants, together with every node that they
+    /// are connected to, directly or indirectly.
+    ///
+    /// Dependencies are only known once they have been tracked, so a memo
+    /// that has never been read has no edges.
+    pub fn from_owner(owner: &Owner) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            let mut builder = GraphBuilder::default();
+            for entry in owner.graph_nodes() {
+                builder.visit(entry);
+            }
+            builder.run()
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            _ = owner;
+            Self::default()
+        }
+    }
+
+    pub fn nodes(&self) -> &[GraphNode] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[GraphEdge] {
+        &self.edges
+    }
+
+    /// Renders the graph in the Graphviz DOT language. Edges point from each
+    /// source to its subscribers.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph reactive_graph {\n");
+        for node in &self.nodes {
+            let mut label = node.kind.to_string();
+            if let Some(extra) = &node.label {
+                _ = write!(label, " {extra}");
+            }
+            if let Some(location) = node.defined_at {
+                _ = write!(label, "\n{location}");
+            }
+            let color = match node.state {
+                ReactiveNodeState::Clean => "black",
+                ReactiveNodeState::Check => "orange",
+                ReactiveNodeState::Dirty => "red",
+            };
+            _ = writeln!(
+                dot,
+                "    {} [label={:?}, shape={}, color={}];",
+                node.id,
+                label,
+                node.kind.shape(),
+                color
+            );
+        }
+        for edge in &self.edges {
+            _ = writeln!(dot, "    {} -> {};", edge.source, edge.subscriber);
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Serializes the graph as JSON, in the form
+    /// `{ "nodes": [{ "id", "kind", "defined_at", "label", "state" }],
+    /// "edges": [{ "source", "subscriber" }] }`.
+    pub fn to_json(&self) -> String {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                serde_json::json!({
+                    "id": node.id,
+                    "kind": node.kind.as_str(),
+                    "defined_at": node.defined_at.map(ToString::to_string),
+                    "label": node.label,
+                    "state": match node.state {
+                        ReactiveNodeState::Clean => "clean",
+                        ReactiveNodeState::Check => "check",
+                        ReactiveNodeState::Dirty => "dirty",
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| {
+                serde_json::json!({
+                    "source": edge.source,
+                    "subscriber": edge.subscriber,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({ "nodes": nodes, "edges": edges }).to_string()
+    }
+}
+
+/// A node as it was recorded when it was created.
+#[cfg(debug_assertions)]
+pub(crate) struct NodeEntry {
+    kind: NodeKind,
+    defined_at: Option<&'static Location<'static>>,
+    label: Option<String>,
+    source: Option<AnySource>,
+    subscriber: Option<AnySubscriber>,
+}
+
+#[cfg(debug_assertions)]
+impl NodeEntry {
+    pub(crate) fn is_alive(&self) -> bool {
+        self.source
+            .as_ref()
+            .map(|source| source.1.strong_count() > 0)
+            .or_else(|| {
+                self.subscriber
+                    .as_ref()
+                    .map(|subscriber| subscriber.1.strong_count() > 0)
+            })
+            .unwrap_or(false)
+    }
+
+    fn state(&self) -> ReactiveNodeState {
+        match (&self.source, &self.subscriber) {
+            (Some(source), _) => source.state(),
+            (None, Some(subscriber)) => subscriber.state(),
+            (None, None) => ReactiveNodeState::Clean,
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+#[derive(Default)]
+struct Registry {
+    // keyed by the ids of both the source and the subscriber, which can differ
+    nodes: FxHashMap<usize, Arc<NodeEntry>>,
+    // the registry is pruned of dead nodes whenever it doubles in size
+    prune_at: usize,
+}
+
+#[cfg(debug_assertions)]
+lazy_static! {
+    static ref REGISTRY: RwLock<Registry> = Default::default();
+}
+
+/// Records a new node, and adds it to the current owner.
+///
+/// This does nothing in release builds.
+#[cfg_attr(not(debug_assertions), allow(unused_variables))]
+pub(crate) fn register(
+    kind: NodeKind,
+    defined_at: Option<&'static Location<'static>>,
+    label: Option<String>,
+    source: Option<AnySource>,
+    subscriber: Option<AnySubscriber>,
+) {
+    #[cfg(debug_assertions)]
+    {
+        let entry = Arc::new(NodeEntry {
+            kind,
+            defined_at,
+            label,
+            source,
+            subscriber,
+        });
+
+        {
+            let mut registry = REGISTRY.write();
+            if registry.nodes.len() >= registry.prune_at {
+                registry.nodes.retain(|_, entry| entry.is_alive());
+                registry.prune_at = (registry.nodes.len() * 2).max(64);
+            }
+            let ids =
+                entry.source.iter().map(|source| source.0).chain(
+                    entry.subscriber.iter().map(|subscriber| subscriber.0),
+                );
+            for id in ids {
+                registry.nodes.insert(id, Arc::clone(&entry));
+            }
+        }
+
+        if let Some(owner) = Owner::current() {
+            owner.add_graph_node(&entry);
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+#[derive(Default)]
+struct GraphBuilder {
+    ids: FxHashMap<*const NodeEntry, usize>,
+    nodes: Vec<GraphNode>,
+    edges: FxHashSet<GraphEdge>,
+    queue: VecDeque<Arc<NodeEntry>>,
+}
+
+#[cfg(debug_assertions)]
+impl GraphBuilder {
+    fn visit(&mut self, entry: Arc<NodeEntry>) -> usize {
+        let key = Arc::as_ptr(&entry);
+        if let Some(id) = self.ids.get(&key) {
+            return *id;
+        }
+        let id = self.nodes.len();
+        self.ids.insert(key, id);
+        self.nodes.push(GraphNode {
+            id,
+            kind: entry.kind,
+            defined_at: entry.defined_at,
+            label: entry.label.clone(),
+            state: entry.state(),
+        });
+        self.queue.push_back(entry);
+        id
+    }
+
+    fn run(mut self) -> ReactiveGraph {
+        while let Some(entry) = self.queue.pop_front() {
+            let this = self.ids[&Arc::as_ptr(&entry)];
+            if let Some(subscriber) = &entry.subscriber {
+                for source in subscriber.sources() {
+                    if let Some(source) = lookup(source.0) {
+                        let source = self.visit(source);
+                        self.edges.insert(GraphEdge {
+                            source,
+                            subscriber: this,
+                        });
+                    }
+                }
+            }
+            if let Some(source) = &entry.source {
+                for subscriber in source.subscribers() {
+                    if let Some(subscriber) = lookup(subscriber.0) {
+                        let subscriber = self.visit(subscriber);
+                        self.edges.insert(GraphEdge {
+                            source: this,
+                            subscriber,
+                        });
+                    }
+                }
+            }
+        }
+
+        let mut edges = self.edges.into_iter().collect::<Vec<_>>();
+        edges.sort_by_key(|edge| (edge.source, edge.subscriber));
+        ReactiveGraph {
+            nodes: self.nodes,
+            edges,
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+fn lookup(id: usize) -> Option<Arc<NodeEntry>> {
+    REGISTRY.read().nodes.get(&id).cloned()
+}
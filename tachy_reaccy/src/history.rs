@@ -0,0 +1,430 @@
+//! Undo and redo for signals and stores.
+//!
+//! An [`ArcHistory`] wraps a writable reactive value: an [`ArcRwSignal`],
+//! an [`ArcStore`], or any field of a store. Each edit made through the
+//! history records a snapshot of the value before it, which [`undo`] restores.
+//! Wrapping a single field of a store keeps its history independent of the
+//! rest of the store.
+//!
+//! ```rust
+//! # use tachy_reaccy::{history::ArcHistory, prelude::*};
+//! let text = ArcRwSignal::new(String::new());
+//! let history = ArcHistory::new(text.clone());
+//!
+//! history.update(|text| text.push_str("Hello"));
+//! history.update(|text| text.push_str(", world"));
+//! assert_eq!(text.get_untracked(), "Hello, world");
+//!
+//! history.undo();
+//! assert_eq!(text.get_untracked(), "Hello");
+//! history.redo();
+//! assert_eq!(text.get_untracked(), "Hello, world");
+//! ```
+//!
+//! Edits made to the wrapped value directly, rather than through the history,
+//! are not recorded.
+//!
+//! [`ArcRwSignal`]: crate::signal::ArcRwSignal
+//! [`ArcStore`]: crate::store::ArcStore
+//! [`undo`]: ArcHistory::undo
+
+use crate::{
+    arena::Stored,
+    signal::{ArcReadSignal, ArcRwSignal},
+    signal_traits::{
+        SignalGetUntracked, SignalIsDisposed, SignalSet, SignalUpdate,
+        SignalWithUntracked,
+    },
+};
+use parking_lot::RwLock;
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug},
+    sync::Arc,
+    time::Duration,
+};
+
+/// A writable reactive value that a history can wrap.
+pub trait HistoryTarget:
+    SignalWithUntracked
+    + SignalUpdate<Value = <Self as SignalWithUntracked>::Value>
+    + SignalIsDisposed
+{
+}
+
+impl<S> HistoryTarget for S where
+    S: SignalWithUntracked
+        + SignalUpdate<Value = <S as SignalWithUntracked>::Value>
+        + SignalIsDisposed
+{
+}
+
+// the type of the value a history records
+type Value<S> = <S as SignalWithUntracked>::Value;
+
+/// The undo and redo stacks of a reactive value.
+pub struct ArcHistory<S>
+where
+    S: HistoryTarget,
+{
+    target: S,
+    state: Arc<RwLock<HistoryState<Value<S>>>>,
+    can_undo: ArcRwSignal<bool>,
+    can_redo: ArcRwSignal<bool>,
+}
+
+struct HistoryState<T> {
+    undo: VecDeque<T>,
+    redo: Vec<T>,
+    capacity: Option<usize>,
+    coalesce_within: Option<Duration>,
+    /// When the last edit was recorded, if later edits can be coalesced
+    /// with it.
+    last_edit: Option<Duration>,
+    /// How many transactions are open, and whether the current one has
+    /// recorded its snapshot yet.
+    transactions: usize,
+    transaction_recorded: bool,
+}
+
+impl<S> Clone for ArcHistory<S>
+where
+    S: HistoryTarget + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            target: self.target.clone(),
+            state: Arc::clone(&self.state),
+            can_undo: self.can_undo.clone(),
+            can_redo: self.can_redo.clone(),
+        }
+    }
+}
+
+impl<S> Debug for ArcHistory<S>
+where
+    S: HistoryTarget,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.read();
+        f.debug_struct("ArcHistory")
+            .field("undo", &state.undo.len())
+            .field("redo", &state.redo.len())
+            .field("capacity", &state.capacity)
+            .field("coalesce_within", &state.coalesce_within)
+            .finish()
+    }
+}
+
+impl<S> ArcHistory<S>
+where
+    S: HistoryTarget,
+    Value<S>: Clone,
+{
+    /// Creates a history for the value, with no limit on the number of edits
+    /// it keeps, and without coalescing edits.
+    #[track_caller]
+    pub fn new(target: S) -> Self {
+        Self {
+            target,
+            state: Arc::new(RwLock::new(HistoryState {
+                undo: VecDeque::new(),
+                redo: Vec::new(),
+                capacity: None,
+                coalesce_within: None,
+                last_edit: None,
+                transactions: 0,
+                transaction_recorded: false,
+            })),
+            can_undo: ArcRwSignal::new(false),
+            can_redo: ArcRwSignal::new(false),
+        }
+    }
+
+    /// Limits the number of edits that can be undone, discarding the oldest
+    /// ones beyond it.
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        {
+            let mut state = self.state.write();
+            state.capacity = Some(capacity);
+            state.trim();
+        }
+        self.update_signals();
+        self
+    }
+
+    /// Coalesces each edit made less than `window` after the previous one
+    /// into the same undo step, e.g. for keystrokes in a text field.
+    pub fn coalesce_within(self, window: Duration) -> Self {
+        self.state.write().coalesce_within = Some(window);
+        self
+    }
+
+    /// The value this history records.
+    pub fn target(&self) -> &S {
+        &self.target
+    }
+
+    /// Whether there is an edit to undo.
+    pub fn can_undo(&self) -> ArcReadSignal<bool> {
+        self.can_undo.read_only()
+    }
+
+    /// Whether there is an undone edit to redo.
+    pub fn can_redo(&self) -> ArcReadSignal<bool> {
+        self.can_redo.read_only()
+    }
+
+    /// Updates the value, recording its previous value so that the edit can
+    /// be undone.
+    pub fn update(&self, fun: impl FnOnce(&mut Value<S>)) {
+        self.record();
+        self.target.update(fun);
+    }
+
+    /// Sets the value, recording its previous value so that the edit can be
+    /// undone.
+    pub fn set(&self, value: impl Into<Value<S>>) {
+        self.update(|n| *n = value.into());
+    }
+
+    /// Runs `fun`, recording all the edits made through this history while it
+    /// runs as a single undo step.
+    pub fn transaction<U>(&self, fun: impl FnOnce() -> U) -> U {
+        {
+            let mut state = self.state.write();
+            if state.transactions == 0 {
+                state.transaction_recorded = false;
+            }
+            state.transactions += 1;
+        }
+        let value = fun();
+        let mut state = self.state.write();
+        state.transactions -= 1;
+        // an edit after a transaction is never coalesced into it
+        if state.transactions == 0 {
+            state.last_edit = None;
+        }
+        value
+    }
+
+    /// Restores the value from before the last edit, returning `false` if
+    /// there was nothing to undo.
+    pub fn undo(&self) -> bool {
+        let Some(prev) = self.state.write().undo.pop_back() else {
+            return false;
+        };
+        let current = self.target.try_with_untracked(Clone::clone);
+        {
+            let mut state = self.state.write();
+            state.redo.extend(current);
+            state.last_edit = None;
+        }
+        self.target.set(prev);
+        self.update_signals();
+        true
+    }
+
+    /// Restores the last undone edit, returning `false` if there was nothing
+    /// to redo.
+    pub fn redo(&self) -> bool {
+        let Some(next) = self.state.write().redo.pop() else {
+            return false;
+        };
+        let current = self.target.try_with_untracked(Clone::clone);
+        {
+            let mut state = self.state.write();
+            state.undo.extend(current);
+            state.last_edit = None;
+            state.trim();
+        }
+        self.target.set(next);
+        self.update_signals();
+        true
+    }
+
+    /// Forgets all the edits that can be undone or redone.
+    pub fn clear(&self) {
+        {
+            let mut state = self.state.write();
+            state.undo.clear();
+            state.redo.clear();
+            state.last_edit = None;
+        }
+        self.update_signals();
+    }
+
+    // records a snapshot of the value before an edit, unless the edit is
+    // coalesced with the one before it
+    fn record(&self) {
+        let now = now();
+        {
+            let mut state = self.state.write();
+            state.redo.clear();
+            let coalesced = if state.transactions > 0 {
+                std::mem::replace(&mut state.transaction_recorded, true)
+            } else {
+                let coalesced = matches!(
+                    (state.coalesce_within, state.last_edit),
+                    (Some(window), Some(last)) if now.saturating_sub(last) < window
+                );
+                state.last_edit = Some(now);
+                coalesced
+            };
+            if !coalesced {
+                if let Some(snapshot) =
+                    self.target.try_with_untracked(Clone::clone)
+                {
+                    state.undo.push_back(snapshot);
+                    state.trim();
+                }
+            }
+        }
+        self.update_signals();
+    }
+
+    fn update_signals(&self) {
+        let (can_undo, can_redo) = {
+            let state = self.state.read();
+            (!state.undo.is_empty(), !state.redo.is_empty())
+        };
+        if self.can_undo.get_untracked() != can_undo {
+            self.can_undo.set(can_undo);
+        }
+        if self.can_redo.get_untracked() != can_redo {
+            self.can_redo.set(can_redo);
+        }
+    }
+}
+
+impl<T> HistoryState<T> {
+    fn trim(&mut self) {
+        if let Some(capacity) = self.capacity {
+            while self.undo.len() > capacity {
+                self.undo.pop_front();
+            }
+        }
+    }
+}
+
+// the time since some fixed point, used to coalesce edits
+#[cfg(feature = "web")]
+fn now() -> Duration {
+    Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+}
+
+#[cfg(not(feature = "web"))]
+fn now() -> Duration {
+    use std::{sync::OnceLock, time::Instant};
+
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed()
+}
+
+/// An arena-allocated [`ArcHistory`], which is `Copy` and is disposed along
+/// with the reactive owner that created it.
+pub struct History<S>
+where
+    S: HistoryTarget + Send + Sync + 'static,
+    Value<S>: Send + Sync + 'static,
+{
+    inner: Stored<ArcHistory<S>>,
+}
+
+impl<S> Copy for History<S>
+where
+    S: HistoryTarget + Send + Sync + 'static,
+    Value<S>: Send + Sync + 'static,
+{
+}
+
+impl<S> Clone for History<S>
+where
+    S: HistoryTarget + Send + Sync + 'static,
+    Value<S>: Send + Sync + 'static,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Debug for History<S>
+where
+    S: HistoryTarget + Send + Sync + 'static,
+    Value<S>: Send + Sync + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("History")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S> History<S>
+where
+    S: HistoryTarget + Clone + Send + Sync + 'static,
+    Value<S>: Clone + Send + Sync + 'static,
+{
+    /// Creates a history for the value. See [`ArcHistory::new`].
+    #[track_caller]
+    pub fn new(target: S) -> Self {
+        Self::from_arc(ArcHistory::new(target))
+    }
+
+    /// Stores a history that was configured with the methods of
+    /// [`ArcHistory`].
+    #[track_caller]
+    pub fn from_arc(history: ArcHistory<S>) -> Self {
+        Self {
+            inner: Stored::new(history),
+        }
+    }
+
+    /// Whether there is an edit to undo. See [`ArcHistory::can_undo`].
+    pub fn can_undo(&self) -> Option<ArcReadSignal<bool>> {
+        self.inner.get().map(|inner| inner.can_undo())
+    }
+
+    /// Whether there is an undone edit to redo. See
+    /// [`ArcHistory::can_redo`].
+    pub fn can_redo(&self) -> Option<ArcReadSignal<bool>> {
+        self.inner.get().map(|inner| inner.can_redo())
+    }
+
+    /// Updates the value. See [`ArcHistory::update`].
+    pub fn update(&self, fun: impl FnOnce(&mut Value<S>)) {
+        if let Some(inner) = self.inner.get() {
+            inner.update(fun);
+        }
+    }
+
+    /// Sets the value. See [`ArcHistory::set`].
+    pub fn set(&self, value: impl Into<Value<S>>) {
+        if let Some(inner) = self.inner.get() {
+            inner.set(value);
+        }
+    }
+
+    /// Records the edits made by `fun` as a single undo step. See
+    /// [`ArcHistory::transaction`].
+    pub fn transaction<U>(&self, fun: impl FnOnce() -> U) -> Option<U> {
+        self.inner.get().map(|inner| inner.transaction(fun))
+    }
+
+    /// Undoes the last edit. See [`ArcHistory::undo`].
+    pub fn undo(&self) -> bool {
+        self.inner.get().map(|inner| inner.undo()).unwrap_or(false)
+    }
+
+    /// Redoes the last undone edit. See [`ArcHistory::redo`].
+    pub fn redo(&self) -> bool {
+        self.inner.get().map(|inner| inner.redo()).unwrap_or(false)
+    }
+
+    /// Forgets all the edits. See [`ArcHistory::clear`].
+    pub fn clear(&self) {
+        if let Some(inner) = self.inner.get() {
+            inner.clear();
+        }
+    }
+}
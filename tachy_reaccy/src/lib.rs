@@ -5,11 +5,14 @@ mod arena;
 pub mod async_signal;
 pub mod context;
 pub mod effect;
+pub mod graph;
+pub mod history;
 pub mod memo;
 
 // The `notify` module is internal, possibly used for internal event notification.
 mod notify;
 
+pub mod persist;
 pub mod render_effect;
 pub mod selector;
 
@@ -18,6 +21,7 @@ pub mod selector;
 mod serde;
 
 pub mod serialization;
+pub mod server_fn;
 pub mod shared_context;
 pub mod signal;
 pub mod signal_traits;
@@ -27,10 +31,12 @@ mod source;
 
 pub mod spawn;
 pub mod store;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 // Using specific items from the `source` and `arena` modules.
-use crate::source::AnySubscriber;
-pub use arena::{Owner, Root};
+use crate::source::{AnySource, AnySubscriber, ReactiveNode, ToAnySource};
+pub use arena::{on_cleanup, Owner, Root};
 // Utilizing futures for asynchronous programming.
 use futures::{Future, Stream};
 use std::{cell::RefCell, pin::Pin};
@@ -38,10 +44,12 @@ use std::{cell::RefCell, pin::Pin};
 // A prelude module to provide easy access to commonly used items.
 pub mod prelude {
     pub use crate::{
-        async_signal::{AsyncDerived, Resource},
+        async_signal::{AsyncDerived, Resource, StreamResource},
+        batch,
         context::{provide_context, use_context},
         effect::Effect,
         memo::{ArcMemo, Memo},
+        on_cleanup,
         signal::{signal, ArcRwSignal, ReadSignal, RwSignal},
         signal_traits::*,
         store::{StoreField, StoreFieldIndex, StoreFieldIterator},
@@ -54,6 +62,11 @@ thread_local! {
     static OBSERVER: RefCell<Option<AnySubscriber>> = RefCell::new(None);
 }
 
+// Thread-local queue of sources that have changed during the current batch, if any.
+thread_local! {
+    static BATCH: RefCell<Option<Vec<AnySource>>> = const { RefCell::new(None) };
+}
+
 // Type aliases for pinned futures and streams, enhancing code readability.
 pub type PinnedFuture<T> = Pin<Box<dyn Future<Output = T> + Send + Sync>>;
 pub type PinnedStream<T> = Pin<Box<dyn Stream<Item = T> + Send + Sync>>;
@@ -87,6 +100,70 @@ pub fn untrack<T>(fun: impl FnOnce() -> T) -> T {
     value
 }
 
+/// Runs the given function, deferring change notifications until it returns.
+///
+/// Any signals that are updated inside the batch notify their subscribers
+/// only once, at the end of the outermost batch, so an effect or memo that
+/// depends on several of them runs once rather than once per update.
+///
+/// Signals hold their new values immediately, but memos and other derived
+/// values are only marked as changed when the batch ends, so reading them
+/// inside the batch may return a stale value.
+///
+/// ```
+/// # use tachy_reaccy::prelude::*;
+/// let first = RwSignal::new("Greg");
+/// let last = RwSignal::new("Johnston");
+/// batch(|| {
+///     first.set("Bob");
+///     last.set("Thompson");
+/// });
+/// assert_eq!(first.get_untracked(), "Bob");
+/// ```
+pub fn batch<T>(fun: impl FnOnce() -> T) -> T {
+    struct Flush(bool);
+
+    impl Drop for Flush {
+        fn drop(&mut self) {
+            // only the outermost batch notifies, even if the function panicked
+            if self.0 {
+                let sources =
+                    BATCH.with(|b| b.borrow_mut().take()).unwrap_or_default();
+                for source in sources {
+                    source.mark_dirty();
+                }
+            }
+        }
+    }
+
+    let outermost = BATCH.with(|b| {
+        let mut b = b.borrow_mut();
+        if b.is_none() {
+            *b = Some(Vec::new());
+            true
+        } else {
+            false
+        }
+    });
+    let _flush = Flush(outermost);
+    fun()
+}
+
+/// If a batch is in progress, queues the source to be marked dirty when it
+/// ends and returns `true`. Otherwise, returns `false`.
+pub(crate) fn defer_mark_dirty(source: &impl ToAnySource) -> bool {
+    BATCH.with(|b| match b.borrow_mut().as_mut() {
+        Some(queue) => {
+            let source = source.to_any_source();
+            if !queue.contains(&source) {
+                queue.push(source);
+            }
+            true
+        }
+        None => false,
+    })
+}
+
 // Logging functions with conditional compilation for web and non-web environments.
 #[cfg(feature = "web")]
 pub fn log(s: &str) {
@@ -1,5 +1,6 @@
 use crate::{
     arena::{Owner, Stored, StoredData},
+    graph::{self, NodeKind},
     signal_traits::*,
     source::{
         AnySource, AnySubscriber, ReactiveNode, ReactiveNodeState, Source,
@@ -33,6 +34,61 @@ impl<T: Send + Sync + 'static> Memo<T> {
         }
     }
 
+    /// Creates a memo that uses `eq` to decide whether its new value is the
+    /// same as the previous one, instead of [`PartialEq`]. Subscribers are
+    /// only notified when `eq` returns `false`.
+    ///
+    /// This is useful when comparing the whole value is expensive, or when
+    /// values should count as equal without being identical. The memo always
+    /// stores its latest value, even if it is "equal" to the previous one.
+    /// ```
+    /// # use tachy_reaccy::prelude::*;
+    /// # use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    /// let temperature = RwSignal::new(20.0_f64);
+    /// let approx = Memo::new_with_compare(
+    ///     move |_| temperature.get(),
+    ///     |a, b| (a - b).abs() < 0.5,
+    /// );
+    /// let runs = Arc::new(AtomicUsize::new(0));
+    /// let display = Memo::new({
+    ///     let runs = Arc::clone(&runs);
+    ///     move |_| {
+    ///         runs.fetch_add(1, Ordering::Relaxed);
+    ///         format!("{:.0}°C", approx.get())
+    ///     }
+    /// });
+    /// assert_eq!(display.get(), "20°C");
+    ///
+    /// // close enough to the previous value, so `display` doesn't rerun
+    /// temperature.set(20.1);
+    /// assert_eq!(display.get(), "20°C");
+    /// assert_eq!(runs.load(Ordering::Relaxed), 1);
+    ///
+    /// temperature.set(21.0);
+    /// assert_eq!(display.get(), "21°C");
+    /// assert_eq!(runs.load(Ordering::Relaxed), 2);
+    /// ```
+    #[track_caller]
+    pub fn new_with_compare(
+        fun: impl Fn(Option<&T>) -> T + Send + Sync + 'static,
+        eq: impl Fn(&T, &T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            inner: Stored::new(ArcMemo::new_with_compare(fun, eq)),
+        }
+    }
+
+    /// Creates a memo that notifies its subscribers every time it reruns,
+    /// so its value doesn't need to implement [`PartialEq`].
+    #[track_caller]
+    pub fn new_always_notify(
+        fun: impl Fn(Option<&T>) -> T + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            inner: Stored::new(ArcMemo::new_always_notify(fun)),
+        }
+    }
+
     pub fn debug_log_inner(&self, name: &str) {
         self.inner.get().unwrap().debug_log_inner(name);
     }
@@ -83,23 +139,47 @@ impl<T: Send + Sync + 'static> ArcMemo<T> {
     where
         T: PartialEq,
     {
+        Self::new_with_compare(fun, T::eq)
+    }
+
+    /// Creates a memo that uses `eq` to decide whether its new value is the
+    /// same as the previous one, instead of [`PartialEq`]. Subscribers are
+    /// only notified when `eq` returns `false`.
+    #[track_caller]
+    pub fn new_with_compare(
+        fun: impl Fn(Option<&T>) -> T + Send + Sync + 'static,
+        eq: impl Fn(&T, &T) -> bool + Send + Sync + 'static,
+    ) -> Self {
         let inner = Arc::new_cyclic(|weak| {
             let subscriber = AnySubscriber(
                 weak.as_ptr() as usize,
                 Weak::clone(weak) as Weak<dyn Subscriber + Send + Sync>,
             );
 
-            RwLock::new(MemoInner::new(
-                Arc::new(fun),
-                |lhs, rhs| lhs.as_ref() == rhs.as_ref(),
-                subscriber,
-            ))
+            RwLock::new(MemoInner::new(Arc::new(fun), Arc::new(eq), subscriber))
         });
-        Self {
+        let this = Self {
             #[cfg(debug_assertions)]
             defined_at: Location::caller(),
             inner,
-        }
+        };
+        graph::register(
+            NodeKind::Memo,
+            this.defined_at(),
+            None,
+            Some(this.to_any_source()),
+            Some(this.to_any_subscriber()),
+        );
+        this
+    }
+
+    /// Creates a memo that notifies its subscribers every time it reruns,
+    /// so its value doesn't need to implement [`PartialEq`].
+    #[track_caller]
+    pub fn new_always_notify(
+        fun: impl Fn(Option<&T>) -> T + Send + Sync + 'static,
+    ) -> Self {
+        Self::new_with_compare(fun, |_, _| false)
     }
 
     pub fn debug_log_inner(&self, name: &str) {
@@ -130,7 +210,8 @@ struct MemoInner<T> {
     value: Option<T>,
     #[allow(clippy::type_complexity)]
     fun: Arc<dyn Fn(Option<&T>) -> T + Send + Sync>,
-    compare_with: fn(Option<&T>, Option<&T>) -> bool,
+    #[allow(clippy::type_complexity)]
+    compare_with: Arc<dyn Fn(&T, &T) -> bool + Send + Sync>,
     owner: Owner,
     state: ReactiveNodeState,
     sources: SourceSet,
@@ -182,7 +263,7 @@ impl<T: Send + Sync + 'static> ReactiveNode for RwLock<MemoInner<T>> {
                 (
                     lock.fun.clone(),
                     lock.value.take(),
-                    lock.compare_with,
+                    lock.compare_with.clone(),
                     lock.owner.clone(),
                 )
             };
@@ -192,8 +273,13 @@ impl<T: Send + Sync + 'static> ReactiveNode for RwLock<MemoInner<T>> {
             let new_value = owner.with_cleanup(|| {
                 any_subscriber.with_observer(|| fun(value.as_ref()))
             });
+            #[cfg(feature = "testing")]
+            crate::testing::record_run(&any_subscriber);
 
-            let changed = !compare_with(Some(&new_value), value.as_ref());
+            let changed = value
+                .as_ref()
+                .map(|value| !compare_with(&new_value, value))
+                .unwrap_or(true);
             let mut lock = self.write();
             lock.value = Some(new_value);
             lock.state = ReactiveNodeState::Clean;
@@ -218,6 +304,10 @@ impl<T: Send + Sync + 'static> ReactiveNode for RwLock<MemoInner<T>> {
             false
         }
     }
+
+    fn state(&self) -> ReactiveNodeState {
+        self.read().state
+    }
 }
 
 impl<T: Send + Sync + 'static> ReactiveNode for ArcMemo<T> {
@@ -259,6 +349,10 @@ impl<T: Send + Sync + 'static> Source for RwLock<MemoInner<T>> {
     fn clear_subscribers(&self) {
         self.write().subscribers.take();
     }
+
+    fn subscribers(&self) -> Vec<AnySubscriber> {
+        (&self.read().subscribers).into_iter().cloned().collect()
+    }
 }
 
 impl<T: Send + Sync + 'static> Subscriber for RwLock<MemoInner<T>> {
@@ -269,6 +363,10 @@ impl<T: Send + Sync + 'static> Subscriber for RwLock<MemoInner<T>> {
     fn clear_sources(&self, subscriber: &AnySubscriber) {
         self.write().sources.clear_sources(subscriber);
     }
+
+    fn sources(&self) -> Vec<AnySource> {
+        (&self.read().sources).into_iter().cloned().collect()
+    }
 }
 
 impl<T: Send + Sync + 'static> Source for ArcMemo<T> {
@@ -288,7 +386,8 @@ impl<T: Send + Sync + 'static> Source for ArcMemo<T> {
 impl<T: Send + Sync + 'static> ToAnySubscriber for ArcMemo<T> {
     fn to_any_subscriber(&self) -> AnySubscriber {
         AnySubscriber(
-            self.inner.data_ptr() as usize,
+            // must match the subscriber that was created in ArcMemo::new
+            Arc::as_ptr(&self.inner) as usize,
             Arc::downgrade(&self.inner) as Weak<dyn Subscriber + Send + Sync>,
         )
     }
@@ -308,14 +407,14 @@ impl<T: Send + Sync + 'static> MemoInner<T> {
     #[allow(clippy::type_complexity)]
     pub fn new(
         fun: Arc<dyn Fn(Option<&T>) -> T + Send + Sync>,
-        compare_with: fn(Option<&T>, Option<&T>) -> bool,
+        compare_with: Arc<dyn Fn(&T, &T) -> bool + Send + Sync>,
         any_subscriber: AnySubscriber,
     ) -> Self {
         Self {
             value: None,
             fun,
             compare_with,
-            owner: Owner::new(),
+            owner: Owner::new_transparent(),
             state: ReactiveNodeState::Dirty,
             sources: Default::default(),
             subscribers: SubscriberSet::new(),
@@ -19,12 +19,14 @@ pub(crate) struct Receiver(Arc<Inner>);
 struct Inner {
     waker: AtomicWaker,
     set: AtomicBool,
+    closed: AtomicBool,
 }
 
 pub fn channel() -> (Sender, Receiver) {
     let inner = Arc::new(Inner {
         waker: AtomicWaker::new(),
         set: AtomicBool::new(false),
+        closed: AtomicBool::new(false),
     });
     (Sender(Arc::clone(&inner)), Receiver(inner))
 }
@@ -34,6 +36,18 @@ impl Sender {
         self.0.set.store(true, Relaxed);
         self.0.waker.wake();
     }
+
+    /// Ends the stream of notifications, even if one is pending.
+    pub fn close(&mut self) {
+        self.0.closed.store(true, Relaxed);
+        self.0.waker.wake();
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        self.close();
+    }
 }
 
 impl Stream for Receiver {
@@ -45,7 +59,9 @@ impl Stream for Receiver {
     ) -> Poll<Option<Self::Item>> {
         self.0.waker.register(cx.waker());
 
-        if self.0.set.swap(false, Relaxed) {
+        if self.0.closed.load(Relaxed) {
+            Poll::Ready(None)
+        } else if self.0.set.swap(false, Relaxed) {
             Poll::Ready(Some(()))
         } else {
             Poll::Pending
@@ -0,0 +1,716 @@
+//! Signals whose values outlive the app.
+//!
+//! An [`ArcPersistedSignal`] loads its initial value from a [`Storage`]
+//! backend, and writes its value back to it whenever it is updated. The value
+//! is encoded with any of the [`SerializableData`] encodings, so the same
+//! signal can be saved to the browser's `localStorage` or `sessionStorage` in a
+//! web app, or to a [`JsonFileStorage`] in a native one.
+//!
+//! ```rust
+//! # use tachy_reaccy::{persist::{ArcPersistedSignal, MemoryStorage}, prelude::*};
+//! let storage = MemoryStorage::default();
+//!
+//! let count = ArcPersistedSignal::new("count", storage.clone(), 0);
+//! count.set(3);
+//!
+//! // e.g., the next time the app starts
+//! let count = ArcPersistedSignal::new("count", storage, 0);
+//! assert_eq!(count.get_untracked(), 3);
+//! ```
+//!
+//! Each value is stored along with a schema version, so that a value saved by
+//! an older version of the app can be migrated when it is loaded. See
+//! [`PersistOptions`].
+
+use crate::{
+    arena::{Stored, StoredData},
+    serialization::{SerdeJson, SerializableData, Serializer},
+    signal::ArcRwSignal,
+    signal_traits::*,
+    source::{AnySource, AnySubscriber, ReactiveNode, Source, ToAnySource},
+    spawn::{sleep, spawn},
+};
+use futures::future::{AbortHandle, Abortable};
+use parking_lot::{Mutex, RwLock};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::{self, Debug},
+    fs, io,
+    marker::PhantomData,
+    panic::Location,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use thiserror::Error;
+
+/// A key-value store that persisted signals save their values to.
+pub trait Storage: Send + Sync + 'static {
+    /// Returns the value saved under `key`, if any.
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError>;
+
+    /// Saves `value` under `key`, replacing any previous value.
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError>;
+
+    /// Removes the value saved under `key`, if any.
+    fn remove(&self, key: &str) -> Result<(), StorageError>;
+}
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("I/O error {0:?}")]
+    Io(#[from] io::Error),
+    #[error("serde_json error {0:?}")]
+    Json(#[from] serde_json::Error),
+    #[error("browser storage error {0}")]
+    Browser(String),
+}
+
+/// A [`Storage`] that keeps values in memory, e.g. for tests.
+///
+/// Clones share the same values.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage(Arc<RwLock<FxHashMap<String, String>>>);
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Ok(self.0.read().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.0.write().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        self.0.write().remove(key);
+        Ok(())
+    }
+}
+
+/// A [`Storage`] that saves all its values in a single JSON file, which is
+/// created when the first value is saved.
+///
+/// The whole file is rewritten each time a value is saved, so it is best
+/// suited to small amounts of state, like user preferences.
+#[derive(Debug, Clone)]
+pub struct JsonFileStorage {
+    path: PathBuf,
+    // only one thread rewrites the file at a time
+    lock: Arc<Mutex<()>>,
+}
+
+impl JsonFileStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Default::default(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, String>, StorageError> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Ok(BTreeMap::new())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn modify(
+        &self,
+        fun: impl FnOnce(&mut BTreeMap<String, String>),
+    ) -> Result<(), StorageError> {
+        let _lock = self.lock.lock();
+        let mut values = self.read()?;
+        fun(&mut values);
+        // write the new contents beside the file first, so that a crash
+        // midway does not lose the values that were already saved
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&values)?)?;
+        fs::rename(tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Storage for JsonFileStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        let _lock = self.lock.lock();
+        Ok(self.read()?.remove(key))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.modify(|values| {
+            values.insert(key.to_string(), value.to_string());
+        })
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        self.modify(|values| {
+            values.remove(key);
+        })
+    }
+}
+
+#[cfg(feature = "web")]
+mod web {
+    use super::{Storage, StorageError};
+
+    /// A [`Storage`] that saves values in the browser's `localStorage`, which
+    /// is kept across browser sessions.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct LocalStorage;
+
+    /// A [`Storage`] that saves values in the browser's `sessionStorage`,
+    /// which is cleared when the page session ends.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct SessionStorage;
+
+    fn browser_error(e: wasm_bindgen::JsValue) -> StorageError {
+        StorageError::Browser(format!("{e:?}"))
+    }
+
+    fn unavailable() -> StorageError {
+        StorageError::Browser("storage is not available".to_string())
+    }
+
+    macro_rules! web_storage {
+        ($ty:ident, $getter:ident) => {
+            impl $ty {
+                fn storage(&self) -> Result<web_sys::Storage, StorageError> {
+                    web_sys::window()
+                        .ok_or_else(unavailable)?
+                        .$getter()
+                        .map_err(browser_error)?
+                        .ok_or_else(unavailable)
+                }
+            }
+
+            impl Storage for $ty {
+                fn get(
+                    &self,
+                    key: &str,
+                ) -> Result<Option<String>, StorageError> {
+                    self.storage()?.get_item(key).map_err(browser_error)
+                }
+
+                fn set(
+                    &self,
+                    key: &str,
+                    value: &str,
+                ) -> Result<(), StorageError> {
+                    self.storage()?.set_item(key, value).map_err(browser_error)
+                }
+
+                fn remove(&self, key: &str) -> Result<(), StorageError> {
+                    self.storage()?.remove_item(key).map_err(browser_error)
+                }
+            }
+        };
+    }
+
+    web_storage!(LocalStorage, local_storage);
+    web_storage!(SessionStorage, session_storage);
+}
+#[cfg(feature = "web")]
+pub use web::*;
+
+/// Converts a value saved with an older schema version into the current one.
+type Migration = dyn Fn(u32, String) -> Option<String> + Send + Sync;
+
+/// How a persisted signal loads and saves its value.
+#[derive(Default)]
+pub struct PersistOptions {
+    version: u32,
+    debounce: Option<Duration>,
+    migrate: Option<Box<Migration>>,
+}
+
+impl Debug for PersistOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistOptions")
+            .field("version", &self.version)
+            .field("debounce", &self.debounce)
+            .field("migrate", &self.migrate.is_some())
+            .finish()
+    }
+}
+
+impl PersistOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the schema version saved with the value, which defaults to `0`.
+    ///
+    /// A value saved with any other version is passed to the
+    /// [`migrate`](Self::migrate) hook when it is loaded, or ignored if there
+    /// is none.
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Waits until the value has not been updated for `delay` before saving
+    /// it, so that a burst of updates is saved once. Each update restarts
+    /// the wait, which is spawned as a task that sleeps with the installed
+    /// [`Executor`](crate::spawn::Executor).
+    ///
+    /// Without a delay, the value is saved each time it is updated.
+    pub fn debounce(mut self, delay: Duration) -> Self {
+        self.debounce = Some(delay);
+        self
+    }
+
+    /// Sets the hook that converts a value saved with another schema version
+    /// into the current one. It receives the saved version and the encoded
+    /// value, and returns the value encoded in the current schema, or `None`
+    /// to ignore it.
+    pub fn migrate(
+        mut self,
+        migrate: impl Fn(u32, String) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.migrate = Some(Box::new(migrate));
+        self
+    }
+}
+
+/// The form in which a value is saved.
+#[derive(Serialize, Deserialize)]
+struct Saved<'a> {
+    version: u32,
+    data: Cow<'a, str>,
+}
+
+/// A signal that loads its initial value from a [`Storage`], and saves its
+/// value to it when it is updated.
+///
+/// Updates made without notifying subscribers, like
+/// [`update_untracked`](SignalUpdateUntracked::try_update_untracked), are
+/// saved along with the next tracked update.
+pub struct ArcPersistedSignal<T, Ser = SerdeJson> {
+    signal: ArcRwSignal<T>,
+    persister: Arc<Persister>,
+    ser: PhantomData<fn() -> Ser>,
+}
+
+struct Persister {
+    key: String,
+    storage: Box<dyn Storage>,
+    version: u32,
+    debounce: Option<Duration>,
+    /// Whether an update has been made since the value was last saved.
+    pending: AtomicBool,
+    /// Cancels the debounced save that is waiting, if any.
+    timer: Mutex<Option<AbortHandle>>,
+}
+
+impl<T, Ser> Clone for ArcPersistedSignal<T, Ser> {
+    #[track_caller]
+    fn clone(&self) -> Self {
+        Self {
+            signal: self.signal.clone(),
+            persister: Arc::clone(&self.persister),
+            ser: PhantomData,
+        }
+    }
+}
+
+impl<T, Ser> Debug for ArcPersistedSignal<T, Ser> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcPersistedSignal")
+            .field("key", &self.persister.key)
+            .field("version", &self.persister.version)
+            .field("signal", &self.signal)
+            .finish()
+    }
+}
+
+impl<T> ArcPersistedSignal<T, SerdeJson>
+where
+    T: SerializableData<SerdeJson> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    /// Creates a signal that is saved under `key` using [`SerdeJson`], with
+    /// the value saved there if any, or `value` otherwise.
+    #[track_caller]
+    pub fn new(
+        key: impl Into<String>,
+        storage: impl Storage,
+        value: T,
+    ) -> Self {
+        Self::new_with_options(key, storage, value, PersistOptions::default())
+    }
+
+    /// Creates a signal that is saved under `key` using [`SerdeJson`]. See
+    /// [`new_with_encoding`](Self::new_with_encoding).
+    #[track_caller]
+    pub fn new_with_options(
+        key: impl Into<String>,
+        storage: impl Storage,
+        value: T,
+        options: PersistOptions,
+    ) -> Self {
+        Self::new_with_encoding(key, storage, value, options)
+    }
+}
+
+impl<T, Ser> ArcPersistedSignal<T, Ser>
+where
+    Ser: Serializer + 'static,
+    T: SerializableData<Ser> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    /// Creates a signal that is saved under `key` using the given
+    /// [`Serializer`].
+    ///
+    /// The initial value is loaded from the storage, migrating it if it was
+    /// saved with another schema version, or is `value` if nothing could be
+    /// loaded.
+    #[track_caller]
+    pub fn new_with_encoding(
+        key: impl Into<String>,
+        storage: impl Storage,
+        value: T,
+        options: PersistOptions,
+    ) -> Self {
+        let PersistOptions {
+            version,
+            debounce,
+            migrate,
+        } = options;
+        let persister = Persister {
+            key: key.into(),
+            storage: Box::new(storage),
+            version,
+            debounce,
+            pending: AtomicBool::new(false),
+            timer: Mutex::new(None),
+        };
+        let (value, migrated) = persister
+            .load::<T, Ser>(migrate.as_deref())
+            .unwrap_or((value, false));
+        let this = Self {
+            signal: ArcRwSignal::new(value),
+            persister: Arc::new(persister),
+            ser: PhantomData,
+        };
+        // save the migrated value, so it is only migrated once
+        if migrated {
+            this.save();
+        }
+        this
+    }
+
+    /// The key the value is saved under.
+    pub fn key(&self) -> &str {
+        &self.persister.key
+    }
+
+    /// Saves the value right away if an update is waiting to be saved, e.g.
+    /// before the app exits.
+    pub fn flush(&self) {
+        if self.persister.pending.swap(false, Ordering::AcqRel) {
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        let key = &self.persister.key;
+        let data = match self.signal.with_untracked(T::ser) {
+            Ok(data) => data,
+            Err(e) => {
+                crate::log(&format!("could not serialize {key:?}: {e:?}"));
+                return;
+            }
+        };
+        let saved = Saved {
+            version: self.persister.version,
+            data: Cow::Owned(data),
+        };
+        let res = serde_json::to_string(&saved)
+            .map_err(StorageError::from)
+            .and_then(|saved| self.persister.storage.set(key, &saved));
+        if let Err(e) = res {
+            crate::log(&format!("could not save {key:?}: {e}"));
+        }
+    }
+
+    fn schedule_save(&self) {
+        self.persister.pending.store(true, Ordering::Release);
+        let Some(delay) = self.persister.debounce else {
+            self.flush();
+            return;
+        };
+
+        // restart the wait, so that the value is only saved once the updates
+        // have stopped for `delay`
+        let (abort, registration) = AbortHandle::new_pair();
+        if let Some(prev) = self.persister.timer.lock().replace(abort) {
+            prev.abort();
+        }
+        let wait = Abortable::new(sleep(delay), registration);
+        let this = self.clone();
+        spawn(async move {
+            if wait.await.is_ok() {
+                this.flush();
+            }
+        });
+    }
+}
+
+impl Persister {
+    // returns the saved value, and whether it was migrated from another
+    // schema version
+    fn load<T, Ser>(&self, migrate: Option<&Migration>) -> Option<(T, bool)>
+    where
+        Ser: Serializer,
+        T: SerializableData<Ser>,
+        T::DeErr: Debug,
+    {
+        let key = &self.key;
+        let saved = match self.storage.get(key) {
+            Ok(saved) => saved?,
+            Err(e) => {
+                crate::log(&format!("could not load {key:?}: {e}"));
+                return None;
+            }
+        };
+        let Saved { version, data } = match serde_json::from_str(&saved) {
+            Ok(saved) => saved,
+            Err(e) => {
+                crate::log(&format!("could not load {key:?}: {e}"));
+                return None;
+            }
+        };
+        let migrated = version != self.version;
+        let data = if migrated {
+            migrate?(version, data.into_owned())?
+        } else {
+            data.into_owned()
+        };
+        match T::de(&data) {
+            Ok(value) => Some((value, migrated)),
+            Err(e) => {
+                crate::log(&format!("could not deserialize {key:?}: {e:?}"));
+                None
+            }
+        }
+    }
+}
+
+impl<T, Ser> ReactiveNode for ArcPersistedSignal<T, Ser> {
+    fn mark_dirty(&self) {
+        self.signal.mark_dirty();
+    }
+
+    fn mark_check(&self) {}
+
+    fn mark_subscribers_check(&self) {
+        self.signal.mark_subscribers_check();
+    }
+
+    fn update_if_necessary(&self) -> bool {
+        self.signal.update_if_necessary()
+    }
+}
+
+impl<T, Ser> ToAnySource for ArcPersistedSignal<T, Ser> {
+    fn to_any_source(&self) -> AnySource {
+        self.signal.to_any_source()
+    }
+}
+
+impl<T, Ser> Source for ArcPersistedSignal<T, Ser> {
+    fn clear_subscribers(&self) {
+        self.signal.clear_subscribers();
+    }
+
+    fn add_subscriber(&self, subscriber: AnySubscriber) {
+        self.signal.add_subscriber(subscriber);
+    }
+
+    fn remove_subscriber(&self, subscriber: &AnySubscriber) {
+        self.signal.remove_subscriber(subscriber);
+    }
+}
+
+impl<T, Ser> DefinedAt for ArcPersistedSignal<T, Ser> {
+    #[inline(always)]
+    fn defined_at(&self) -> Option<&'static Location<'static>> {
+        self.signal.defined_at()
+    }
+}
+
+impl<T, Ser> SignalWithUntracked for ArcPersistedSignal<T, Ser> {
+    type Value = T;
+
+    fn try_with_untracked<U>(
+        &self,
+        fun: impl FnOnce(&Self::Value) -> U,
+    ) -> Option<U> {
+        self.signal.try_with_untracked(fun)
+    }
+}
+
+impl<T, Ser> Trigger for ArcPersistedSignal<T, Ser>
+where
+    Ser: Serializer + 'static,
+    T: SerializableData<Ser> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    fn trigger(&self) {
+        self.signal.trigger();
+        self.schedule_save();
+    }
+}
+
+impl<T, Ser> SignalUpdateUntracked for ArcPersistedSignal<T, Ser> {
+    type Value = T;
+
+    fn try_update_untracked<U>(
+        &self,
+        fun: impl FnOnce(&mut Self::Value) -> U,
+    ) -> Option<U> {
+        self.signal.try_update_untracked(fun)
+    }
+}
+
+impl<T, Ser> SignalIsDisposed for ArcPersistedSignal<T, Ser> {
+    #[inline(always)]
+    fn is_disposed(&self) -> bool {
+        false
+    }
+}
+
+/// An arena-allocated [`ArcPersistedSignal`], which is `Copy` and is disposed
+/// along with the reactive owner that created it.
+pub struct PersistedSignal<T, Ser = SerdeJson>
+where
+    T: Send + Sync + 'static,
+    Ser: 'static,
+{
+    inner: Stored<ArcPersistedSignal<T, Ser>>,
+}
+
+impl<T> PersistedSignal<T, SerdeJson>
+where
+    T: SerializableData<SerdeJson> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    /// Creates a signal that is saved under `key`. See
+    /// [`ArcPersistedSignal::new`].
+    #[track_caller]
+    pub fn new(
+        key: impl Into<String>,
+        storage: impl Storage,
+        value: T,
+    ) -> Self {
+        Self::from_arc(ArcPersistedSignal::new(key, storage, value))
+    }
+
+    /// Creates a signal that is saved under `key`. See
+    /// [`ArcPersistedSignal::new_with_options`].
+    #[track_caller]
+    pub fn new_with_options(
+        key: impl Into<String>,
+        storage: impl Storage,
+        value: T,
+        options: PersistOptions,
+    ) -> Self {
+        Self::from_arc(ArcPersistedSignal::new_with_options(
+            key, storage, value, options,
+        ))
+    }
+}
+
+impl<T, Ser> PersistedSignal<T, Ser>
+where
+    Ser: Serializer + 'static,
+    T: SerializableData<Ser> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    /// Creates a signal that is saved under `key` using the given
+    /// [`Serializer`]. See [`ArcPersistedSignal::new_with_encoding`].
+    #[track_caller]
+    pub fn new_with_encoding(
+        key: impl Into<String>,
+        storage: impl Storage,
+        value: T,
+        options: PersistOptions,
+    ) -> Self {
+        Self::from_arc(ArcPersistedSignal::new_with_encoding(
+            key, storage, value, options,
+        ))
+    }
+
+    #[track_caller]
+    pub fn from_arc(signal: ArcPersistedSignal<T, Ser>) -> Self {
+        Self {
+            inner: Stored::new(signal),
+        }
+    }
+
+    /// Saves the value right away if an update is waiting to be saved. See
+    /// [`ArcPersistedSignal::flush`].
+    pub fn flush(&self) {
+        if let Some(inner) = self.inner.get() {
+            inner.flush();
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static, Ser: 'static> Copy for PersistedSignal<T, Ser> {}
+
+impl<T: Send + Sync + 'static, Ser: 'static> Clone for PersistedSignal<T, Ser> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Send + Sync + 'static, Ser: 'static> Debug for PersistedSignal<T, Ser> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistedSignal")
+            .field("type", &std::any::type_name::<T>())
+            .field("store", &self.inner)
+            .finish()
+    }
+}
+
+impl<T: Send + Sync + 'static, Ser: 'static> SignalIsDisposed
+    for PersistedSignal<T, Ser>
+{
+    fn is_disposed(&self) -> bool {
+        !self.inner.exists()
+    }
+}
+
+impl<T: Send + Sync + 'static, Ser: 'static> StoredData
+    for PersistedSignal<T, Ser>
+{
+    type Data = ArcPersistedSignal<T, Ser>;
+
+    fn get_value(&self) -> Option<Self::Data> {
+        self.inner.get()
+    }
+
+    fn dispose(&self) {
+        self.inner.dispose();
+    }
+}
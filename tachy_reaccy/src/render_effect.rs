@@ -1,6 +1,7 @@
 use crate::{
     arena::Owner,
     effect::EffectInner,
+    graph::{self, NodeKind},
     notify::channel,
     source::{AnySubscriber, SourceSet, Subscriber, ToAnySubscriber},
     spawn::spawn_local,
@@ -10,6 +11,7 @@ use parking_lot::RwLock;
 use std::{
     fmt::Debug,
     mem,
+    panic::Location,
     sync::{Arc, Weak},
 };
 
@@ -33,28 +35,54 @@ impl<T> RenderEffect<T>
 where
     T: 'static,
 {
+    #[track_caller]
     pub fn new(fun: impl FnMut(Option<T>) -> T + 'static) -> Self {
         Self::new_with_value(fun, None)
     }
 
+    #[track_caller]
     pub fn new_with_value(
         mut fun: impl FnMut(Option<T>) -> T + 'static,
         initial_value: Option<T>,
     ) -> Self {
         let (observer, mut rx) = channel();
         let value = Arc::new(RwLock::new(None));
-        let owner = Owner::new();
+        let owner = Owner::new_transparent();
         let inner = Arc::new(RwLock::new(EffectInner {
             owner: owner.clone(),
             observer,
             sources: SourceSet::new(),
         }));
+        graph::register(
+            NodeKind::RenderEffect,
+            Some(Location::caller()),
+            None,
+            None,
+            Some(inner.to_any_subscriber()),
+        );
+
+        // stop running when the parent owner is cleaned up or disposed
+        Owner::on_cleanup({
+            let inner = Arc::downgrade(&inner);
+            move || {
+                if let Some(inner) = inner.upgrade() {
+                    let owner = {
+                        let mut inner = inner.write();
+                        inner.observer.close();
+                        inner.owner.clone()
+                    };
+                    owner.dispose();
+                }
+            }
+        });
 
         let initial_value = Some(owner.with(|| {
             inner
                 .to_any_subscriber()
                 .with_observer(|| fun(initial_value))
         }));
+        #[cfg(feature = "testing")]
+        crate::testing::record_run(&inner.to_any_subscriber());
         *value.write() = initial_value;
 
         spawn_local({
@@ -70,6 +98,8 @@ where
                         subscriber.with_observer(|| fun(old_value))
                     });
                     *value.write() = Some(new_value);
+                    #[cfg(feature = "testing")]
+                    crate::testing::record_run(&subscriber);
                 }
             }
         });
@@ -200,3 +200,126 @@ mod rkyv {
 
 #[cfg(feature = "rkyv")]
 pub use rkyv::*;
+
+#[cfg(feature = "postcard")]
+mod postcard {
+    use super::{SerializableData, Serializer};
+    use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine as _};
+    use serde::{de::DeserializeOwned, Serialize};
+    use thiserror::Error;
+
+    /// A [`Serializer`] that serializes and deserializes using the compact
+    /// binary format of [`postcard`], sent as base64.
+    ///
+    /// The serialized data is usually much smaller than JSON for data made up
+    /// of numbers, at the cost of not being human-readable.
+    pub struct Postcard;
+
+    impl Serializer for Postcard {}
+
+    #[derive(Error, Debug)]
+    pub enum PostcardError {
+        #[error("postcard error {0:?}")]
+        Postcard(postcard::Error),
+        #[error("base64 error {0:?}")]
+        Base64Decode(base64::DecodeError),
+    }
+
+    impl From<postcard::Error> for PostcardError {
+        fn from(value: postcard::Error) -> Self {
+            PostcardError::Postcard(value)
+        }
+    }
+
+    impl From<base64::DecodeError> for PostcardError {
+        fn from(value: base64::DecodeError) -> Self {
+            PostcardError::Base64Decode(value)
+        }
+    }
+
+    impl<T> SerializableData<Postcard> for T
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        type SerErr = PostcardError;
+        type DeErr = PostcardError;
+
+        fn ser(&self) -> Result<String, Self::SerErr> {
+            let bytes = postcard::to_allocvec(self)?;
+            Ok(STANDARD_NO_PAD.encode(bytes))
+        }
+
+        fn de(data: &str) -> Result<Self, Self::DeErr> {
+            let bytes = STANDARD_NO_PAD.decode(data.as_bytes())?;
+            Ok(postcard::from_bytes(&bytes)?)
+        }
+    }
+}
+
+#[cfg(feature = "postcard")]
+pub use postcard::*;
+
+#[cfg(feature = "compression")]
+mod compression {
+    use super::{SerializableData, Serializer};
+    use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine as _};
+    use core::marker::PhantomData;
+    use miniz_oxide::inflate::DecompressError;
+    use std::string::FromUtf8Error;
+    use thiserror::Error;
+
+    /// A [`Serializer`] that serializes using another [`Serializer`], then
+    /// compresses the result with DEFLATE and sends it as base64.
+    ///
+    /// This pays off for large payloads with a lot of repetition, like the
+    /// field names in a long list of JSON objects, e.g. `Compressed<SerdeJson>`.
+    pub struct Compressed<Ser>(PhantomData<Ser>);
+
+    impl<Ser: Serializer> Serializer for Compressed<Ser> {}
+
+    /// The compression level, from 0 (none) to 10 (smallest).
+    const LEVEL: u8 = 6;
+
+    #[derive(Error, Debug)]
+    pub enum CompressedError<E> {
+        #[error("{0:?}")]
+        Inner(E),
+        #[error("base64 error {0:?}")]
+        Base64Decode(base64::DecodeError),
+        #[error("decompression error {0:?}")]
+        Decompress(DecompressError),
+        #[error("utf-8 error {0:?}")]
+        Utf8(FromUtf8Error),
+    }
+
+    impl<T, Ser> SerializableData<Compressed<Ser>> for T
+    where
+        Ser: Serializer,
+        T: SerializableData<Ser>,
+    {
+        type SerErr = T::SerErr;
+        type DeErr = CompressedError<T::DeErr>;
+
+        fn ser(&self) -> Result<String, Self::SerErr> {
+            let data = SerializableData::<Ser>::ser(self)?;
+            let bytes =
+                miniz_oxide::deflate::compress_to_vec(data.as_bytes(), LEVEL);
+            Ok(STANDARD_NO_PAD.encode(bytes))
+        }
+
+        fn de(data: &str) -> Result<Self, Self::DeErr> {
+            let bytes = STANDARD_NO_PAD
+                .decode(data.as_bytes())
+                .map_err(CompressedError::Base64Decode)?;
+            let bytes = miniz_oxide::inflate::decompress_to_vec(&bytes)
+                .map_err(CompressedError::Decompress)?;
+            let data =
+                String::from_utf8(bytes).map_err(CompressedError::Utf8)?;
+            <T as SerializableData<Ser>>::de(&data)
+                .map_err(CompressedError::Inner)
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+pub use compression::*;
@@ -0,0 +1,295 @@
+//! Server functions: functions that run on the server, and that the client
+//! calls over HTTP.
+//!
+//! A server function is usually defined with the `#[server]` macro, which
+//! bundles its arguments into a struct that implements [`ServerFn`]. On the
+//! client, calling the function serializes its arguments with one of the
+//! [`serialization`](crate::serialization) encodings and POSTs them to the
+//! server with the installed [`Client`]. On the server, a [`ServerFnRegistry`]
+//! maps the path of each server function to a handler that deserializes the
+//! arguments, runs the function, and serializes its return value.
+//!
+//! ```
+//! # use tachy_reaccy::{serialization::Str, server_fn::*};
+//! # use std::{fmt, str::FromStr};
+//! // the arguments of `double(n: i32)`, as the `#[server]` macro defines them
+//! struct Double(i32);
+//! # impl fmt::Display for Double {
+//! #     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.0.fmt(f) }
+//! # }
+//! # impl FromStr for Double {
+//! #     type Err = std::num::ParseIntError;
+//! #     fn from_str(s: &str) -> Result<Self, Self::Err> { s.parse().map(Double) }
+//! # }
+//!
+//! impl ServerFn for Double {
+//!     const PATH: &'static str = "/api/double";
+//!     type Encoding = Str;
+//!     type Output = i32;
+//!
+//!     fn run_body(self) -> ServerFnFuture<i32> {
+//!         Box::pin(async move { Ok(self.0 * 2) })
+//!     }
+//! }
+//!
+//! let registry = ServerFnRegistry::new().register::<Double>();
+//! let response = futures::executor::block_on(registry.handle(Request {
+//!     path: "/api/double".into(),
+//!     body: "21".into(),
+//! }));
+//! assert_eq!(response.body, "42");
+//! ```
+
+use crate::{
+    serialization::{SerializableData, Serializer},
+    PinnedFuture,
+};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fmt::Debug, future::Future, pin::Pin, sync::OnceLock};
+use thiserror::Error;
+
+/// The future returned by the body of a server function.
+pub type ServerFnFuture<T> =
+    Pin<Box<dyn Future<Output = Result<T, ServerFnError>> + Send>>;
+
+/// A function that runs on the server, and that the client calls over HTTP.
+///
+/// This is implemented for the struct that holds the arguments of the
+/// function, which is serialized using [`ServerFn::Encoding`].
+pub trait ServerFn: Send + Sized + 'static {
+    /// The path at which the server function is served.
+    const PATH: &'static str;
+
+    /// How the arguments and the return value are serialized.
+    type Encoding: Serializer;
+
+    /// The value returned by the server function, if it succeeds.
+    type Output;
+
+    /// Runs the server function. This should only be called on the server.
+    fn run_body(self) -> ServerFnFuture<Self::Output>;
+}
+
+/// An error that prevented a server function from returning a value.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize, Deserialize)]
+pub enum ServerFnError {
+    /// The request could not be sent, or the server could not be reached.
+    #[error("error sending the request: {0}")]
+    Request(String),
+    /// The arguments or the return value could not be serialized.
+    #[error("error serializing: {0}")]
+    Serialization(String),
+    /// The arguments or the return value could not be deserialized.
+    #[error("error deserializing: {0}")]
+    Deserialization(String),
+    /// There is no server function at the requested path.
+    #[error("no server function was found at {0}")]
+    NotFound(String),
+    /// The server function ran, and returned an error.
+    #[error("error running the server function: {0}")]
+    ServerError(String),
+}
+
+impl ServerFnError {
+    /// Creates an error to return from the body of a server function.
+    pub fn new(message: impl ToString) -> Self {
+        Self::ServerError(message.to_string())
+    }
+}
+
+/// A request to call a server function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// The path of the server function.
+    pub path: String,
+    /// The serialized arguments.
+    pub body: String,
+}
+
+/// The response to a request to call a server function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The HTTP status code: `200` if the server function succeeded.
+    pub status: u16,
+    /// The serialized return value if the server function succeeded, or the
+    /// [`ServerFnError`] as JSON if it did not.
+    pub body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, error: &ServerFnError) -> Self {
+        Self {
+            status,
+            body: serde_json::to_string(error)
+                .expect("couldn't serialize ServerFnError"),
+        }
+    }
+}
+
+/// Sends requests to call server functions from the client, usually as HTTP
+/// POST requests.
+pub trait Client: Send + Sync {
+    /// Sends the request to the server, and resolves with its response.
+    fn send(
+        &self,
+        req: Request,
+    ) -> PinnedFuture<Result<Response, ServerFnError>>;
+}
+
+static CLIENT: OnceLock<Box<dyn Client>> = OnceLock::new();
+
+/// The error returned by [`set_client`] if a client has already been set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientAlreadySet;
+
+impl fmt::Display for ClientAlreadySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a server function client has already been set")
+    }
+}
+
+impl std::error::Error for ClientAlreadySet {}
+
+/// Installs the client used to call all server functions.
+///
+/// This can only be done once, and should be done before any server functions
+/// are called.
+pub fn set_client(
+    client: impl Client + 'static,
+) -> Result<(), ClientAlreadySet> {
+    CLIENT.set(Box::new(client)).map_err(|_| ClientAlreadySet)
+}
+
+/// Calls the server function with the given arguments, using the installed
+/// [`Client`].
+pub async fn call<F>(args: F) -> Result<F::Output, ServerFnError>
+where
+    F: ServerFn + SerializableData<F::Encoding>,
+    <F as SerializableData<F::Encoding>>::SerErr: Debug,
+    F::Output: SerializableData<F::Encoding>,
+    <F::Output as SerializableData<F::Encoding>>::DeErr: Debug,
+{
+    let body = args
+        .ser()
+        .map_err(|e| ServerFnError::Serialization(format!("{e:?}")))?;
+    let client = CLIENT.get().ok_or_else(|| {
+        ServerFnError::Request(
+            "no server function client has been set".to_string(),
+        )
+    })?;
+    let res = client
+        .send(Request {
+            path: F::PATH.to_string(),
+            body,
+        })
+        .await?;
+    if res.status == 200 {
+        F::Output::de(&res.body)
+            .map_err(|e| ServerFnError::Deserialization(format!("{e:?}")))
+    } else {
+        Err(serde_json::from_str(&res.body).unwrap_or_else(|_| {
+            ServerFnError::Request(format!(
+                "the server responded with {}: {}",
+                res.status, res.body
+            ))
+        }))
+    }
+}
+
+type Handler = Box<
+    dyn Fn(String) -> Pin<Box<dyn Future<Output = Response> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// The server functions that can be called by the client, by path.
+#[derive(Default)]
+pub struct ServerFnRegistry {
+    handlers: FxHashMap<&'static str, Handler>,
+}
+
+impl Debug for ServerFnRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerFnRegistry")
+            .field("paths", &self.handlers.keys())
+            .finish()
+    }
+}
+
+impl ServerFnRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the server function at its [`ServerFn::PATH`].
+    ///
+    /// # Panics
+    /// Panics if another server function has already been registered at the
+    /// same path.
+    pub fn register<F>(mut self) -> Self
+    where
+        F: ServerFn + SerializableData<F::Encoding>,
+        <F as SerializableData<F::Encoding>>::DeErr: Debug,
+        F::Output: SerializableData<F::Encoding>,
+        <F::Output as SerializableData<F::Encoding>>::SerErr: Debug,
+    {
+        let prev = self.handlers.insert(
+            F::PATH,
+            Box::new(|body| {
+                Box::pin(async move {
+                    let args = match F::de(&body) {
+                        Ok(args) => args,
+                        Err(e) => {
+                            let e = ServerFnError::Deserialization(format!(
+                                "{e:?}"
+                            ));
+                            return Response::error(400, &e);
+                        }
+                    };
+                    match args.run_body().await {
+                        Ok(value) => match value.ser() {
+                            Ok(body) => Response::ok(body),
+                            Err(e) => Response::error(
+                                500,
+                                &ServerFnError::Serialization(format!("{e:?}")),
+                            ),
+                        },
+                        Err(e) => Response::error(500, &e),
+                    }
+                })
+            }),
+        );
+        assert!(
+            prev.is_none(),
+            "two server functions were registered at {}",
+            F::PATH
+        );
+        self
+    }
+
+    /// The paths of the registered server functions, to add to the route
+    /// table of an HTTP server.
+    pub fn paths(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.handlers.keys().copied()
+    }
+
+    /// Runs the server function at the path of the request, resolving with
+    /// the response to send to the client.
+    pub fn handle(
+        &self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        match self.handlers.get(req.path.as_str()) {
+            Some(handler) => handler(req.body),
+            None => {
+                let e = ServerFnError::NotFound(req.path);
+                Box::pin(async move { Response::error(404, &e) })
+            }
+        }
+    }
+}
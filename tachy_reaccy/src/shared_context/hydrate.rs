@@ -1,25 +1,29 @@
-use super::{SerializedDataId, SharedContext};
+use super::{IssuedIds, SerializationError, SerializedDataId, SharedContext};
 use crate::{PinnedFuture, PinnedStream};
 use core::fmt::Debug;
-use js_sys::Array;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use wasm_bindgen::prelude::wasm_bindgen;
+use futures::channel::{mpsc, oneshot};
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::{
+    prelude::{wasm_bindgen, Closure},
+    JsValue,
+};
 
 #[wasm_bindgen]
 extern "C" {
-    static __RESOLVED_RESOURCES: Array;
+    static __RESOLVED_RESOURCES: Object;
+    static __PENDING_RESOURCES: Array;
+    static __RESOURCE_STREAMS: Object;
+    static __RESOURCE_RESOLVERS: Object;
 }
 
 #[derive(Default)]
 pub struct HydrateSharedContext {
-    id: AtomicUsize,
+    ids: IssuedIds,
 }
 
 impl HydrateSharedContext {
     pub fn new() -> Self {
-        Self {
-            id: AtomicUsize::new(0),
-        }
+        Self::default()
     }
 }
 
@@ -31,21 +35,122 @@ impl Debug for HydrateSharedContext {
 
 impl SharedContext for HydrateSharedContext {
     fn next_id(&self) -> SerializedDataId {
-        let id = self.id.fetch_add(1, Ordering::Relaxed);
-        SerializedDataId(id)
+        let id = SerializedDataId::next();
+        self.ids.check(&id);
+        id
+    }
+
+    fn write_async(
+        &self,
+        _id: SerializedDataId,
+        _fut: PinnedFuture<Result<String, SerializationError>>,
+    ) {
+    }
+
+    fn write_stream(
+        &self,
+        _id: SerializedDataId,
+        _stream: PinnedStream<Result<String, SerializationError>>,
+    ) {
+    }
+
+    fn read_data(
+        &self,
+        id: &SerializedDataId,
+    ) -> Option<Result<String, SerializationError>> {
+        resolved_data(id)
     }
 
-    fn write_async(&self, _id: SerializedDataId, _fut: PinnedFuture<String>) {}
+    fn await_data(
+        &self,
+        id: &SerializedDataId,
+    ) -> Option<PinnedFuture<Result<String, SerializationError>>> {
+        if let Some(data) = self.read_data(id) {
+            return Some(Box::pin(async move { data }));
+        }
+        let key = JsValue::from_str(&id.to_string());
+        if !__PENDING_RESOURCES.includes(&key, 0) {
+            return None;
+        }
 
-    fn read_data(&self, id: &SerializedDataId) -> Option<String> {
-        __RESOLVED_RESOURCES.get(id.0 as u32).as_string()
+        // the server calls the resolver once it has streamed the data
+        let (tx, rx) = oneshot::channel();
+        let resolve = {
+            let id = id.clone();
+            Closure::once_into_js(move || {
+                if let Some(data) = resolved_data(&id) {
+                    _ = tx.send(data);
+                }
+            })
+        };
+        _ = Reflect::set(&__RESOURCE_RESOLVERS, &key, &resolve);
+        Some(Box::pin(async move {
+            rx.await.unwrap_or_else(|_| {
+                Err(SerializationError(
+                    "the server did not send this data".to_string(),
+                ))
+            })
+        }))
     }
 
-    fn await_data(&self, _id: &SerializedDataId) -> Option<String> {
-        todo!()
+    fn read_stream(
+        &self,
+        id: &SerializedDataId,
+    ) -> Option<PinnedStream<Result<String, SerializationError>>> {
+        let key = JsValue::from_str(&id.to_string());
+        let data = Reflect::get(&__RESOURCE_STREAMS, &key)
+            .ok()
+            .filter(|data| !data.is_undefined())?;
+        let data = Array::from(&data);
+
+        // sends the data that has arrived since it was last called, and
+        // closes the channel once the server marks the end of the stream
+        let (tx, rx) = mpsc::unbounded();
+        let mut sent = 0;
+        let mut send_arrived = move || {
+            while sent < data.length() {
+                let item = data.get(sent);
+                sent += 1;
+                match js_data(&item) {
+                    Some(item) => _ = tx.unbounded_send(item),
+                    None => {
+                        tx.close_channel();
+                        return;
+                    }
+                }
+            }
+        };
+        send_arrived();
+
+        // the server calls the resolver each time it has streamed more data
+        let resolve = Closure::<dyn FnMut()>::new(send_arrived);
+        _ = Reflect::set(&__RESOURCE_RESOLVERS, &key, &resolve.into_js_value());
+        Some(Box::pin(rx))
     }
 
     fn pending_data(&self) -> Option<PinnedStream<String>> {
         None
     }
 }
+
+fn resolved_data(
+    id: &SerializedDataId,
+) -> Option<Result<String, SerializationError>> {
+    let data = Reflect::get(
+        &__RESOLVED_RESOURCES,
+        &JsValue::from_str(&id.to_string()),
+    )
+    .ok()?;
+    js_data(&data)
+}
+
+fn js_data(data: &JsValue) -> Option<Result<String, SerializationError>> {
+    if let Some(data) = data.as_string() {
+        return Some(Ok(data));
+    }
+    // the server sends `{ error }` if it couldn't serialize the data
+    Reflect::get(data, &JsValue::from_str("error"))
+        .ok()?
+        .as_string()
+        .map(|e| Err(SerializationError(e)))
+}
@@ -1 +1,46 @@
-// TODO
+//! Support for islands, in which only some parts of a server-rendered page are
+//! hydrated.
+//!
+//! Every piece of data sent from the server has a [`SerializedDataId`] derived
+//! from the [position](Owner::position) of the owner it was created under. A
+//! client hydrating islands only creates the islands, not the owners around
+//! them. Instead, the server creates each island under its own owner with
+//! [`island_owner`] and records its position in the page, and the client
+//! creates the owner for that island at the same position with
+//! [`hydrate_island_owner`].
+//!
+//! [`SerializedDataId`]: super::SerializedDataId
+
+use crate::arena::Owner;
+
+/// Creates a new owner for an island on the server, returning it along with its
+/// position written as a string, or `None` if there is no shared context.
+///
+/// The owner lives as long as the current owner does, so that the data created
+/// under it can be serialized after the island has been rendered.
+pub fn island_owner() -> Option<(Owner, String)> {
+    Owner::shared_context()?;
+    let owner = Owner::new();
+    Owner::on_cleanup({
+        let owner = owner.clone();
+        move || drop(owner)
+    });
+    let position = owner
+        .position()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("-");
+    Some((owner, position))
+}
+
+/// Creates a new owner for an island on the client, at the position returned by
+/// [`island_owner`] on the server, or `None` if the position is not valid.
+pub fn hydrate_island_owner(position: &str) -> Option<Owner> {
+    let position = position
+        .split('-')
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    Some(Owner::new_at(position))
+}
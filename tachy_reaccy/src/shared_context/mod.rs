@@ -2,44 +2,102 @@
 mod hydrate;
 mod islands;
 mod ssr;
-use crate::{PinnedFuture, PinnedStream};
+mod state;
+use crate::{arena::Owner, PinnedFuture, PinnedStream};
 #[cfg(feature = "web")]
 pub use hydrate::*;
 pub use islands::*;
+#[cfg(debug_assertions)]
+use parking_lot::Mutex;
+#[cfg(debug_assertions)]
+use rustc_hash::FxHashSet;
 use serde::{Deserialize, Serialize};
 pub use ssr::*;
-use std::fmt::Debug;
+pub(crate) use state::serialize_state;
+use std::fmt::{Debug, Display};
+use thiserror::Error;
 
 pub trait SharedContext: Debug {
-    /// Returns the next in a series of IDs that is unique to a particular request and response.
+    /// Returns the ID for the next piece of data created under the current [`Owner`],
+    /// which is unique to a particular request and response.
+    ///
+    /// The ID is derived from the position of the owner in the tree of owners, rather
+    /// than from the order in which data is created across the whole page, so data
+    /// matches up between the server and the client even if they create it in a
+    /// different order. See [`SerializedDataId::next`].
     ///
     /// This should not be used as a global unique ID mechanism. It is specific to the process
     /// of serializing and deserializing data from the server to the browser as part of an HTTP
     /// response.
+    ///
+    /// [`Owner`]: crate::Owner
     fn next_id(&self) -> SerializedDataId;
 
     /// The given [`Future`] should resolve with some data that can be serialized
     /// from the server to the client. This will be polled as part of the process of
     /// building the HTTP response, *not* when it is first created.
     ///
+    /// If the data could not be serialized, the [`SerializationError`] is sent to the
+    /// client in its place.
+    ///
+    /// In browser implementations, this should be a no-op.
+    fn write_async(
+        &self,
+        id: SerializedDataId,
+        fut: PinnedFuture<Result<String, SerializationError>>,
+    );
+
+    /// The given [`Stream`] yields a series of pieces of data that should be sent from
+    /// the server to the client one at a time, as each of them arrives. The HTTP response
+    /// is not finished until the stream has ended.
+    ///
+    /// If a piece of data could not be serialized, the [`SerializationError`] is sent
+    /// to the client in its place.
+    ///
     /// In browser implementations, this should be a no-op.
-    fn write_async(&self, id: SerializedDataId, fut: PinnedFuture<String>);
+    fn write_stream(
+        &self,
+        id: SerializedDataId,
+        stream: PinnedStream<Result<String, SerializationError>>,
+    );
 
     /// Reads the current value of some data from the shared context, if it has been
     /// sent from the server. This returns the serialized data as a `String` that should
-    /// be deserialized using [`Serializable::de`].
+    /// be deserialized using [`Serializable::de`], or the error that prevented the server
+    /// from serializing it.
     ///
     /// On the server and in client-side rendered implementations, this should
     /// always return [`None`].
-    fn read_data(&self, id: &SerializedDataId) -> Option<String>;
+    fn read_data(
+        &self,
+        id: &SerializedDataId,
+    ) -> Option<Result<String, SerializationError>>;
 
     /// Returns a [`Future`] that resolves with a `String` that should
     /// be deserialized using [`Serializable::de`] once the given piece of server
-    /// data has resolved.
+    /// data has resolved, or with the error that prevented the server from
+    /// serializing it. If the data has already arrived, the [`Future`] is ready
+    /// immediately.
     ///
+    /// Returns [`None`] if the server is not going to send this piece of data.
     /// On the server and in client-side rendered implementations, this should
-    /// return a [`Future`] that is immediately ready with [`None`].
-    fn await_data(&self, id: &SerializedDataId) -> Option<String>;
+    /// always return [`None`].
+    fn await_data(
+        &self,
+        id: &SerializedDataId,
+    ) -> Option<PinnedFuture<Result<String, SerializationError>>>;
+
+    /// Returns a [`Stream`] of the pieces of data written by the server with
+    /// [`SharedContext::write_stream`], each as a `String` that should be deserialized
+    /// using [`Serializable::de`]. It yields the pieces that have already arrived, then
+    /// the rest as they arrive, and ends when the server's stream ends.
+    ///
+    /// Returns [`None`] if the server is not going to send this stream. On the server
+    /// and in client-side rendered implementations, this should always return [`None`].
+    fn read_stream(
+        &self,
+        id: &SerializedDataId,
+    ) -> Option<PinnedStream<Result<String, SerializationError>>>;
 
     /// Returns some [`Stream`] of HTML that contains JavaScript `<script>` tags defining
     /// all values being serialized from the server to the client, with their serialized values
@@ -49,11 +107,80 @@ pub trait SharedContext: Debug {
     fn pending_data(&self) -> Option<PinnedStream<String>>;
 }
 
+/// Identifies a piece of data serialized from the server to the client: the
+/// [position](crate::Owner::position) of the owner it was created under, followed
+/// by its index among the data created under that owner.
+///
+/// It is written as its parts separated by `-`, e.g. `0-2-1`.
 #[derive(
     Clone, Debug, PartialEq, Eq, Hash, Default, Deserialize, Serialize,
 )]
 #[serde(transparent)]
-pub struct SerializedDataId(usize);
+pub struct SerializedDataId(Vec<u16>);
+
+impl SerializedDataId {
+    pub fn new(id: Vec<u16>) -> Self {
+        Self(id)
+    }
+
+    /// Returns the ID for the next piece of data created under the current
+    /// [`Owner`](crate::Owner), or the default ID if there is no owner.
+    pub fn next() -> Self {
+        Owner::current()
+            .map(|owner| owner.next_data_id())
+            .unwrap_or_default()
+    }
+
+    pub fn into_inner(self) -> Vec<u16> {
+        self.0
+    }
+}
+
+impl Display for SerializedDataId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = self.0.iter();
+        if let Some(first) = parts.next() {
+            write!(f, "{first}")?;
+        }
+        for part in parts {
+            write!(f, "-{part}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The IDs that have been given out by a shared context, in debug builds.
+///
+/// IDs that are derived from positions in the tree of owners can collide if an
+/// owner is created at a position that is already taken, as when an island is
+/// hydrated twice. This would silently give the data of one resource to another,
+/// so it is reported instead.
+#[derive(Debug, Default)]
+pub(crate) struct IssuedIds(
+    #[cfg(debug_assertions)] Mutex<FxHashSet<SerializedDataId>>,
+);
+
+impl IssuedIds {
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    pub fn check(&self, id: &SerializedDataId) {
+        #[cfg(debug_assertions)]
+        if !self.0.lock().insert(id.clone()) {
+            panic!(
+                "The serialized data ID {id} was given out more than once. \
+                 Two owners were created at the same position, so their data \
+                 would be mixed up."
+            );
+        }
+    }
+}
+
+/// An error that prevented the server from serializing some data. It is sent to
+/// the client instead of the data, so that the client can show an error rather
+/// than the server panicking.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Error, Deserialize, Serialize)]
+#[error("{0}")]
+#[serde(transparent)]
+pub struct SerializationError(pub String);
 
 /*
 enum SerializableData {
@@ -1,6 +1,7 @@
-use super::{SerializedDataId, SharedContext};
+use super::{IssuedIds, SerializationError, SerializedDataId, SharedContext};
 use crate::{PinnedFuture, PinnedStream};
 use futures::{
+    future,
     stream::{self, FuturesUnordered},
     StreamExt,
 };
@@ -8,14 +9,17 @@ use parking_lot::RwLock;
 use std::{
     fmt::{Debug, Write},
     mem,
-    sync::atomic::{AtomicUsize, Ordering},
 };
 
+type AsyncData = PinnedFuture<Result<String, SerializationError>>;
+type StreamData = PinnedStream<Result<String, SerializationError>>;
+
 #[derive(Default)]
 pub struct SsrSharedContext {
-    id: AtomicUsize,
+    ids: IssuedIds,
     sync_buf: RwLock<Vec<ResolvedData>>,
-    async_buf: RwLock<Vec<(SerializedDataId, PinnedFuture<String>)>>,
+    async_buf: RwLock<Vec<(SerializedDataId, AsyncData)>>,
+    stream_buf: RwLock<Vec<(SerializedDataId, StreamData)>>,
 }
 
 impl SsrSharedContext {
@@ -27,68 +31,121 @@ impl SsrSharedContext {
 impl Debug for SsrSharedContext {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SsrSharedContext")
-            .field("id", &self.id)
+            .field("ids", &self.ids)
             .field("sync_buf", &self.sync_buf)
             .field("async_buf", &self.async_buf.read().len())
+            .field("stream_buf", &self.stream_buf.read().len())
             .finish()
     }
 }
 
 impl SharedContext for SsrSharedContext {
     fn next_id(&self) -> SerializedDataId {
-        let id = self.id.fetch_add(1, Ordering::Relaxed);
-        SerializedDataId(id)
+        let id = SerializedDataId::next();
+        self.ids.check(&id);
+        id
     }
 
-    fn write_async(&self, id: SerializedDataId, fut: PinnedFuture<String>) {
+    fn write_async(&self, id: SerializedDataId, fut: AsyncData) {
         self.async_buf.write().push((id, fut))
     }
 
+    fn write_stream(&self, id: SerializedDataId, stream: StreamData) {
+        self.stream_buf.write().push((id, stream))
+    }
+
     fn pending_data(&self) -> Option<PinnedStream<String>> {
         let sync_data = mem::take(&mut *self.sync_buf.write());
         let async_data = mem::take(&mut *self.async_buf.write());
+        let stream_data = mem::take(&mut *self.stream_buf.write());
 
         // 1) initial, synchronous setup chunk
         let mut initial_chunk = String::new();
-        // resolved synchronous resources
-        initial_chunk.push_str("__RESOLVED_RESOURCES=[");
+        // resolved synchronous resources, keyed by ID
+        initial_chunk.push_str("__RESOLVED_RESOURCES={");
         for resolved in sync_data {
             resolved.write_to_buf(&mut initial_chunk);
             initial_chunk.push(',');
         }
-        initial_chunk.push_str("];");
+        initial_chunk.push_str("};");
 
         // pending async resources
         initial_chunk.push_str("__PENDING_RESOURCES=[");
         for (id, _) in &async_data {
-            write!(&mut initial_chunk, "{},", id.0).unwrap();
+            write!(&mut initial_chunk, "\"{id}\",").unwrap();
         }
         initial_chunk.push_str("];");
 
+        // streams, which collect their data as it arrives
+        initial_chunk.push_str("__RESOURCE_STREAMS={");
+        for (id, _) in &stream_data {
+            write!(&mut initial_chunk, "\"{id}\":[],").unwrap();
+        }
+        initial_chunk.push_str("};");
+
         // resolvers
-        initial_chunk.push_str("__RESOURCE_RESOLVERS=[];");
+        initial_chunk.push_str("__RESOURCE_RESOLVERS={};");
 
         // 2) async resources as they resolve
         let async_data = async_data
             .into_iter()
             .map(|(id, data)| async move {
-                let data = data.await;
-                format!("__RESOLVED_RESOURCES[{}] = {data:?};", id.0)
+                let data = js_data(data.await);
+                // store the data, then wake up the client if it is waiting
+                format!(
+                    "__RESOLVED_RESOURCES[\"{id}\"] = \
+                     {data};__RESOURCE_RESOLVERS[\"{id}\"]?.();"
+                )
             })
             .collect::<FuturesUnordered<_>>();
 
-        let stream =
-            stream::once(async move { initial_chunk }).chain(async_data);
+        // 3) each piece of streamed data as it arrives, then a `null` to
+        //    mark the end of the stream
+        let stream_data = stream_data.into_iter().map(|(id, data)| {
+            data.map(js_data)
+                .chain(stream::once(future::ready("null".to_string())))
+                .map(move |data| {
+                    format!(
+                        "__RESOURCE_STREAMS[\"{id}\"].push({data});\
+                         __RESOURCE_RESOLVERS[\"{id}\"]?.();"
+                    )
+                })
+        });
+
+        let stream = stream::once(async move { initial_chunk })
+            .chain(stream::select(async_data, stream::select_all(stream_data)));
         Some(Box::pin(stream))
     }
 
-    fn read_data(&self, _id: &SerializedDataId) -> Option<String> {
+    fn read_data(
+        &self,
+        _id: &SerializedDataId,
+    ) -> Option<Result<String, SerializationError>> {
         None
     }
 
-    fn await_data(&self, _id: &SerializedDataId) -> Option<String> {
+    fn await_data(&self, _id: &SerializedDataId) -> Option<AsyncData> {
         None
     }
+
+    fn read_stream(&self, _id: &SerializedDataId) -> Option<StreamData> {
+        None
+    }
+}
+
+// writes some serialized data as a JavaScript value: errors are sent as an
+// object, so the client can tell them apart from serialized data
+fn js_data(data: Result<String, SerializationError>) -> String {
+    match data {
+        Ok(data) => js_string(&data),
+        Err(SerializationError(e)) => format!("{{error: {}}}", js_string(&e)),
+    }
+}
+
+// writes a string as a JavaScript string literal, escaping < to prevent it
+// being interpreted as another opening HTML tag, e.g. `</script>`
+fn js_string(s: &str) -> String {
+    format!("{s:?}").replace('<', "\\u003c")
 }
 
 #[derive(Debug)]
@@ -97,8 +154,6 @@ struct ResolvedData(SerializedDataId, String);
 impl ResolvedData {
     pub fn write_to_buf(&self, buf: &mut String) {
         let ResolvedData(id, ser) = self;
-        // escapes < to prevent it being interpreted as another opening HTML tag
-        let ser = ser.replace('<', "\\u003c");
-        write!(buf, "{}: {:?}", id.0, ser).unwrap();
+        write!(buf, "\"{id}\": {}", js_string(ser)).unwrap();
     }
 }
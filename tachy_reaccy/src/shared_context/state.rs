@@ -0,0 +1,78 @@
+use super::SerializationError;
+use crate::{
+    arena::Owner,
+    serialization::{SerializableData, Serializer},
+};
+use parking_lot::RwLock;
+use std::{fmt::Debug, sync::Arc};
+
+/// Serializes the state of a signal or store from the server to the client.
+///
+/// On the server, the value is serialized when the data for the response is
+/// built, so the client receives the final value after rendering rather than
+/// the value it was created with. While hydrating, the value is replaced with
+/// the one sent by the server: immediately if it has arrived, or with `set`
+/// once it arrives, if the server is still streaming it.
+#[cfg_attr(not(feature = "hydration"), allow(unused_variables))]
+pub(crate) fn serialize_state<T, Ser>(
+    value: &Arc<RwLock<T>>,
+    set: impl FnOnce(T) + Send + 'static,
+) where
+    Ser: Serializer,
+    T: SerializableData<Ser> + Send + Sync + 'static,
+    T::SerErr: Debug,
+    T::DeErr: Debug,
+{
+    let Some(shared_context) = Owner::shared_context() else {
+        return;
+    };
+    let id = shared_context.next_id();
+
+    #[cfg(feature = "hydration")]
+    {
+        if let Some(data) = shared_context.read_data(&id) {
+            if let Some(data) = deserialize::<T, Ser>(data) {
+                *value.write() = data;
+            }
+        } else if let Some(data) = shared_context.await_data(&id) {
+            crate::spawn::spawn(async move {
+                if let Some(data) = deserialize::<T, Ser>(data.await) {
+                    set(data);
+                }
+            });
+        }
+    }
+
+    let value = Arc::clone(value);
+    shared_context.write_async(
+        id,
+        Box::pin(async move {
+            <T as SerializableData<Ser>>::ser(&*value.read())
+                .map_err(|e| SerializationError(format!("{e:?}")))
+        }),
+    );
+}
+
+#[cfg(feature = "hydration")]
+fn deserialize<T, Ser>(data: Result<String, SerializationError>) -> Option<T>
+where
+    Ser: Serializer,
+    T: SerializableData<Ser>,
+    T::DeErr: Debug,
+{
+    match data {
+        Ok(data) => match T::de(&data) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                crate::log(&format!(
+                    "couldn't deserialize from {data:?}: {e:?}"
+                ));
+                None
+            }
+        },
+        Err(e) => {
+            crate::log(&format!("the server could not serialize state: {e}"));
+            None
+        }
+    }
+}
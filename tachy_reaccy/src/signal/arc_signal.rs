@@ -1,5 +1,9 @@
 use super::{ArcReadSignal, ArcWriteSignal};
 use crate::{
+    defer_mark_dirty,
+    graph::{self, NodeKind},
+    serialization::{SerdeJson, SerializableData, Serializer},
+    shared_context::serialize_state,
     signal_traits::*,
     source::{
         AnySource, AnySubscriber, ReactiveNode, Source, SubscriberSet,
@@ -46,13 +50,55 @@ impl<T> ArcRwSignal<T> {
         feature = "tracing",
         tracing::instrument(level = "trace", skip_all,)
     )]
+    #[track_caller]
     pub fn new(value: T) -> Self {
-        Self {
+        let this = Self {
             #[cfg(debug_assertions)]
             defined_at: Location::caller(),
             value: Arc::new(RwLock::new(value)),
             inner: Arc::new(RwLock::new(SubscriberSet::new())),
-        }
+        };
+        graph::register(
+            NodeKind::Signal,
+            this.defined_at(),
+            None,
+            Some(this.to_any_source()),
+            None,
+        );
+        this
+    }
+
+    /// Creates a signal whose value is serialized from the server to the
+    /// client using [`SerdeJson`].
+    ///
+    /// The value of the signal when the server builds its response is sent to
+    /// the client, and replaces the initial value while hydrating, so that
+    /// state computed on the server is not lost.
+    #[track_caller]
+    pub fn new_serialized(value: T) -> Self
+    where
+        T: SerializableData<SerdeJson> + Send + Sync + 'static,
+        T::SerErr: Debug,
+        T::DeErr: Debug,
+    {
+        Self::new_serialized_with_encoding::<SerdeJson>(value)
+    }
+
+    /// Creates a signal whose value is serialized from the server to the
+    /// client using the given [`Serializer`]. See
+    /// [`new_serialized`](Self::new_serialized).
+    #[track_caller]
+    pub fn new_serialized_with_encoding<Ser>(value: T) -> Self
+    where
+        Ser: Serializer,
+        T: SerializableData<Ser> + Send + Sync + 'static,
+        T::SerErr: Debug,
+        T::DeErr: Debug,
+    {
+        let this = Self::new(value);
+        let signal = this.clone();
+        serialize_state::<T, Ser>(&this.value, move |value| signal.set(value));
+        this
     }
 
     #[inline(always)]
@@ -113,11 +159,17 @@ impl Source for RwLock<SubscriberSet> {
     fn remove_subscriber(&self, subscriber: &AnySubscriber) {
         self.write().unsubscribe(subscriber)
     }
+
+    fn subscribers(&self) -> Vec<AnySubscriber> {
+        (&*self.read()).into_iter().cloned().collect()
+    }
 }
 
 impl<T> ReactiveNode for ArcRwSignal<T> {
     fn mark_dirty(&self) {
-        self.mark_subscribers_check();
+        if !defer_mark_dirty(self) {
+            self.mark_subscribers_check();
+        }
     }
 
     fn mark_check(&self) {}
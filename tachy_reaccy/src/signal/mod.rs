@@ -1,13 +1,16 @@
 mod arc_signal;
 mod read;
+mod stream;
 mod write;
 use crate::{
     arena::{Stored, StoredData},
+    serialization::{SerdeJson, SerializableData, Serializer},
     signal_traits::*,
     unwrap_signal,
 };
 pub use arc_signal::ArcRwSignal;
 pub use read::*;
+pub use stream::SignalStream;
 pub mod trigger;
 use std::{fmt::Debug, sync::Arc};
 pub use write::*;
@@ -37,12 +40,45 @@ impl<T: Send + Sync + 'static> RwSignal<T> {
         feature = "tracing",
         tracing::instrument(level = "debug", skip_all,)
     )]
+    #[track_caller]
     pub fn new(value: T) -> Self {
         Self {
             inner: Stored::new(ArcRwSignal::new(value)),
         }
     }
 
+    /// Creates a signal whose value is serialized from the server to the
+    /// client. See [`ArcRwSignal::new_serialized`].
+    #[track_caller]
+    pub fn new_serialized(value: T) -> Self
+    where
+        T: SerializableData<SerdeJson>,
+        T::SerErr: Debug,
+        T::DeErr: Debug,
+    {
+        Self {
+            inner: Stored::new(ArcRwSignal::new_serialized(value)),
+        }
+    }
+
+    /// Creates a signal whose value is serialized from the server to the
+    /// client using the given [`Serializer`]. See
+    /// [`ArcRwSignal::new_serialized`].
+    #[track_caller]
+    pub fn new_serialized_with_encoding<Ser>(value: T) -> Self
+    where
+        Ser: Serializer,
+        T: SerializableData<Ser>,
+        T::SerErr: Debug,
+        T::DeErr: Debug,
+    {
+        Self {
+            inner: Stored::new(
+                ArcRwSignal::new_serialized_with_encoding::<Ser>(value),
+            ),
+        }
+    }
+
     #[inline(always)]
     pub fn read_only(&self) -> ReadSignal<T> {
         ReadSignal {
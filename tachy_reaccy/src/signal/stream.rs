@@ -0,0 +1,252 @@
+use super::{ArcReadSignal, ArcRwSignal, ReadSignal, RwSignal};
+use crate::{
+    arena::{Owner, Stored, StoredData},
+    notify::{channel, Receiver, Sender},
+    signal_traits::*,
+    source::{
+        AnySource, AnySubscriber, ReactiveNode, Source, Subscriber,
+        ToAnySubscriber,
+    },
+    spawn::{spawn, spawn_local},
+    unwrap_signal,
+};
+use futures::{
+    future::{AbortHandle, Abortable},
+    Future, Stream, StreamExt,
+};
+use parking_lot::RwLock;
+use std::{
+    fmt::Debug,
+    pin::Pin,
+    sync::{Arc, Weak},
+    task::{Context, Poll},
+};
+
+/// A [`Stream`] of the values of a signal, created with
+/// [`ArcReadSignal::to_stream`].
+///
+/// It yields the value of the signal each time subscribers are notified that
+/// it has changed, starting with the first change after the stream was
+/// created. Several changes made before the stream is polled again are only
+/// yielded once, with the latest value.
+///
+/// The stream ends when the [`Owner`] it was created under is cleaned up or
+/// disposed.
+pub struct SignalStream<T> {
+    signal: ArcRwSignal<T>,
+    observer: Arc<StreamObserver>,
+    rx: Receiver,
+}
+
+/// Subscribes to the signal on behalf of the stream.
+struct StreamObserver(RwLock<Sender>);
+
+impl<T> Debug for SignalStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignalStream")
+            .field("signal", &self.signal)
+            .finish()
+    }
+}
+
+impl<T> SignalStream<T> {
+    fn new(signal: ArcRwSignal<T>) -> Self {
+        let (tx, rx) = channel();
+        let observer = Arc::new(StreamObserver(RwLock::new(tx)));
+
+        // stop yielding values when the owner is cleaned up or disposed
+        Owner::on_cleanup({
+            let observer = Arc::downgrade(&observer);
+            move || {
+                if let Some(observer) = observer.upgrade() {
+                    observer.0.write().close();
+                }
+            }
+        });
+
+        signal.add_subscriber(observer.to_any_subscriber());
+        Self {
+            signal,
+            observer,
+            rx,
+        }
+    }
+}
+
+impl<T: Clone> Stream for SignalStream<T> {
+    type Item = T;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        match self.rx.poll_next_unpin(cx) {
+            Poll::Ready(Some(())) => {
+                // signals forget their subscribers each time they notify them,
+                // so subscribe again before reading the value, to hear about
+                // any change that happens after it has been read
+                self.signal
+                    .add_subscriber(self.observer.to_any_subscriber());
+                Poll::Ready(Some(self.signal.get_untracked()))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> Drop for SignalStream<T> {
+    fn drop(&mut self) {
+        self.signal
+            .remove_subscriber(&self.observer.to_any_subscriber());
+    }
+}
+
+impl ToAnySubscriber for Arc<StreamObserver> {
+    fn to_any_subscriber(&self) -> AnySubscriber {
+        AnySubscriber(
+            Arc::as_ptr(self) as usize,
+            Arc::downgrade(self) as Weak<dyn Subscriber + Send + Sync>,
+        )
+    }
+}
+
+impl ReactiveNode for StreamObserver {
+    fn mark_dirty(&self) {
+        self.0.write().notify();
+    }
+
+    fn mark_check(&self) {
+        self.0.write().notify();
+    }
+
+    fn mark_subscribers_check(&self) {}
+
+    fn update_if_necessary(&self) -> bool {
+        false
+    }
+}
+
+impl Subscriber for StreamObserver {
+    fn add_source(&self, _source: AnySource) {}
+
+    fn clear_sources(&self, _subscriber: &AnySubscriber) {}
+}
+
+impl<T> ArcReadSignal<T> {
+    /// Returns a [`Stream`] that yields the new value of the signal each time
+    /// it changes. See [`SignalStream`].
+    pub fn to_stream(&self) -> SignalStream<T> {
+        SignalStream::new(self.0.clone())
+    }
+}
+
+impl<T: Send + Sync + 'static> ReadSignal<T> {
+    /// Returns a [`Stream`] that yields the new value of the signal each time
+    /// it changes. See [`SignalStream`].
+    #[track_caller]
+    pub fn to_stream(&self) -> SignalStream<T> {
+        self.get_value()
+            .map(|inner| inner.to_stream())
+            .unwrap_or_else(unwrap_signal!(self))
+    }
+}
+
+impl<T> ArcRwSignal<T> {
+    /// Returns a [`Stream`] that yields the new value of the signal each time
+    /// it changes. See [`SignalStream`].
+    pub fn to_stream(&self) -> SignalStream<T> {
+        SignalStream::new(self.clone())
+    }
+
+    /// Creates a signal that holds `initial` until `stream` yields its first
+    /// item, and then holds the latest item it has yielded.
+    ///
+    /// The stream is polled in a task spawned on the current
+    /// [`Executor`](crate::spawn::Executor), until it ends or the [`Owner`]
+    /// the signal was created under is cleaned up or disposed.
+    #[track_caller]
+    pub fn from_stream(
+        initial: T,
+        stream: impl Stream<Item = T> + Send + 'static,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        let signal = Self::new(initial);
+        spawn(signal.clone().follow(stream));
+        signal
+    }
+
+    /// Creates a signal that holds the latest item yielded by a `stream` that
+    /// cannot be sent between threads, like a websocket in the browser. See
+    /// [`from_stream`](Self::from_stream).
+    #[track_caller]
+    pub fn from_stream_local(
+        initial: T,
+        stream: impl Stream<Item = T> + 'static,
+    ) -> Self
+    where
+        T: 'static,
+    {
+        let signal = Self::new(initial);
+        spawn_local(signal.clone().follow(stream));
+        signal
+    }
+
+    // sets the signal to each item of the stream, until the current owner is
+    // cleaned up
+    fn follow(self, stream: impl Stream<Item = T>) -> impl Future<Output = ()> {
+        let (abort, registration) = AbortHandle::new_pair();
+        Owner::on_cleanup(move || abort.abort());
+
+        let task = Abortable::new(
+            async move {
+                let mut stream = Box::pin(stream);
+                while let Some(item) = stream.next().await {
+                    self.set(item);
+                }
+            },
+            registration,
+        );
+        async move {
+            _ = task.await;
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> RwSignal<T> {
+    /// Returns a [`Stream`] that yields the new value of the signal each time
+    /// it changes. See [`SignalStream`].
+    #[track_caller]
+    pub fn to_stream(&self) -> SignalStream<T> {
+        self.get_value()
+            .map(|inner| inner.to_stream())
+            .unwrap_or_else(unwrap_signal!(self))
+    }
+
+    /// Creates a signal that holds the latest item yielded by `stream`. See
+    /// [`ArcRwSignal::from_stream`].
+    #[track_caller]
+    pub fn from_stream(
+        initial: T,
+        stream: impl Stream<Item = T> + Send + 'static,
+    ) -> Self {
+        Self {
+            inner: Stored::new(ArcRwSignal::from_stream(initial, stream)),
+        }
+    }
+
+    /// Creates a signal that holds the latest item yielded by a `stream` that
+    /// cannot be sent between threads. See
+    /// [`ArcRwSignal::from_stream_local`].
+    #[track_caller]
+    pub fn from_stream_local(
+        initial: T,
+        stream: impl Stream<Item = T> + 'static,
+    ) -> Self {
+        Self {
+            inner: Stored::new(ArcRwSignal::from_stream_local(initial, stream)),
+        }
+    }
+}
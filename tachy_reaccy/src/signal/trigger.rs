@@ -1,4 +1,6 @@
 use crate::{
+    defer_mark_dirty,
+    graph::{self, NodeKind},
     signal_traits::*,
     source::{
         AnySource, AnySubscriber, ReactiveNode, Source, SubscriberSet,
@@ -52,10 +54,28 @@ impl ArcTrigger {
         feature = "tracing",
         tracing::instrument(level = "trace", skip_all,)
     )]
+    #[track_caller]
     pub fn new() -> Self {
+        let this = Self::new_unregistered(Location::caller());
+        graph::register(
+            NodeKind::Trigger,
+            this.defined_at(),
+            None,
+            Some(this.to_any_source()),
+            None,
+        );
+        this
+    }
+
+    /// Creates a trigger without recording it in the reactive graph, so that
+    /// it can be recorded as part of something else, like a store field.
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    pub(crate) fn new_unregistered(
+        defined_at: &'static Location<'static>,
+    ) -> Self {
         Self {
             #[cfg(debug_assertions)]
-            defined_at: Location::caller(),
+            defined_at,
             inner: Arc::new(RwLock::new(SubscriberSet::new())),
         }
     }
@@ -73,7 +93,9 @@ impl ArcTrigger {
 
 impl ReactiveNode for ArcTrigger {
     fn mark_dirty(&self) {
-        self.mark_subscribers_check();
+        if !defer_mark_dirty(self) {
+            self.mark_subscribers_check();
+        }
     }
 
     fn mark_check(&self) {}
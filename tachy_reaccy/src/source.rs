@@ -15,6 +15,13 @@ pub trait ReactiveNode {
     /// Regenerates the value for this node, if needed, and returns whether
     /// it has actually changed or not.
     fn update_if_necessary(&self) -> bool;
+
+    /// Returns the current state of this node, for debugging.
+    ///
+    /// Nodes that don't cache a value are always clean.
+    fn state(&self) -> ReactiveNodeState {
+        ReactiveNodeState::Clean
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -39,6 +46,11 @@ pub trait Source: ReactiveNode {
 
     /// Remove all subscribers from this source's list of dependencies.
     fn clear_subscribers(&self);
+
+    /// Returns this source's current subscribers, for debugging.
+    fn subscribers(&self) -> Vec<AnySubscriber> {
+        Vec::new()
+    }
 }
 
 pub trait Track {
@@ -102,6 +114,13 @@ impl Source for AnySource {
             inner.clear_subscribers();
         }
     }
+
+    fn subscribers(&self) -> Vec<AnySubscriber> {
+        self.1
+            .upgrade()
+            .map(|inner| inner.subscribers())
+            .unwrap_or_default()
+    }
 }
 
 impl ReactiveNode for AnySource {
@@ -130,6 +149,13 @@ impl ReactiveNode for AnySource {
             inner.mark_check()
         }
     }
+
+    fn state(&self) -> ReactiveNodeState {
+        self.1
+            .upgrade()
+            .map(|inner| inner.state())
+            .unwrap_or(ReactiveNodeState::Clean)
+    }
 }
 
 /// Converts a [`Subscriber`] to a type-erased [`AnySubscriber`].
@@ -145,6 +171,11 @@ pub trait Subscriber: ReactiveNode {
 
     // Clears the set of sources for this subscriber.
     fn clear_sources(&self, subscriber: &AnySubscriber);
+
+    /// Returns the sources this subscriber currently depends on, for debugging.
+    fn sources(&self) -> Vec<AnySource> {
+        Vec::new()
+    }
 }
 
 /// A type-erased subscriber.
@@ -169,6 +200,13 @@ impl Subscriber for AnySubscriber {
             inner.clear_sources(subscriber);
         }
     }
+
+    fn sources(&self) -> Vec<AnySource> {
+        self.1
+            .upgrade()
+            .map(|inner| inner.sources())
+            .unwrap_or_default()
+    }
 }
 
 impl ReactiveNode for AnySubscriber {
@@ -197,6 +235,13 @@ impl ReactiveNode for AnySubscriber {
             inner.mark_check()
         }
     }
+
+    fn state(&self) -> ReactiveNodeState {
+        self.1
+            .upgrade()
+            .map(|inner| inner.state())
+            .unwrap_or(ReactiveNodeState::Clean)
+    }
 }
 
 impl AnySubscriber {
@@ -1,38 +1,254 @@
-use cfg_if::cfg_if;
-use std::future::Future;
+//! Spawning the async tasks that drive effects and async signals.
+//!
+//! By default, tasks are spawned with `wasm-bindgen-futures` in the browser, or
+//! on the `glib` or `tokio` runtime if the corresponding feature is enabled.
+//! Any other runtime can be used by installing an [`Executor`] with
+//! [`set_executor`] before creating any reactive values.
+//!
+//! ```
+//! use futures::{
+//!     executor::{LocalPool, LocalSpawner},
+//!     task::LocalSpawnExt,
+//! };
+//! use std::cell::RefCell;
+//! use tachy_reaccy::spawn::{
+//!     set_executor, Executor, PinnedLocalFuture, PinnedSendFuture,
+//! };
+//!
+//! thread_local! {
+//!     static POOL: RefCell<LocalPool> = RefCell::new(LocalPool::new());
+//!     static SPAWNER: LocalSpawner = POOL.with(|pool| pool.borrow().spawner());
+//! }
+//!
+//! /// Runs tasks whenever the game loop calls `run_until_stalled`.
+//! struct GameLoop;
+//!
+//! impl Executor for GameLoop {
+//!     fn spawn(&self, fut: PinnedSendFuture) {
+//!         self.spawn_local(fut);
+//!     }
+//!
+//!     fn spawn_local(&self, fut: PinnedLocalFuture) {
+//!         SPAWNER
+//!             .with(|spawner| spawner.spawn_local(fut))
+//!             .expect("the game loop has stopped");
+//!     }
+//! }
+//!
+//! set_executor(GameLoop).unwrap();
+//!
+//! // once per frame
+//! POOL.with(|pool| pool.borrow_mut().run_until_stalled());
+//! ```
 
+use std::{fmt, future::Future, pin::Pin, sync::OnceLock, time::Duration};
+
+/// A type-erased future that can be sent between threads.
+pub type PinnedSendFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A type-erased future that must run on the thread that created it.
+pub type PinnedLocalFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// An async runtime that can run the tasks created by the reactive system.
+///
+/// This is the dynamic equivalent of the `Spawner` trait in `tachydom`: both
+/// should poll the future to completion in the background, without blocking
+/// the caller.
+pub trait Executor: Send + Sync {
+    /// Spawns a task that may be moved to another thread.
+    fn spawn(&self, fut: PinnedSendFuture);
+
+    /// Spawns a task on the current thread.
+    fn spawn_local(&self, fut: PinnedLocalFuture);
+
+    /// Returns a future that resolves once `duration` has passed, so that a
+    /// task can wait without blocking the thread it runs on.
+    ///
+    /// By default, the future resolves right away, so executors that have a
+    /// timer should override this.
+    fn sleep(&self, _duration: Duration) -> PinnedSendFuture {
+        Box::pin(async {})
+    }
+}
+
+static EXECUTOR: OnceLock<Box<dyn Executor>> = OnceLock::new();
+
+/// The error returned by [`set_executor`] if an executor has already been set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutorAlreadySet;
+
+impl fmt::Display for ExecutorAlreadySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an executor has already been set")
+    }
+}
+
+impl std::error::Error for ExecutorAlreadySet {}
+
+/// Installs the executor used to spawn all reactive tasks.
+///
+/// This can only be done once, and should be done before any effects or async
+/// signals are created.
+pub fn set_executor(
+    executor: impl Executor + 'static,
+) -> Result<(), ExecutorAlreadySet> {
+    EXECUTOR
+        .set(Box::new(executor))
+        .map_err(|_| ExecutorAlreadySet)
+}
+
+/// Spawns a task on the current thread, using the installed [`Executor`] or
+/// the default for the current platform.
 pub fn spawn_local<F>(fut: F)
 where
     F: Future<Output = ()> + 'static,
 {
-    cfg_if! {
-        if #[cfg(target_arch = "wasm32")] {
-            wasm_bindgen_futures::spawn_local(fut)
-        } else if #[cfg(feature = "glib")] {
-            let main_context = glib::MainContext::default();
-            main_context.spawn_local(fut);
-        } else if #[cfg(any(test, doctest, feature = "tokio"))] {
-            tokio::task::spawn_local(fut);
-        }  else {
-            futures::executor::block_on(fut)
-        }
-    }
+    executor().spawn_local(Box::pin(fut))
 }
 
+/// Spawns a task that may be moved to another thread, using the installed
+/// [`Executor`] or the default for the current platform.
 pub fn spawn<F>(fut: F)
 where
     F: Future<Output = ()> + Send + 'static,
 {
-    cfg_if! {
+    executor().spawn(Box::pin(fut))
+}
+
+/// Returns a future that resolves once `duration` has passed, using the timer
+/// of the installed [`Executor`] or the default for the current platform.
+pub fn sleep(duration: Duration) -> PinnedSendFuture {
+    executor().sleep(duration)
+}
+
+fn executor() -> &'static dyn Executor {
+    EXECUTOR.get_or_init(default_executor).as_ref()
+}
+
+fn default_executor() -> Box<dyn Executor> {
+    cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
-            wasm_bindgen_futures::spawn_local(fut)
+            Box::new(Wasm)
         } else if #[cfg(feature = "glib")] {
-            let main_context = glib::MainContext::default();
-            main_context.spawn(fut);
+            Box::new(Glib)
         } else if #[cfg(any(test, doctest, feature = "tokio"))] {
-            tokio::task::spawn(fut);
-        }  else {
-            futures::executor::block_on(fut)
+            Box::new(Tokio)
+        } else {
+            Box::new(NoExecutor)
         }
     }
 }
+
+/// Used when there is no default executor for the current platform, so that
+/// spawning fails loudly rather than blocking forever.
+#[cfg(not(any(
+    target_arch = "wasm32",
+    feature = "glib",
+    test,
+    doctest,
+    feature = "tokio"
+)))]
+struct NoExecutor;
+
+#[cfg(not(any(
+    target_arch = "wasm32",
+    feature = "glib",
+    test,
+    doctest,
+    feature = "tokio"
+)))]
+impl Executor for NoExecutor {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        self.spawn_local(fut)
+    }
+
+    fn spawn_local(&self, _fut: PinnedLocalFuture) {
+        panic!(
+            "No async executor is available to run reactive tasks. Enable the \
+             `tokio` or `glib` feature, or install one with \
+             `tachy_reaccy::spawn::set_executor`."
+        )
+    }
+}
+
+/// Spawns tasks with `wasm-bindgen-futures`.
+///
+/// Everything runs on the current thread, so [`Executor::spawn`] is the same
+/// as [`Executor::spawn_local`].
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Wasm;
+
+#[cfg(target_arch = "wasm32")]
+impl Executor for Wasm {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        wasm_bindgen_futures::spawn_local(fut)
+    }
+
+    fn spawn_local(&self, fut: PinnedLocalFuture) {
+        wasm_bindgen_futures::spawn_local(fut)
+    }
+
+    fn sleep(&self, duration: Duration) -> PinnedSendFuture {
+        use futures::channel::oneshot;
+        use wasm_bindgen::{closure::Closure, JsCast};
+
+        let (tx, rx) = oneshot::channel();
+        let wake = Closure::once_into_js(move || _ = tx.send(()));
+        let scheduled = web_sys::window().map(|window| {
+            window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                wake.unchecked_ref(),
+                duration.as_millis().try_into().unwrap_or(i32::MAX),
+            )
+        });
+        match scheduled {
+            Some(Ok(_)) => Box::pin(async move {
+                _ = rx.await;
+            }),
+            // without a timer, don't wait forever
+            _ => Box::pin(async {}),
+        }
+    }
+}
+
+/// Spawns tasks on the default `glib` main context.
+#[cfg(feature = "glib")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Glib;
+
+#[cfg(feature = "glib")]
+impl Executor for Glib {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        glib::MainContext::default().spawn(fut);
+    }
+
+    fn spawn_local(&self, fut: PinnedLocalFuture) {
+        glib::MainContext::default().spawn_local(fut);
+    }
+
+    fn sleep(&self, duration: Duration) -> PinnedSendFuture {
+        glib::timeout_future(duration)
+    }
+}
+
+/// Spawns tasks on the current `tokio` runtime.
+///
+/// Local tasks must be spawned inside a [`LocalSet`](tokio::task::LocalSet).
+#[cfg(any(test, doctest, feature = "tokio"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tokio;
+
+#[cfg(any(test, doctest, feature = "tokio"))]
+impl Executor for Tokio {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        tokio::task::spawn(fut);
+    }
+
+    fn spawn_local(&self, fut: PinnedLocalFuture) {
+        tokio::task::spawn_local(fut);
+    }
+
+    fn sleep(&self, duration: Duration) -> PinnedSendFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
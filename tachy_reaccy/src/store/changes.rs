@@ -0,0 +1,535 @@
+use super::{ArcStore, Store, StorePath, StorePathSegment};
+use crate::arena::StoredData;
+use parking_lot::RwLock;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug},
+    hash::{BuildHasher, Hash},
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// A write to a store, as reported to the observers registered with
+/// [`ArcStore::on_change`].
+///
+/// Changes serialize to, and deserialize from, [RFC 6902] JSON Patch
+/// operations. The path of each operation is the [RFC 6901] JSON pointer to
+/// the field that changed within the value of the store, as serialized by
+/// `serde_json` (e.g., `/posts/0/author/name`), so a change can be replayed
+/// with [`ArcStore::apply_patch`] on any store of the same type, or applied
+/// to the JSON value of the store by any other JSON Patch implementation.
+///
+/// [RFC 6902]: https://datatracker.ietf.org/doc/html/rfc6902
+/// [RFC 6901]: https://datatracker.ietf.org/doc/html/rfc6901
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "JsonPatchOp", into = "JsonPatchOp")]
+pub struct StoreChange {
+    /// The reference tokens of the JSON pointer to the field that changed:
+    /// e.g., `["posts", "0", "author", "name"]` for `/posts/0/author/name`.
+    pub path: Vec<String>,
+    /// How it changed.
+    pub op: StoreOp,
+}
+
+/// The operation of a [`StoreChange`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreOp {
+    /// Inserts a value: into a `Vec`, shifting the rows after it, or into an
+    /// `Option` that is `None`. Anywhere else, it replaces the value.
+    Add(Value),
+    /// Removes a value: from a `Vec`, or from an `Option`, which becomes
+    /// `None`.
+    Remove,
+    /// Replaces the value.
+    Replace(Value),
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonPatchOp {
+    op: JsonPatchOpKind,
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum JsonPatchOpKind {
+    Add,
+    Remove,
+    Replace,
+}
+
+impl From<StoreChange> for JsonPatchOp {
+    fn from(change: StoreChange) -> Self {
+        let path = change
+            .path
+            .iter()
+            .map(|token| {
+                format!("/{}", token.replace('~', "~0").replace('/', "~1"))
+            })
+            .collect();
+        let (op, value) = match change.op {
+            StoreOp::Add(value) => (JsonPatchOpKind::Add, Some(value)),
+            StoreOp::Remove => (JsonPatchOpKind::Remove, None),
+            StoreOp::Replace(value) => (JsonPatchOpKind::Replace, Some(value)),
+        };
+        JsonPatchOp { op, path, value }
+    }
+}
+
+impl TryFrom<JsonPatchOp> for StoreChange {
+    type Error = JsonPatchError;
+
+    fn try_from(op: JsonPatchOp) -> Result<Self, Self::Error> {
+        let path = if op.path.is_empty() {
+            Vec::new()
+        } else {
+            op.path
+                .strip_prefix('/')
+                .ok_or_else(|| JsonPatchError::InvalidPointer(op.path.clone()))?
+                .split('/')
+                .map(|token| token.replace("~1", "/").replace("~0", "~"))
+                .collect()
+        };
+        let op = match (op.op, op.value) {
+            (JsonPatchOpKind::Add, Some(value)) => StoreOp::Add(value),
+            (JsonPatchOpKind::Remove, _) => StoreOp::Remove,
+            (JsonPatchOpKind::Replace, Some(value)) => StoreOp::Replace(value),
+            (_, None) => return Err(JsonPatchError::MissingValue),
+        };
+        Ok(StoreChange { path, op })
+    }
+}
+
+/// An error that prevented a [`StoreChange`] from being parsed or applied.
+#[derive(Debug, Error)]
+pub enum JsonPatchError {
+    /// The path is not a JSON pointer.
+    #[error("invalid JSON pointer {0:?}")]
+    InvalidPointer(String),
+    /// An `add` or `replace` operation has no value.
+    #[error("missing value for an add or replace operation")]
+    MissingValue,
+    /// There is no field at the path of the change.
+    #[error("no field at the path of the change")]
+    PathNotFound,
+    /// The field at the path of the change cannot be removed.
+    #[error("the field at the path of the change cannot be removed")]
+    CannotRemove,
+    /// The value of the change does not match the type of the field.
+    #[error("could not deserialize the value of the change: {0}")]
+    Deserialization(#[from] serde_json::Error),
+}
+
+/// A value whose fields can be serialized and replaced one at a time, so that
+/// writes to a store holding it can be described as [`StoreChange`]s.
+///
+/// Each field is found either by the [`StorePath`] that accesses it in the
+/// store, or by the JSON pointer to it in the serialized value.
+///
+/// This is usually derived with `#[derive(JsonPatch)]`, which maps the path of
+/// the accessor derived by `#[derive(Store)]` for each field to the name serde
+/// gives it. Other values are serialized and replaced as a whole.
+pub trait JsonPatch: Serialize + DeserializeOwned {
+    /// Serializes the value at `path` within `self`, pushing the reference
+    /// tokens of the JSON pointer to it onto `pointer`. If `path` leads inside
+    /// a value that cannot be addressed any further, that whole value is
+    /// serialized.
+    fn json_at(
+        &self,
+        path: &[StorePathSegment],
+        pointer: &mut Vec<String>,
+    ) -> Result<Value, serde_json::Error>;
+
+    /// Applies the operation to the value at the JSON `pointer` within
+    /// `self`, pushing each segment of the store path it follows onto
+    /// `found`.
+    fn apply_json(
+        &mut self,
+        pointer: &[String],
+        op: StoreOp,
+        found: &mut StorePath,
+    ) -> Result<(), JsonPatchError>;
+}
+
+/// Applies an operation to a value that cannot be addressed any further.
+#[doc(hidden)]
+pub fn apply_json_whole<T>(
+    value: &mut T,
+    pointer: &[String],
+    op: StoreOp,
+) -> Result<(), JsonPatchError>
+where
+    T: DeserializeOwned,
+{
+    if !pointer.is_empty() {
+        return Err(JsonPatchError::PathNotFound);
+    }
+    match op {
+        StoreOp::Add(new) | StoreOp::Replace(new) => {
+            *value = serde_json::from_value(new)?;
+            Ok(())
+        }
+        StoreOp::Remove => Err(JsonPatchError::CannotRemove),
+    }
+}
+
+/// Strips the reference tokens of a field from the start of a JSON pointer.
+#[doc(hidden)]
+pub fn strip_pointer<'a>(
+    pointer: &'a [String],
+    tokens: &[&str],
+) -> Option<&'a [String]> {
+    if pointer.len() >= tokens.len()
+        && pointer
+            .iter()
+            .zip(tokens)
+            .all(|(token, field)| token == field)
+    {
+        Some(&pointer[tokens.len()..])
+    } else {
+        None
+    }
+}
+
+macro_rules! json_patch_whole {
+    ($($ty:ty),*) => {
+        $(
+            impl JsonPatch for $ty {
+                fn json_at(
+                    &self,
+                    _path: &[StorePathSegment],
+                    _pointer: &mut Vec<String>,
+                ) -> Result<Value, serde_json::Error> {
+                    serde_json::to_value(self)
+                }
+
+                fn apply_json(
+                    &mut self,
+                    pointer: &[String],
+                    op: StoreOp,
+                    _found: &mut StorePath,
+                ) -> Result<(), JsonPatchError> {
+                    apply_json_whole(self, pointer, op)
+                }
+            }
+        )*
+    };
+}
+
+json_patch_whole! {
+    (), bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128,
+    isize, f32, f64, String, Cow<'static, str>
+}
+
+/// The entries of a map are accessed by keys that only the store can
+/// resolve, so maps are serialized and replaced as a whole.
+impl<K, V, S> JsonPatch for HashMap<K, V, S>
+where
+    K: Serialize + DeserializeOwned + Hash + Eq,
+    V: Serialize + DeserializeOwned,
+    S: BuildHasher + Default,
+{
+    fn json_at(
+        &self,
+        _path: &[StorePathSegment],
+        _pointer: &mut Vec<String>,
+    ) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    fn apply_json(
+        &mut self,
+        pointer: &[String],
+        op: StoreOp,
+        _found: &mut StorePath,
+    ) -> Result<(), JsonPatchError> {
+        apply_json_whole(self, pointer, op)
+    }
+}
+
+impl<K, V> JsonPatch for BTreeMap<K, V>
+where
+    K: Serialize + DeserializeOwned + Ord,
+    V: Serialize + DeserializeOwned,
+{
+    fn json_at(
+        &self,
+        _path: &[StorePathSegment],
+        _pointer: &mut Vec<String>,
+    ) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    fn apply_json(
+        &mut self,
+        pointer: &[String],
+        op: StoreOp,
+        _found: &mut StorePath,
+    ) -> Result<(), JsonPatchError> {
+        apply_json_whole(self, pointer, op)
+    }
+}
+
+impl<T> JsonPatch for Option<T>
+where
+    T: JsonPatch,
+{
+    fn json_at(
+        &self,
+        path: &[StorePathSegment],
+        pointer: &mut Vec<String>,
+    ) -> Result<Value, serde_json::Error> {
+        match self {
+            Some(value) => value.json_at(path, pointer),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn apply_json(
+        &mut self,
+        pointer: &[String],
+        op: StoreOp,
+        found: &mut StorePath,
+    ) -> Result<(), JsonPatchError> {
+        match (self, pointer, op) {
+            (this, [], StoreOp::Remove) => {
+                *this = None;
+                Ok(())
+            }
+            (Some(value), pointer, op) => value.apply_json(pointer, op, found),
+            (this, pointer, op) => apply_json_whole(this, pointer, op),
+        }
+    }
+}
+
+impl<T> JsonPatch for Vec<T>
+where
+    T: JsonPatch,
+{
+    fn json_at(
+        &self,
+        path: &[StorePathSegment],
+        pointer: &mut Vec<String>,
+    ) -> Result<Value, serde_json::Error> {
+        match path.split_first() {
+            Some((idx, rest)) if usize::from(*idx) < self.len() => {
+                let idx = usize::from(*idx);
+                pointer.push(idx.to_string());
+                self[idx].json_at(rest, pointer)
+            }
+            _ => serde_json::to_value(self),
+        }
+    }
+
+    /// Rows can be inserted and removed at an index, or appended at `-`. The
+    /// `Vec` itself is then reported as changed, as the rows after that index
+    /// have moved.
+    fn apply_json(
+        &mut self,
+        pointer: &[String],
+        op: StoreOp,
+        found: &mut StorePath,
+    ) -> Result<(), JsonPatchError> {
+        let Some((token, rest)) = pointer.split_first() else {
+            return apply_json_whole(self, pointer, op);
+        };
+        let idx = match token.as_str() {
+            "-" => self.len(),
+            token => token
+                .parse::<usize>()
+                .map_err(|_| JsonPatchError::PathNotFound)?,
+        };
+        match (rest, op) {
+            ([], StoreOp::Add(value)) if idx <= self.len() => {
+                self.insert(idx, serde_json::from_value(value)?);
+                Ok(())
+            }
+            ([], StoreOp::Remove) if idx < self.len() => {
+                self.remove(idx);
+                Ok(())
+            }
+            (rest, op) => {
+                let row =
+                    self.get_mut(idx).ok_or(JsonPatchError::PathNotFound)?;
+                found.push(idx);
+                row.apply_json(rest, op, found)
+            }
+        }
+    }
+}
+
+type Observer = Arc<dyn Fn(&StorePath) + Send + Sync>;
+
+/// The observers of writes to a store.
+#[derive(Clone, Default)]
+pub struct StoreObservers(Arc<RwLock<ObserverList>>);
+
+#[derive(Default)]
+struct ObserverList {
+    next_id: usize,
+    observers: Vec<(usize, Observer)>,
+}
+
+impl Debug for StoreObservers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreObservers")
+            .field("observers", &self.0.read().observers.len())
+            .finish()
+    }
+}
+
+impl StoreObservers {
+    fn add(&self, observer: Observer) -> usize {
+        let mut list = self.0.write();
+        let id = list.next_id;
+        list.next_id += 1;
+        list.observers.push((id, observer));
+        id
+    }
+
+    /// Reports a write to the field at `path` to each observer.
+    pub(crate) fn notify(&self, path: &StorePath) {
+        // observers can add or remove observers, so they are not called while
+        // the list is locked
+        let observers = {
+            let list = self.0.read();
+            if list.observers.is_empty() {
+                return;
+            }
+            list.observers
+                .iter()
+                .map(|(_, observer)| Arc::clone(observer))
+                .collect::<Vec<_>>()
+        };
+        for observer in observers {
+            observer(path);
+        }
+    }
+}
+
+/// An observer registered with [`ArcStore::on_change`].
+#[derive(Debug)]
+#[must_use = "an observer can only be stopped through its handle"]
+pub struct StoreObserver {
+    observers: StoreObservers,
+    id: usize,
+}
+
+impl StoreObserver {
+    /// Stops observing the store.
+    pub fn stop(self) {
+        self.observers
+            .0
+            .write()
+            .observers
+            .retain(|(id, _)| *id != self.id);
+    }
+}
+
+impl<T> ArcStore<T>
+where
+    T: JsonPatch + Send + Sync + 'static,
+{
+    /// Calls `fun` with a [`StoreChange`] after each write to the store, or to
+    /// any of its fields, until [`StoreObserver::stop`] is called.
+    ///
+    /// Each write is reported as replacing the value of the field that was
+    /// written to; a [`patch`](Self::patch) reports each field that changed.
+    /// Changes applied with [`apply_patch`](Self::apply_patch)
+    /// are not reported, so that stores kept in sync by exchanging their
+    /// changes do not echo them back to each other.
+    pub fn on_change(
+        &self,
+        fun: impl Fn(&StoreChange) + Send + Sync + 'static,
+    ) -> StoreObserver {
+        let value = Arc::clone(&self.value);
+        let id = self.observers.add(Arc::new(move |path: &StorePath| {
+            let mut pointer = Vec::new();
+            let json = value.read().json_at(path.segments(), &mut pointer);
+            match json {
+                Ok(json) => fun(&StoreChange {
+                    path: pointer,
+                    op: StoreOp::Replace(json),
+                }),
+                Err(e) => crate::log(&format!(
+                    "couldn't serialize the change to {path}: {e}"
+                )),
+            }
+        }));
+        StoreObserver {
+            observers: self.observers.clone(),
+            id,
+        }
+    }
+
+    /// Applies a sequence of changes to the store, notifying the fields that
+    /// changed, the fields that contain them, and the fields they contain.
+    ///
+    /// The changes are applied in order. If one of them fails, the changes
+    /// before it remain applied.
+    pub fn apply_patch(
+        &self,
+        changes: impl IntoIterator<Item = StoreChange>,
+    ) -> Result<(), JsonPatchError> {
+        let mut changed = Vec::new();
+        let res = changes.into_iter().try_for_each(|change| {
+            let mut found = StorePath::default();
+            self.value.write().apply_json(
+                &change.path,
+                change.op,
+                &mut found,
+            )?;
+            changed.push(found);
+            Ok(())
+        });
+
+        let triggers = {
+            let signals = self.signals.read();
+            signals
+                .0
+                .iter()
+                .filter(|(path, _)| {
+                    changed.iter().any(|changed| {
+                        changed.starts_with(path) || path.starts_with(changed)
+                    })
+                })
+                .map(|(_, trigger)| trigger.clone())
+                .collect::<Vec<_>>()
+        };
+        for trigger in triggers {
+            trigger.notify();
+        }
+        res
+    }
+}
+
+impl<T> Store<T>
+where
+    T: JsonPatch + Send + Sync + 'static,
+{
+    /// Calls `fun` with a [`StoreChange`] after each write to the store. See
+    /// [`ArcStore::on_change`].
+    pub fn on_change(
+        &self,
+        fun: impl Fn(&StoreChange) + Send + Sync + 'static,
+    ) -> Option<StoreObserver> {
+        self.get_value().map(|inner| inner.on_change(fun))
+    }
+
+    /// Applies a sequence of changes to the store. See
+    /// [`ArcStore::apply_patch`].
+    pub fn apply_patch(
+        &self,
+        changes: impl IntoIterator<Item = StoreChange>,
+    ) -> Result<(), JsonPatchError> {
+        match self.get_value() {
+            Some(inner) => inner.apply_patch(changes),
+            None => Ok(()),
+        }
+    }
+}
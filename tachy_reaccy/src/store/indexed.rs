@@ -1,4 +1,4 @@
-use super::{StoreField, StorePath, StorePathSegment};
+use super::{KeyMap, StoreField, StoreObservers, StorePath, StorePathSegment};
 use crate::{
     prelude::{
         DefinedAt, SignalIsDisposed, SignalUpdateUntracked,
@@ -89,6 +89,16 @@ where
         self.inner.get_trigger(path)
     }
 
+    #[inline(always)]
+    fn keys(&self) -> KeyMap {
+        self.inner.keys()
+    }
+
+    #[inline(always)]
+    fn observers(&self) -> StoreObservers {
+        self.inner.observers()
+    }
+
     #[inline(always)]
     fn path(&self) -> impl Iterator<Item = StorePathSegment> {
         self.inner.path().chain(iter::once((&self.idx).into()))
@@ -189,7 +199,7 @@ where
     Idx: Clone + Send + Sync + 'static,
 {
     fn trigger(&self) {
-        self.get_trigger(self.path().collect()).notify();
+        self.trigger_field();
     }
 }
 
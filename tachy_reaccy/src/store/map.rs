@@ -0,0 +1,483 @@
+use super::{StoreField, StoreObservers, StorePath, StorePathSegment};
+use crate::{
+    prelude::{
+        DefinedAt, SignalIsDisposed, SignalUpdateUntracked,
+        SignalWithUntracked, Trigger,
+    },
+    signal::trigger::ArcTrigger,
+    source::Track,
+};
+use parking_lot::{MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock};
+use rustc_hash::FxHashMap;
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug},
+    hash::{BuildHasher, Hash},
+    iter,
+    marker::PhantomData,
+    panic::Location,
+    sync::Arc,
+    vec,
+};
+
+/// The path segments assigned to the keys of each map in a store.
+///
+/// Each key is assigned its own segment the first time it is accessed, and
+/// keeps it for the lifetime of the store, so two keys of the same map never
+/// share a path, no matter how their values compare or hash.
+#[derive(Clone, Default)]
+pub struct KeyMap(
+    Arc<RwLock<FxHashMap<StorePath, Box<dyn Any + Send + Sync>>>>,
+);
+
+impl Debug for KeyMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyMap")
+            .field("maps", &self.0.read().len())
+            .finish()
+    }
+}
+
+struct MapKeys<K> {
+    segments: FxHashMap<K, StorePathSegment>,
+}
+
+impl KeyMap {
+    pub(crate) fn segment<K>(
+        &self,
+        path: StorePath,
+        key: &K,
+    ) -> StorePathSegment
+    where
+        K: Hash + Eq + Clone + Send + Sync + 'static,
+    {
+        let mut maps = self.0.write();
+        let keys = maps
+            .entry(path)
+            .or_insert_with(|| {
+                Box::new(MapKeys::<K> {
+                    segments: FxHashMap::default(),
+                })
+            })
+            .downcast_mut::<MapKeys<K>>()
+            .expect("the keys of a map in a store changed type");
+        let next = keys.segments.len();
+        *keys
+            .segments
+            .entry(key.clone())
+            .or_insert_with(|| next.into())
+    }
+}
+
+/// A map that can be held in a store, and accessed one entry at a time with
+/// [`StoreFieldMap`].
+pub trait StoreMap {
+    type Key;
+    type Value;
+
+    fn get(&self, key: &Self::Key) -> Option<&Self::Value>;
+
+    fn get_mut(&mut self, key: &Self::Key) -> Option<&mut Self::Value>;
+
+    fn insert(
+        &mut self,
+        key: Self::Key,
+        value: Self::Value,
+    ) -> Option<Self::Value>;
+
+    fn remove(&mut self, key: &Self::Key) -> Option<Self::Value>;
+
+    fn keys(&self) -> impl Iterator<Item = &Self::Key>;
+}
+
+impl<K, V, S> StoreMap for HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Key = K;
+    type Value = V;
+
+    fn get(&self, key: &K) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        HashMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &K> {
+        HashMap::keys(self)
+    }
+}
+
+impl<K, V> StoreMap for BTreeMap<K, V>
+where
+    K: Ord,
+{
+    type Key = K;
+    type Value = V;
+
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &K> {
+        BTreeMap::keys(self)
+    }
+}
+
+/// Accesses the entries of a store field that holds a map.
+///
+/// Each entry is tracked separately from the others, and from the set of
+/// keys, which is only notified when an entry is inserted or removed.
+pub trait StoreFieldMap<Prev>: StoreField<Prev> + Sized
+where
+    Prev: StoreMap,
+{
+    /// Accesses the entry for `key`, which only notifies its subscribers when
+    /// that entry changes. It does not need to exist yet; reading it while it
+    /// does not exist returns [`None`] from `try_with`, `try_get` and so on.
+    fn at_map_key(self, key: &Prev::Key) -> AtMapKey<Self, Prev>;
+
+    /// Checks whether the map contains `key`, tracking the set of keys.
+    fn contains_map_key(&self, key: &Prev::Key) -> bool;
+
+    /// Inserts an entry, returning the previous value for `key`, if any.
+    ///
+    /// This notifies the entry, and the set of keys if `key` is new.
+    fn insert_entry(
+        &self,
+        key: Prev::Key,
+        value: Prev::Value,
+    ) -> Option<Prev::Value>;
+
+    /// Removes the entry for `key`, returning its value, if any.
+    ///
+    /// This notifies the entry and the set of keys if it existed.
+    fn remove_entry(&self, key: &Prev::Key) -> Option<Prev::Value>;
+
+    /// Iterates over the entries of the map, tracking the set of keys, but
+    /// not the entries themselves.
+    fn iter_map(self) -> MapStoreFieldIter<Self, Prev>;
+}
+
+impl<Inner, Prev> StoreFieldMap<Prev> for Inner
+where
+    Inner: StoreField<Prev> + Send + Sync + Clone + 'static,
+    Prev: StoreMap + 'static,
+    Prev::Key: Hash + Eq + Clone + Send + Sync + 'static,
+    Prev::Value: 'static,
+{
+    #[track_caller]
+    fn at_map_key(self, key: &Prev::Key) -> AtMapKey<Self, Prev> {
+        AtMapKey {
+            #[cfg(debug_assertions)]
+            defined_at: Location::caller(),
+            inner: self,
+            key: key.clone(),
+            ty: PhantomData,
+        }
+    }
+
+    fn contains_map_key(&self, key: &Prev::Key) -> bool {
+        self.track_field();
+        self.with_field_untracked(|map| map.get(key).is_some())
+    }
+
+    fn insert_entry(
+        &self,
+        key: Prev::Key,
+        value: Prev::Value,
+    ) -> Option<Prev::Value> {
+        let entry = self.clone().at_map_key(&key);
+        let prev = {
+            let data = self.data();
+            let write = self.clone().writer();
+            let mut map = write(&data);
+            map.insert(key, value)
+        };
+        if prev.is_none() {
+            self.get_trigger(self.path().collect()).notify();
+        }
+        entry.trigger();
+        prev
+    }
+
+    fn remove_entry(&self, key: &Prev::Key) -> Option<Prev::Value> {
+        let prev = {
+            let data = self.data();
+            let write = self.clone().writer();
+            let mut map = write(&data);
+            map.remove(key)
+        };
+        if prev.is_some() {
+            self.get_trigger(self.path().collect()).notify();
+            self.clone().at_map_key(key).trigger();
+        }
+        prev
+    }
+
+    fn iter_map(self) -> MapStoreFieldIter<Self, Prev> {
+        // reactively track the set of keys
+        self.track_field();
+
+        let keys = self.with_field_untracked(|map| {
+            map.keys().cloned().collect::<Vec<_>>()
+        });
+        MapStoreFieldIter {
+            field: self,
+            keys: keys.into_iter(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AtMapKey<Inner, Prev>
+where
+    Prev: StoreMap,
+{
+    #[cfg(debug_assertions)]
+    defined_at: &'static Location<'static>,
+    inner: Inner,
+    key: Prev::Key,
+    ty: PhantomData<Prev>,
+}
+
+impl<Inner, Prev> AtMapKey<Inner, Prev>
+where
+    Prev: StoreMap,
+{
+    /// The key of the entry.
+    pub fn map_key(&self) -> &Prev::Key {
+        &self.key
+    }
+}
+
+impl<Inner, Prev> Clone for AtMapKey<Inner, Prev>
+where
+    Inner: Clone,
+    Prev: StoreMap,
+    Prev::Key: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            defined_at: self.defined_at,
+            inner: self.inner.clone(),
+            key: self.key.clone(),
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<Inner, Prev> Copy for AtMapKey<Inner, Prev>
+where
+    Inner: Copy,
+    Prev: StoreMap,
+    Prev::Key: Copy,
+{
+}
+
+impl<Inner, Prev> StoreField<Prev::Value> for AtMapKey<Inner, Prev>
+where
+    Inner: StoreField<Prev> + Send + Sync + Clone + 'static,
+    Prev: StoreMap + 'static,
+    Prev::Key: Hash + Eq + Clone + Send + Sync + 'static,
+    Prev::Value: 'static,
+{
+    type Orig = Inner::Orig;
+
+    #[inline(always)]
+    fn data(&self) -> Arc<RwLock<Self::Orig>> {
+        self.inner.data()
+    }
+
+    #[inline(always)]
+    fn get_trigger(&self, path: StorePath) -> ArcTrigger {
+        self.inner.get_trigger(path)
+    }
+
+    #[inline(always)]
+    fn keys(&self) -> KeyMap {
+        self.inner.keys()
+    }
+
+    #[inline(always)]
+    fn observers(&self) -> StoreObservers {
+        self.inner.observers()
+    }
+
+    fn path(&self) -> impl Iterator<Item = StorePathSegment> {
+        let segment = self
+            .inner
+            .keys()
+            .segment(self.inner.path().collect(), &self.key);
+        self.inner.path().chain(iter::once(segment))
+    }
+
+    fn reader(
+        &self,
+    ) -> impl for<'a> Fn(
+        &'a RwLock<Self::Orig>,
+    ) -> MappedRwLockReadGuard<'a, Prev::Value>
+           + Send
+           + Sync
+           + 'static {
+        let inner = self.inner.clone();
+        let key = self.key.clone();
+        move |lock| {
+            let inner = inner.reader();
+            let lock = inner(lock);
+            MappedRwLockReadGuard::map(lock, |prev| {
+                prev.get(&key).expect("no entry for this key in the map")
+            })
+        }
+    }
+
+    fn writer(
+        self,
+    ) -> impl for<'a> Fn(
+        &'a RwLock<Self::Orig>,
+    ) -> MappedRwLockWriteGuard<'a, Prev::Value>
+           + Send
+           + Sync
+           + 'static {
+        move |lock| {
+            let inner = self.inner.clone().writer();
+            let lock = inner(lock);
+            MappedRwLockWriteGuard::map(lock, |prev| {
+                prev.get_mut(&self.key)
+                    .expect("no entry for this key in the map")
+            })
+        }
+    }
+}
+
+impl<Inner, Prev> DefinedAt for AtMapKey<Inner, Prev>
+where
+    Prev: StoreMap,
+{
+    fn defined_at(&self) -> Option<&'static Location<'static>> {
+        #[cfg(debug_assertions)]
+        {
+            Some(self.defined_at)
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            None
+        }
+    }
+}
+
+impl<Inner, Prev> Track for AtMapKey<Inner, Prev>
+where
+    Inner: StoreField<Prev> + Send + Sync + Clone + 'static,
+    Prev: StoreMap + 'static,
+    Prev::Key: Hash + Eq + Clone + Send + Sync + 'static,
+    Prev::Value: 'static,
+{
+    fn track(&self) {
+        let trigger = self.get_trigger(self.path().collect());
+        trigger.track();
+    }
+}
+
+impl<Inner, Prev> SignalWithUntracked for AtMapKey<Inner, Prev>
+where
+    Inner: StoreField<Prev> + SignalWithUntracked<Value = Prev>,
+    Prev: StoreMap + 'static,
+{
+    type Value = Prev::Value;
+
+    fn try_with_untracked<U>(
+        &self,
+        fun: impl FnOnce(&Self::Value) -> U,
+    ) -> Option<U> {
+        self.inner
+            .try_with_untracked(|prev| prev.get(&self.key).map(fun))
+            .flatten()
+    }
+}
+
+impl<Inner, Prev> SignalIsDisposed for AtMapKey<Inner, Prev>
+where
+    Prev: StoreMap,
+{
+    fn is_disposed(&self) -> bool {
+        false
+    }
+}
+
+impl<Inner, Prev> Trigger for AtMapKey<Inner, Prev>
+where
+    Inner: StoreField<Prev> + Send + Sync + Clone + 'static,
+    Prev: StoreMap + 'static,
+    Prev::Key: Hash + Eq + Clone + Send + Sync + 'static,
+    Prev::Value: 'static,
+{
+    fn trigger(&self) {
+        self.trigger_field();
+    }
+}
+
+impl<Inner, Prev> SignalUpdateUntracked for AtMapKey<Inner, Prev>
+where
+    Inner: StoreField<Prev> + SignalUpdateUntracked<Value = Prev>,
+    Prev: StoreMap + 'static,
+{
+    type Value = Prev::Value;
+
+    fn try_update_untracked<U>(
+        &self,
+        fun: impl FnOnce(&mut Self::Value) -> U,
+    ) -> Option<U> {
+        self.inner
+            .try_update_untracked(|prev| prev.get_mut(&self.key).map(fun))
+            .flatten()
+    }
+}
+
+pub struct MapStoreFieldIter<Inner, Prev>
+where
+    Prev: StoreMap,
+{
+    field: Inner,
+    keys: vec::IntoIter<Prev::Key>,
+}
+
+impl<Inner, Prev> Iterator for MapStoreFieldIter<Inner, Prev>
+where
+    Inner: StoreField<Prev> + Send + Sync + Clone + 'static,
+    Prev: StoreMap + 'static,
+    Prev::Key: Hash + Eq + Clone + Send + Sync + 'static,
+    Prev::Value: 'static,
+{
+    type Item = AtMapKey<Inner, Prev>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next()?;
+        Some(self.field.clone().at_map_key(&key))
+    }
+}
@@ -1,9 +1,12 @@
 use crate::{
     arena::{Stored, StoredData},
-    prelude::{SignalUpdateUntracked, SignalWithUntracked, Trigger},
+    graph::{self, NodeKind},
+    prelude::{SignalSet, SignalUpdateUntracked, SignalWithUntracked, Trigger},
+    serialization::{SerdeJson, SerializableData, Serializer},
+    shared_context::serialize_state,
     signal::trigger::ArcTrigger,
     signal_traits::{DefinedAt, SignalIsDisposed},
-    source::Track,
+    source::{ToAnySource, Track},
 };
 use parking_lot::{MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock};
 use rustc_hash::FxHashMap;
@@ -12,16 +15,25 @@ mod indexed;
 pub use indexed::*;
 mod keyed;
 pub use keyed::*;
+mod changes;
+pub use changes::*;
+mod map;
+pub use map::*;
+mod patch;
+pub use patch::*;
 mod path;
 pub use path::*;
 mod stored;
 pub use stored::*;
+mod variant;
+pub use variant::*;
 
 pub struct Store<T: Send + Sync + 'static> {
     inner: Stored<ArcStore<T>>,
 }
 
 impl<T: Send + Sync + 'static> Store<T> {
+    #[track_caller]
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "debug", skip_all,)
@@ -31,6 +43,38 @@ impl<T: Send + Sync + 'static> Store<T> {
             inner: Stored::new(ArcStore::new(value)),
         }
     }
+
+    /// Creates a store whose value is serialized from the server to the
+    /// client. See [`ArcStore::new_serialized`].
+    #[track_caller]
+    pub fn new_serialized(value: T) -> Self
+    where
+        T: SerializableData<SerdeJson>,
+        T::SerErr: Debug,
+        T::DeErr: Debug,
+    {
+        Self {
+            inner: Stored::new(ArcStore::new_serialized(value)),
+        }
+    }
+
+    /// Creates a store whose value is serialized from the server to the
+    /// client using the given [`Serializer`]. See
+    /// [`ArcStore::new_serialized`].
+    #[track_caller]
+    pub fn new_serialized_with_encoding<Ser>(value: T) -> Self
+    where
+        Ser: Serializer,
+        T: SerializableData<Ser>,
+        T::SerErr: Debug,
+        T::DeErr: Debug,
+    {
+        Self {
+            inner: Stored::new(ArcStore::new_serialized_with_encoding::<Ser>(
+                value,
+            )),
+        }
+    }
 }
 
 impl<T: Send + Sync + 'static> Copy for Store<T> {}
@@ -73,28 +117,46 @@ pub struct ArcStore<T> {
     defined_at: &'static Location<'static>,
     pub(crate) value: Arc<RwLock<T>>,
     signals: Arc<RwLock<TriggerMap>>,
+    keys: KeyMap,
+    observers: StoreObservers,
 }
 
 #[derive(Debug, Default)]
 struct TriggerMap(FxHashMap<StorePath, ArcTrigger>);
 
 impl TriggerMap {
-    fn get_or_insert(&mut self, key: StorePath) -> ArcTrigger {
+    fn get_or_insert(
+        &mut self,
+        key: StorePath,
+        defined_at: &'static Location<'static>,
+    ) -> ArcTrigger {
         if let Some(trigger) = self.0.get(&key) {
             trigger.clone()
         } else {
-            let new = ArcTrigger::new();
+            let new = ArcTrigger::new_unregistered(defined_at);
+            graph::register(
+                NodeKind::StoreField,
+                new.defined_at(),
+                cfg!(debug_assertions).then(|| key.to_string()),
+                Some(new.to_any_source()),
+                None,
+            );
             self.0.insert(key, new.clone());
             new
         }
     }
 
+    fn get(&self, key: &StorePath) -> Option<ArcTrigger> {
+        self.0.get(key).cloned()
+    }
+
     fn remove(&mut self, key: &StorePath) -> Option<ArcTrigger> {
         self.0.remove(key)
     }
 }
 
 impl<T> ArcStore<T> {
+    #[track_caller]
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(level = "trace", skip_all,)
@@ -105,9 +167,44 @@ impl<T> ArcStore<T> {
             defined_at: Location::caller(),
             value: Arc::new(RwLock::new(value)),
             signals: Default::default(),
+            keys: Default::default(),
+            observers: Default::default(),
             /* inner: Arc::new(RwLock::new(SubscriberSet::new())), */
         }
     }
+
+    /// Creates a store whose value is serialized from the server to the
+    /// client using [`SerdeJson`].
+    ///
+    /// The value of the store when the server builds its response is sent to
+    /// the client, and replaces the initial value while hydrating, so that
+    /// state computed on the server is not lost.
+    #[track_caller]
+    pub fn new_serialized(value: T) -> Self
+    where
+        T: SerializableData<SerdeJson> + Send + Sync + 'static,
+        T::SerErr: Debug,
+        T::DeErr: Debug,
+    {
+        Self::new_serialized_with_encoding::<SerdeJson>(value)
+    }
+
+    /// Creates a store whose value is serialized from the server to the
+    /// client using the given [`Serializer`]. See
+    /// [`new_serialized`](Self::new_serialized).
+    #[track_caller]
+    pub fn new_serialized_with_encoding<Ser>(value: T) -> Self
+    where
+        Ser: Serializer,
+        T: SerializableData<Ser> + Send + Sync + 'static,
+        T::SerErr: Debug,
+        T::DeErr: Debug,
+    {
+        let this = Self::new(value);
+        let store = this.clone();
+        serialize_state::<T, Ser>(&this.value, move |value| store.set(value));
+        this
+    }
 }
 
 impl<T: Debug> Debug for ArcStore<T> {
@@ -117,6 +214,8 @@ impl<T: Debug> Debug for ArcStore<T> {
         let f = f.field("defined_at", &self.defined_at);
         f.field("value", &self.value)
             .field("signals", &self.signals)
+            .field("keys", &self.keys)
+            .field("observers", &self.observers)
             .finish()
     }
 }
@@ -128,6 +227,8 @@ impl<T> Clone for ArcStore<T> {
             defined_at: self.defined_at,
             value: Arc::clone(&self.value),
             signals: Arc::clone(&self.signals),
+            keys: self.keys.clone(),
+            observers: self.observers.clone(),
         }
     }
 }
@@ -145,7 +246,7 @@ impl<T> SignalWithUntracked for ArcStore<T> {
 
 impl<T> Trigger for ArcStore<T> {
     fn trigger(&self) {
-        self.get_trigger(self.path().collect()).notify();
+        self.trigger_field();
     }
 }
 
@@ -174,6 +275,8 @@ where
     defined_at: &'static std::panic::Location<'static>,
     data: Arc<RwLock<Orig>>,
     trigger: ArcTrigger,
+    path: StorePath,
+    observers: StoreObservers,
     read: Arc<
         dyn for<'a> Fn(&'a RwLock<Orig>) -> MappedRwLockReadGuard<'a, T>
             + Send
@@ -208,6 +311,8 @@ where
             defined_at: Location::caller(),
             data: Arc::clone(&self.data),
             trigger: self.trigger.clone(),
+            path: self.path.clone(),
+            observers: self.observers.clone(),
             write: Arc::clone(&self.write),
         }
     }
@@ -231,12 +336,19 @@ where
                 trigger,
                 read,
             } = read;
-            let ArcWriteStoreField { write, .. } = write;
+            let ArcWriteStoreField {
+                path,
+                observers,
+                write,
+                ..
+            } = write;
             Some(Self {
                 #[cfg(debug_assertions)]
                 defined_at,
                 data,
                 trigger,
+                path,
+                observers,
                 read,
                 write,
             })
@@ -253,6 +365,8 @@ impl<Orig, T> Clone for ArcRwStoreField<Orig, T> {
             defined_at: self.defined_at,
             data: Arc::clone(&self.data),
             trigger: self.trigger.clone(),
+            path: self.path.clone(),
+            observers: self.observers.clone(),
             read: Arc::clone(&self.read),
             write: Arc::clone(&self.write),
         }
@@ -292,6 +406,7 @@ impl<Orig, T> SignalWithUntracked for ArcRwStoreField<Orig, T> {
 impl<Orig, T> Trigger for ArcRwStoreField<Orig, T> {
     fn trigger(&self) {
         self.trigger.notify();
+        self.observers.notify(&self.path);
     }
 }
 
@@ -377,6 +492,8 @@ where
     defined_at: &'static std::panic::Location<'static>,
     data: Arc<RwLock<Orig>>,
     trigger: ArcTrigger,
+    path: StorePath,
+    observers: StoreObservers,
     write: Arc<
         dyn for<'a> Fn(&'a RwLock<Orig>) -> MappedRwLockWriteGuard<'a, T>
             + Send
@@ -391,6 +508,8 @@ impl<Orig, T> Clone for ArcWriteStoreField<Orig, T> {
             defined_at: self.defined_at,
             data: Arc::clone(&self.data),
             trigger: self.trigger.clone(),
+            path: self.path.clone(),
+            observers: self.observers.clone(),
             write: Arc::clone(&self.write),
         }
     }
@@ -412,6 +531,7 @@ impl<Orig, T> DefinedAt for ArcWriteStoreField<Orig, T> {
 impl<Orig, T> Trigger for ArcWriteStoreField<Orig, T> {
     fn trigger(&self) {
         self.trigger.notify();
+        self.observers.notify(&self.path);
     }
 }
 
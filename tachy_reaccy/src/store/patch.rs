@@ -0,0 +1,242 @@
+use super::{ArcStore, KeyMap, Store, StoreMap, StorePath};
+use crate::arena::StoredData;
+use rustc_hash::FxHashSet;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash},
+};
+
+/// A value that can be updated in place by diffing it against a new value,
+/// reporting the [`StorePath`] of each part that changed.
+///
+/// This is usually derived with `#[derive(Patch)]`, which patches each field
+/// at the same path as the accessor derived by `#[derive(Store)]`. Values that
+/// cannot be broken down any further are compared with [`PartialEq`].
+pub trait PatchField {
+    /// Replaces `self` with `new`, calling `notify` with the path of each
+    /// part of `self` that changed. `path` is the path of `self`, and `keys`
+    /// holds the path segments of the entries of any maps in the store.
+    fn patch_field(
+        &mut self,
+        new: Self,
+        path: &StorePath,
+        keys: &KeyMap,
+        notify: &mut dyn FnMut(&StorePath),
+    );
+}
+
+macro_rules! patch_primitives {
+    ($($ty:ty),*) => {
+        $(
+            impl PatchField for $ty {
+                fn patch_field(
+                    &mut self,
+                    new: Self,
+                    path: &StorePath,
+                    _keys: &KeyMap,
+                    notify: &mut dyn FnMut(&StorePath),
+                ) {
+                    if *self != new {
+                        *self = new;
+                        notify(path);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+patch_primitives! {
+    (), bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128,
+    isize, f32, f64, String, &'static str, Cow<'static, str>
+}
+
+impl<T> PatchField for Option<T>
+where
+    T: PatchField,
+{
+    fn patch_field(
+        &mut self,
+        new: Self,
+        path: &StorePath,
+        keys: &KeyMap,
+        notify: &mut dyn FnMut(&StorePath),
+    ) {
+        match (self, new) {
+            (None, None) => {}
+            (Some(old), Some(new)) => old.patch_field(new, path, keys, notify),
+            (this, new) => {
+                *this = new;
+                notify(path);
+            }
+        }
+    }
+}
+
+/// Rows are patched by index, which is also how rows accessed by key are
+/// tracked. If the length changes, the `Vec` itself is reported as changed.
+impl<T> PatchField for Vec<T>
+where
+    T: PatchField,
+{
+    fn patch_field(
+        &mut self,
+        new: Self,
+        path: &StorePath,
+        keys: &KeyMap,
+        notify: &mut dyn FnMut(&StorePath),
+    ) {
+        let (old_len, new_len) = (self.len(), new.len());
+        let mut new = new.into_iter();
+        for (idx, (row, new)) in self.iter_mut().zip(new.by_ref()).enumerate() {
+            let mut row_path = path.clone();
+            row_path.push(idx);
+            row.patch_field(new, &row_path, keys, notify);
+        }
+        self.truncate(new_len);
+        self.extend(new);
+        if old_len != new_len {
+            notify(path);
+        }
+    }
+}
+
+/// Entries are patched by key, at the same paths as the entries accessed with
+/// [`at_map_key`](super::StoreFieldMap::at_map_key). Entries that are inserted
+/// or removed are reported as changed.
+fn patch_map<M>(
+    this: &mut M,
+    new: M,
+    path: &StorePath,
+    keys: &KeyMap,
+    notify: &mut dyn FnMut(&StorePath),
+) where
+    M: StoreMap + IntoIterator<Item = (M::Key, M::Value)>,
+    M::Key: Hash + Eq + Clone + Send + Sync + 'static,
+    M::Value: PatchField,
+{
+    let entry_path = |key: &M::Key| {
+        let mut entry_path = path.clone();
+        entry_path.push(keys.segment(path.clone(), key));
+        entry_path
+    };
+
+    let removed = this
+        .keys()
+        .filter(|key| new.get(key).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    for key in removed {
+        this.remove(&key);
+        notify(&entry_path(&key));
+    }
+
+    for (key, value) in new {
+        let entry_path = entry_path(&key);
+        match this.get_mut(&key) {
+            Some(old) => old.patch_field(value, &entry_path, keys, notify),
+            None => {
+                this.insert(key, value);
+                notify(&entry_path);
+            }
+        }
+    }
+}
+
+impl<K, V, S> PatchField for HashMap<K, V, S>
+where
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    V: PatchField,
+    S: BuildHasher,
+{
+    fn patch_field(
+        &mut self,
+        new: Self,
+        path: &StorePath,
+        keys: &KeyMap,
+        notify: &mut dyn FnMut(&StorePath),
+    ) {
+        patch_map(self, new, path, keys, notify)
+    }
+}
+
+impl<K, V> PatchField for BTreeMap<K, V>
+where
+    K: Ord + Hash + Clone + Send + Sync + 'static,
+    V: PatchField,
+{
+    fn patch_field(
+        &mut self,
+        new: Self,
+        path: &StorePath,
+        keys: &KeyMap,
+        notify: &mut dyn FnMut(&StorePath),
+    ) {
+        patch_map(self, new, path, keys, notify)
+    }
+}
+
+impl<T> ArcStore<T>
+where
+    T: PatchField,
+{
+    /// Replaces the value of the store with a new one, only notifying the
+    /// fields whose values actually changed, along with the fields that
+    /// contain them.
+    ///
+    /// By contrast, replacing the value with [`update`] only notifies the
+    /// root of the store.
+    ///
+    /// Each field that changed is also reported to the observers of the
+    /// store.
+    ///
+    /// [`update`]: crate::signal_traits::SignalUpdate::update
+    pub fn patch(&self, new: T) {
+        let mut changed = FxHashSet::default();
+        let mut patched = Vec::new();
+        self.value.write().patch_field(
+            new,
+            &StorePath::default(),
+            &self.keys,
+            &mut |path| {
+                patched.push(path.clone());
+                // the value at each path that contains this one changed, too
+                let mut path = path.clone();
+                while changed.insert(path.clone()) {
+                    if path.pop().is_none() {
+                        break;
+                    }
+                }
+            },
+        );
+
+        // only fields that are being tracked have triggers
+        let triggers = {
+            let signals = self.signals.read();
+            changed
+                .iter()
+                .filter_map(|path| signals.get(path))
+                .collect::<Vec<_>>()
+        };
+        for trigger in triggers {
+            trigger.notify();
+        }
+        for path in &patched {
+            self.observers.notify(path);
+        }
+    }
+}
+
+impl<T> Store<T>
+where
+    T: PatchField + Send + Sync + 'static,
+{
+    /// Replaces the value of the store with a new one, only notifying the
+    /// fields whose values actually changed. See [`ArcStore::patch`].
+    pub fn patch(&self, new: T) {
+        if let Some(inner) = self.get_value() {
+            inner.patch(new);
+        }
+    }
+}
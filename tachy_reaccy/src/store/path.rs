@@ -1,6 +1,6 @@
 use super::{
-    ArcReadStoreField, ArcRwStoreField, ArcStore, ArcWriteStoreField,
-    ReadStoreField, RwStoreField, Store, WriteStoreField,
+    ArcReadStoreField, ArcRwStoreField, ArcStore, ArcWriteStoreField, KeyMap,
+    ReadStoreField, RwStoreField, Store, StoreObservers, WriteStoreField,
 };
 use crate::{
     arena::Stored,
@@ -17,13 +17,14 @@ use parking_lot::{
     RwLockWriteGuard,
 };
 use std::{
+    fmt::{self, Display},
     iter::{self},
     marker::PhantomData,
     panic::Location,
     sync::Arc,
 };
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct StorePath(Vec<StorePathSegment>);
 
 impl From<Vec<StorePathSegment>> for StorePath {
@@ -32,6 +33,18 @@ impl From<Vec<StorePathSegment>> for StorePath {
     }
 }
 
+impl Display for StorePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, segment) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", segment.0)?;
+        }
+        Ok(())
+    }
+}
+
 impl StorePath {
     pub fn push(&mut self, segment: impl Into<StorePathSegment>) {
         self.0.push(segment.into());
@@ -40,6 +53,15 @@ impl StorePath {
     pub fn pop(&mut self) -> Option<StorePathSegment> {
         self.0.pop()
     }
+
+    pub fn segments(&self) -> &[StorePathSegment] {
+        &self.0
+    }
+
+    /// Whether this path is the same as `other`, or leads into it.
+    pub fn starts_with(&self, other: &StorePath) -> bool {
+        self.0.starts_with(&other.0)
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -57,6 +79,12 @@ impl From<&usize> for StorePathSegment {
     }
 }
 
+impl From<StorePathSegment> for usize {
+    fn from(value: StorePathSegment) -> Self {
+        value.0
+    }
+}
+
 impl FromIterator<StorePathSegment> for StorePath {
     fn from_iter<T: IntoIterator<Item = StorePathSegment>>(iter: T) -> Self {
         Self(Vec::from_iter(iter))
@@ -70,6 +98,12 @@ pub trait StoreField<T>: Sized {
 
     fn get_trigger(&self, path: StorePath) -> ArcTrigger;
 
+    /// The path segments assigned to the keys of the maps in the store.
+    fn keys(&self) -> KeyMap;
+
+    /// The observers of writes to the store.
+    fn observers(&self) -> StoreObservers;
+
     fn path(&self) -> impl Iterator<Item = StorePathSegment>;
 
     fn reader(
@@ -86,6 +120,29 @@ pub trait StoreField<T>: Sized {
            + Sync
            + 'static;
 
+    /// Tracks the field as a whole, so that the current observer runs again
+    /// when it is replaced, e.g. when an enum changes to another variant.
+    fn track_field(&self) {
+        self.get_trigger(self.path().collect()).track();
+    }
+
+    /// Notifies the subscribers of the field that it has changed, and reports
+    /// the write to the observers of the store.
+    fn trigger_field(&self) {
+        let path = self.path().collect::<StorePath>();
+        self.get_trigger(path.clone()).notify();
+        self.observers().notify(&path);
+    }
+
+    /// Applies the function to the current value of the field, without
+    /// tracking it.
+    fn with_field_untracked<U>(&self, fun: impl FnOnce(&T) -> U) -> U {
+        let data = self.data();
+        let read = self.reader();
+        let value = read(&data);
+        fun(&value)
+    }
+
     #[track_caller]
     fn rw(self) -> RwStoreField<Self::Orig, T>
     where
@@ -138,11 +195,14 @@ pub trait StoreField<T>: Sized {
 
     #[track_caller]
     fn arc_write(self) -> ArcWriteStoreField<Self::Orig, T> {
+        let path = self.path().collect::<StorePath>();
         ArcWriteStoreField {
             #[cfg(debug_assertions)]
             defined_at: std::panic::Location::caller(),
             data: self.data(),
-            trigger: self.get_trigger(self.path().collect()),
+            trigger: self.get_trigger(path.clone()),
+            path,
+            observers: self.observers(),
             write: Arc::new({
                 let write = self.writer();
                 move |orig| write(orig)
@@ -155,11 +215,14 @@ pub trait StoreField<T>: Sized {
     where
         Self: Clone,
     {
+        let path = self.path().collect::<StorePath>();
         ArcRwStoreField {
             #[cfg(debug_assertions)]
             defined_at: std::panic::Location::caller(),
             data: self.data(),
-            trigger: self.get_trigger(self.path().collect()),
+            trigger: self.get_trigger(path.clone()),
+            path,
+            observers: self.observers(),
             read: Arc::new({
                 let read = self.clone().reader();
                 move |orig| read(orig)
@@ -181,10 +244,22 @@ impl<T> StoreField<T> for ArcStore<T> {
 
     fn get_trigger(&self, path: StorePath) -> ArcTrigger {
         let triggers = &self.signals;
-        let trigger = triggers.write().get_or_insert(path);
+        #[cfg(debug_assertions)]
+        let defined_at = self.defined_at;
+        #[cfg(not(debug_assertions))]
+        let defined_at = Location::caller();
+        let trigger = triggers.write().get_or_insert(path, defined_at);
         trigger
     }
 
+    fn keys(&self) -> KeyMap {
+        self.keys.clone()
+    }
+
+    fn observers(&self) -> StoreObservers {
+        self.observers.clone()
+    }
+
     fn path(&self) -> impl Iterator<Item = StorePathSegment> {
         iter::empty()
     }
@@ -244,6 +319,20 @@ impl<T: Send + Sync + 'static> StoreField<T> for Store<T> {
             .unwrap_or_else(unwrap_signal!(self))
     }
 
+    fn keys(&self) -> KeyMap {
+        self.inner
+            .get()
+            .map(|inner| inner.keys())
+            .unwrap_or_else(unwrap_signal!(self))
+    }
+
+    fn observers(&self) -> StoreObservers {
+        self.inner
+            .get()
+            .map(|inner| inner.observers())
+            .unwrap_or_else(unwrap_signal!(self))
+    }
+
     fn path(&self) -> impl Iterator<Item = StorePathSegment> {
         iter::empty()
     }
@@ -352,6 +441,14 @@ where
         self.inner.get_trigger(path)
     }
 
+    fn keys(&self) -> KeyMap {
+        self.inner.keys()
+    }
+
+    fn observers(&self) -> StoreObservers {
+        self.inner.observers()
+    }
+
     fn reader(
         &self,
     ) -> impl for<'a> Fn(&'a RwLock<Self::Orig>) -> MappedRwLockReadGuard<'a, T>
@@ -439,7 +536,7 @@ where
     T: 'static,
 {
     fn trigger(&self) {
-        self.get_trigger(self.path().collect()).notify();
+        self.trigger_field();
     }
 }
 
@@ -0,0 +1,222 @@
+use super::{KeyMap, StoreField, StoreObservers, StorePath, StorePathSegment};
+use crate::{
+    prelude::{
+        DefinedAt, SignalIsDisposed, SignalUpdateUntracked,
+        SignalWithUntracked, Trigger,
+    },
+    signal::trigger::ArcTrigger,
+    source::Track,
+};
+use parking_lot::{MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock};
+use std::{iter, marker::PhantomData, panic::Location, sync::Arc};
+
+/// A field of one variant of an enum in a store.
+///
+/// The enum can change to another variant while the field is held, so reads
+/// and updates through it return [`None`] once that has happened. Tracking
+/// the field also tracks the enum, so observers run again when it changes
+/// variant.
+#[derive(Debug)]
+pub struct VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev>,
+{
+    #[cfg(debug_assertions)]
+    defined_at: &'static Location<'static>,
+    path_segment: StorePathSegment,
+    inner: Inner,
+    read: fn(&Prev) -> Option<&T>,
+    write: fn(&mut Prev) -> Option<&mut T>,
+    ty: PhantomData<T>,
+}
+
+impl<Inner, Prev, T> Clone for VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev> + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            defined_at: self.defined_at,
+            path_segment: self.path_segment,
+            inner: self.inner.clone(),
+            read: self.read,
+            write: self.write,
+            ty: self.ty,
+        }
+    }
+}
+
+impl<Inner, Prev, T> Copy for VariantField<Inner, Prev, T> where
+    Inner: StoreField<Prev> + Copy
+{
+}
+
+impl<Inner, Prev, T> VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev>,
+{
+    #[track_caller]
+    pub fn new(
+        inner: Inner,
+        path_segment: StorePathSegment,
+        read: fn(&Prev) -> Option<&T>,
+        write: fn(&mut Prev) -> Option<&mut T>,
+    ) -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            defined_at: Location::caller(),
+            inner,
+            path_segment,
+            read,
+            write,
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<Inner, Prev, T> StoreField<T> for VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev> + Send + Sync + Clone + 'static,
+    Prev: 'static,
+    T: 'static,
+{
+    type Orig = Inner::Orig;
+
+    fn path(&self) -> impl Iterator<Item = StorePathSegment> {
+        self.inner.path().chain(iter::once(self.path_segment))
+    }
+
+    fn data(&self) -> Arc<RwLock<Self::Orig>> {
+        self.inner.data()
+    }
+
+    fn get_trigger(&self, path: StorePath) -> ArcTrigger {
+        self.inner.get_trigger(path)
+    }
+
+    fn keys(&self) -> KeyMap {
+        self.inner.keys()
+    }
+
+    fn observers(&self) -> StoreObservers {
+        self.inner.observers()
+    }
+
+    fn track_field(&self) {
+        self.inner.track_field();
+        self.get_trigger(self.path().collect()).track();
+    }
+
+    fn reader(
+        &self,
+    ) -> impl for<'a> Fn(&'a RwLock<Self::Orig>) -> MappedRwLockReadGuard<'a, T>
+           + Send
+           + Sync
+           + 'static {
+        let inner = self.inner.clone();
+        let read = self.read;
+        move |lock| {
+            let inner = inner.reader();
+            let lock = inner(lock);
+            MappedRwLockReadGuard::map(lock, |inner| {
+                (read)(inner).expect("the enum has changed to another variant")
+            })
+        }
+    }
+
+    fn writer(
+        self,
+    ) -> impl for<'a> Fn(&'a RwLock<Self::Orig>) -> MappedRwLockWriteGuard<'a, T>
+           + Send
+           + Sync
+           + 'static {
+        move |lock| {
+            let inner = self.inner.clone().writer();
+            let lock = inner(lock);
+            MappedRwLockWriteGuard::map(lock, |inner| {
+                (self.write)(inner)
+                    .expect("the enum has changed to another variant")
+            })
+        }
+    }
+}
+
+impl<Inner, Prev, T> DefinedAt for VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev>,
+{
+    fn defined_at(&self) -> Option<&'static Location<'static>> {
+        #[cfg(debug_assertions)]
+        {
+            Some(self.defined_at)
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            None
+        }
+    }
+}
+
+impl<Inner, Prev, T> Track for VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev> + Send + Sync + Clone + 'static,
+    Prev: 'static,
+    T: 'static,
+{
+    fn track(&self) {
+        self.track_field();
+    }
+}
+
+impl<Inner, Prev, T> SignalWithUntracked for VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev> + SignalWithUntracked<Value = Prev>,
+{
+    type Value = T;
+
+    fn try_with_untracked<U>(
+        &self,
+        fun: impl FnOnce(&Self::Value) -> U,
+    ) -> Option<U> {
+        self.inner
+            .try_with_untracked(|prev| (self.read)(prev).map(fun))
+            .flatten()
+    }
+}
+
+impl<Inner, Prev, T> SignalIsDisposed for VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev>,
+{
+    fn is_disposed(&self) -> bool {
+        false
+    }
+}
+
+impl<Inner, Prev, T> Trigger for VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev> + Send + Sync + Clone + 'static,
+    Prev: 'static,
+    T: 'static,
+{
+    fn trigger(&self) {
+        self.trigger_field();
+    }
+}
+
+impl<Inner, Prev, T> SignalUpdateUntracked for VariantField<Inner, Prev, T>
+where
+    Inner: StoreField<Prev> + SignalUpdateUntracked<Value = Prev>,
+{
+    type Value = T;
+
+    fn try_update_untracked<U>(
+        &self,
+        fun: impl FnOnce(&mut Self::Value) -> U,
+    ) -> Option<U> {
+        self.inner
+            .try_update_untracked(|prev| (self.write)(prev).map(fun))
+            .flatten()
+    }
+}
@@ -0,0 +1,219 @@
+//! A deterministic runtime for testing effects and async signals.
+//!
+//! [`install`] sets an [`Executor`] that queues spawned tasks on the current
+//! thread, instead of running them in the background. Tests then decide
+//! exactly when those tasks run by calling [`tick`] or [`run_until_stalled`],
+//! so they don't need to sleep and hope that an effect has run in time.
+//!
+//! [`run_count`] reports how many times an effect, memo or async derived has
+//! run, so tests can assert that nothing reruns more often than it should.
+//!
+//! Tasks that [`sleep`](crate::spawn::sleep) wait for a clock that only moves
+//! when [`advance`] is called, so timeouts take no real time either.
+//!
+//! ```
+//! use tachy_reaccy::{prelude::*, testing};
+//!
+//! testing::install();
+//!
+//! let a = RwSignal::new(0);
+//! let effect = Effect::new(move |_| {
+//!     a.get();
+//! });
+//! assert_eq!(testing::run_count(&effect), 0);
+//!
+//! testing::run_until_stalled();
+//! assert_eq!(testing::run_count(&effect), 1);
+//!
+//! a.set(1);
+//! a.set(2);
+//! testing::run_until_stalled();
+//! assert_eq!(testing::run_count(&effect), 2);
+//! ```
+
+use crate::{
+    source::{AnySubscriber, ToAnySubscriber},
+    spawn::{set_executor, Executor, PinnedLocalFuture, PinnedSendFuture},
+};
+use futures::{
+    future,
+    task::{waker, ArcWake},
+};
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static TASKS: RefCell<Vec<Option<PinnedLocalFuture>>> =
+        const { RefCell::new(Vec::new()) };
+    static READY: Arc<Mutex<VecDeque<usize>>> = Default::default();
+    static RUNS: RefCell<FxHashMap<usize, usize>> = Default::default();
+    static CLOCK: RefCell<Clock> = Default::default();
+}
+
+// the time that has passed on this thread according to `advance`, and the
+// tasks that are sleeping until a later time
+#[derive(Default)]
+struct Clock {
+    now: Duration,
+    sleeping: Vec<(Duration, Waker)>,
+}
+
+/// An [`Executor`] that queues tasks until [`tick`] or [`run_until_stalled`]
+/// is called.
+///
+/// Tasks are queued on the thread that spawns them, and only run on that
+/// thread, so each test has its own queue even when tests run in parallel.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TestExecutor;
+
+impl Executor for TestExecutor {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        self.spawn_local(fut);
+    }
+
+    fn spawn_local(&self, fut: PinnedLocalFuture) {
+        let id = TASKS.with(|tasks| {
+            let mut tasks = tasks.borrow_mut();
+            tasks.push(Some(fut));
+            tasks.len() - 1
+        });
+        READY.with(|ready| ready.lock().push_back(id));
+    }
+
+    fn sleep(&self, duration: Duration) -> PinnedSendFuture {
+        let until = CLOCK.with(|clock| clock.borrow().now) + duration;
+        Box::pin(future::poll_fn(move |cx| {
+            CLOCK.with(|clock| {
+                let mut clock = clock.borrow_mut();
+                if clock.now >= until {
+                    Poll::Ready(())
+                } else {
+                    clock.sleeping.push((until, cx.waker().clone()));
+                    Poll::Pending
+                }
+            })
+        }))
+    }
+}
+
+/// Installs the [`TestExecutor`] as the executor for all reactive tasks.
+///
+/// This can be called at the start of every test.
+///
+/// ## Panics
+/// Panics if a different executor has already been installed.
+pub fn install() {
+    if set_executor(TestExecutor).is_ok() {
+        INSTALLED.store(true, Ordering::Relaxed);
+    } else if !INSTALLED.load(Ordering::Relaxed) {
+        panic!(
+            "Tried to install the test executor, but a different executor has \
+             already been installed."
+        );
+    }
+}
+
+struct Wake {
+    id: usize,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl ArcWake for Wake {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        let mut ready = arc_self.ready.lock();
+        if !ready.contains(&arc_self.id) {
+            ready.push_back(arc_self.id);
+        }
+    }
+}
+
+/// Polls each task that is ready to make progress once, and returns how many
+/// tasks were polled.
+///
+/// Tasks that are woken up while this runs (for example, effects that depend
+/// on a signal set by another effect) are not polled until the next tick.
+pub fn tick() -> usize {
+    let ready = READY.with(Arc::clone);
+    let batch = ready.lock().drain(..).collect::<Vec<_>>();
+    for &id in &batch {
+        // take the task out while polling it, so that it can spawn new tasks
+        let Some(mut fut) =
+            TASKS.with(|tasks| tasks.borrow_mut().get_mut(id)?.take())
+        else {
+            continue;
+        };
+        let waker = waker(Arc::new(Wake {
+            id,
+            ready: Arc::clone(&ready),
+        }));
+        let mut cx = Context::from_waker(&waker);
+        if fut.as_mut().poll(&mut cx).is_pending() {
+            TASKS.with(|tasks| tasks.borrow_mut()[id] = Some(fut));
+        }
+    }
+    batch.len()
+}
+
+/// Ticks until no tasks are ready to make progress, and returns how many
+/// ticks that took.
+pub fn run_until_stalled() -> usize {
+    let mut ticks = 0;
+    while tick() > 0 {
+        ticks += 1;
+    }
+    ticks
+}
+
+/// Moves the clock of this thread forward by `duration`, waking the tasks
+/// whose [`sleep`](crate::spawn::sleep) has ended. They run on the next tick.
+pub fn advance(duration: Duration) {
+    let woken = CLOCK.with(|clock| {
+        let mut clock = clock.borrow_mut();
+        clock.now += duration;
+        let now = clock.now;
+        let (woken, sleeping): (Vec<_>, Vec<_>) =
+            mem::take(&mut clock.sleeping)
+                .into_iter()
+                .partition(|(until, _)| *until <= now);
+        clock.sleeping = sleeping;
+        woken
+    });
+    for (_, waker) in woken {
+        waker.wake();
+    }
+}
+
+/// Returns the number of tasks that have been spawned on this thread and have
+/// not yet completed.
+pub fn pending_tasks() -> usize {
+    TASKS.with(|tasks| tasks.borrow().iter().filter(|t| t.is_some()).count())
+}
+
+/// Returns how many times the given effect, memo or async derived has run on
+/// this thread.
+pub fn run_count(node: &impl ToAnySubscriber) -> usize {
+    let id = node.to_any_subscriber().0;
+    RUNS.with(|runs| runs.borrow().get(&id).copied().unwrap_or_default())
+}
+
+/// Resets all the run counts on this thread.
+pub fn reset_run_counts() {
+    RUNS.with(|runs| runs.borrow_mut().clear());
+}
+
+pub(crate) fn record_run(subscriber: &AnySubscriber) {
+    RUNS.with(|runs| *runs.borrow_mut().entry(subscriber.0).or_default() += 1);
+}
@@ -0,0 +1,171 @@
+use futures::{channel::oneshot, FutureExt};
+use parking_lot::Mutex;
+use std::{future::IntoFuture, sync::Arc};
+use tachy_reaccy::{
+    async_signal::{ArcAsyncDerived, AsyncError, AsyncState},
+    prelude::*,
+    testing::{self, run_count, run_until_stalled},
+};
+
+type Senders = Arc<Mutex<Vec<oneshot::Sender<i32>>>>;
+
+// each run waits for a value to be sent on a new channel
+fn derived_from_channels(
+    source: RwSignal<i32>,
+) -> (ArcAsyncDerived<i32>, Senders) {
+    let senders: Senders = Default::default();
+    let derived = ArcAsyncDerived::new({
+        let senders = Arc::clone(&senders);
+        move || {
+            source.track();
+            let (tx, rx) = oneshot::channel();
+            senders.lock().push(tx);
+            async move { rx.await.unwrap_or_default() }
+        }
+    });
+    (derived, senders)
+}
+
+#[test]
+fn superseded_runs_are_cancelled() {
+    testing::install();
+
+    let source = RwSignal::new(0);
+    let (derived, senders) = derived_from_channels(source);
+    run_until_stalled();
+    assert_eq!(senders.lock().len(), 1);
+
+    source.set(1);
+    run_until_stalled();
+    assert_eq!(senders.lock().len(), 2);
+    assert_eq!(derived.get_untracked(), AsyncState::Loading);
+
+    // the first run has been dropped, so nothing is listening any more
+    let first = senders.lock().remove(0);
+    assert!(first.is_canceled());
+    assert!(first.send(1).is_err());
+
+    let second = senders.lock().remove(0);
+    second.send(2).unwrap();
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Complete(2));
+}
+
+#[test]
+fn reloading_keeps_the_last_value_until_a_run_finishes() {
+    testing::install();
+
+    let source = RwSignal::new(0);
+    let (derived, senders) = derived_from_channels(source);
+    run_until_stalled();
+    senders.lock().remove(0).send(1).unwrap();
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Complete(1));
+
+    // the source changes twice while the first rerun is still pending
+    source.set(1);
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Reloading(1));
+    source.set(2);
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Reloading(1));
+
+    senders.lock().pop().unwrap().send(3).unwrap();
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Complete(3));
+}
+
+#[test]
+fn stale_values_never_overwrite_newer_ones() {
+    testing::install();
+
+    let source = RwSignal::new(0);
+    let (derived, senders) = derived_from_channels(source);
+    run_until_stalled();
+    senders.lock().remove(0).send(1).unwrap();
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Complete(1));
+
+    source.set(1);
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Reloading(1));
+    source.set(2);
+    run_until_stalled();
+
+    // the latest run finishes first, and the earlier one can't finish later
+    let mut senders = senders.lock();
+    senders.pop().unwrap().send(3).unwrap();
+    run_until_stalled();
+    assert!(senders.pop().unwrap().send(2).is_err());
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Complete(3));
+}
+
+#[test]
+fn refetch_reruns_without_source_changes() {
+    testing::install();
+
+    let runs = Arc::new(Mutex::new(0));
+    let derived = ArcAsyncDerived::new({
+        let runs = Arc::clone(&runs);
+        move || {
+            *runs.lock() += 1;
+            let run = *runs.lock();
+            async move { run }
+        }
+    });
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Complete(1));
+
+    derived.refetch();
+    run_until_stalled();
+    assert_eq!(run_count(&derived), 2);
+    assert_eq!(derived.get_untracked(), AsyncState::Complete(2));
+}
+
+#[test]
+fn dropping_the_signal_cancels_the_current_run() {
+    testing::install();
+
+    let source = RwSignal::new(0);
+    let (derived, senders) = derived_from_channels(source);
+    run_until_stalled();
+
+    drop(derived);
+    run_until_stalled();
+    assert!(senders.lock()[0].is_canceled());
+    assert_eq!(testing::pending_tasks(), 0);
+}
+
+#[test]
+fn failed_runs_resolve_with_their_error() {
+    testing::install();
+
+    let source = RwSignal::new(0);
+    let derived = ArcAsyncDerived::new_fallible(move || {
+        let n = source.get();
+        async move {
+            if n < 0 {
+                Err(format!("{n} is negative"))
+            } else {
+                Ok(n)
+            }
+        }
+    });
+    run_until_stalled();
+    assert_eq!(derived.result().now_or_never(), Some(Ok(0)));
+
+    source.set(-1);
+    run_until_stalled();
+    let error = AsyncError::Failed("-1 is negative".to_string());
+    assert_eq!(derived.get_untracked(), AsyncState::Error(error.clone()));
+    assert_eq!(derived.result().now_or_never(), Some(Err(error)));
+    // awaiting the value itself waits for a run that succeeds
+    let mut value = derived.clone().into_future();
+    assert_eq!((&mut value).now_or_never(), None);
+
+    source.set(1);
+    run_until_stalled();
+    assert_eq!(value.now_or_never(), Some(1));
+    assert_eq!(derived.result().now_or_never(), Some(Ok(1)));
+}
@@ -4,14 +4,35 @@
 }; */
 use parking_lot::RwLock;
 use std::{mem, sync::Arc};
-use tachy_reaccy::prelude::*;
+use tachy_reaccy::{
+    prelude::*,
+    spawn::{set_executor, Executor, PinnedLocalFuture, PinnedSendFuture},
+};
 
 pub async fn tick() {
     tokio::time::sleep(std::time::Duration::from_micros(1)).await;
 }
 
+// spawns effects on the test's tokio runtime, whichever features are enabled
+struct TokioExecutor;
+
+impl Executor for TokioExecutor {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        tokio::task::spawn(fut);
+    }
+
+    fn spawn_local(&self, fut: PinnedLocalFuture) {
+        tokio::task::spawn_local(fut);
+    }
+}
+
+fn install_tokio() {
+    _ = set_executor(TokioExecutor);
+}
+
 #[tokio::test]
 async fn effect_runs() {
+    install_tokio();
     let a = RwSignal::new(-1);
 
     // simulate an arbitrary side effect
@@ -39,6 +60,7 @@ async fn effect_runs() {
 
 #[tokio::test]
 async fn dynamic_dependencies() {
+    install_tokio();
     let first = RwSignal::new("Greg");
     let last = RwSignal::new("Johnston");
     let use_last = RwSignal::new(true);
@@ -96,6 +118,48 @@ async fn dynamic_dependencies() {
     assert_eq!(*combined_count.read(), 5);
 }
 
+// run counts are only recorded with the `testing` feature
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn batch_notifies_subscribers_once() {
+    use tachy_reaccy::testing;
+
+    install_tokio();
+
+    let first = RwSignal::new("Greg");
+    let last = RwSignal::new("Johnston");
+    let full_name =
+        Memo::new(move |_| format!("{} {}", first.get(), last.get()));
+
+    let runs = Arc::new(RwLock::new(Vec::new()));
+
+    mem::forget(Effect::new_sync({
+        let runs = Arc::clone(&runs);
+        move |_| {
+            runs.write().push(full_name.get());
+        }
+    }));
+
+    tick().await;
+    assert_eq!(*runs.read(), ["Greg Johnston"]);
+    assert_eq!(testing::run_count(&full_name), 1);
+
+    batch(|| {
+        first.set("Bob");
+        // the memo is not marked as changed until the batch ends, so reading
+        // it does not run it for each update
+        assert_eq!(full_name.get_untracked(), "Greg Johnston");
+        batch(|| last.set("Thompson"));
+        // signals themselves are updated immediately
+        assert_eq!(last.get_untracked(), "Thompson");
+    });
+    assert_eq!(full_name.get_untracked(), "Bob Thompson");
+    assert_eq!(testing::run_count(&full_name), 2);
+
+    tick().await;
+    assert_eq!(*runs.read(), ["Greg Johnston", "Bob Thompson"]);
+}
+
 /*
 #[test]
 fn effect_tracks_memo() {
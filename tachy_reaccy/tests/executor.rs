@@ -0,0 +1,62 @@
+use futures::{
+    executor::{LocalPool, LocalSpawner},
+    task::LocalSpawnExt,
+};
+use parking_lot::RwLock;
+use std::{cell::RefCell, mem, sync::Arc};
+use tachy_reaccy::{
+    prelude::*,
+    spawn::{set_executor, Executor, PinnedLocalFuture, PinnedSendFuture},
+};
+
+thread_local! {
+    static POOL: RefCell<LocalPool> = RefCell::new(LocalPool::new());
+    static SPAWNER: LocalSpawner = POOL.with(|pool| pool.borrow().spawner());
+}
+
+struct Pool;
+
+impl Executor for Pool {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        self.spawn_local(fut);
+    }
+
+    fn spawn_local(&self, fut: PinnedLocalFuture) {
+        SPAWNER.with(|spawner| spawner.spawn_local(fut)).unwrap();
+    }
+}
+
+fn run_until_stalled() {
+    POOL.with(|pool| pool.borrow_mut().run_until_stalled());
+}
+
+#[test]
+fn effects_run_on_installed_executor() {
+    _ = set_executor(Pool);
+
+    let a = RwSignal::new(-1);
+    let b = Arc::new(RwLock::new(String::new()));
+
+    mem::forget(Effect::new({
+        let b = Arc::clone(&b);
+        move |_| {
+            *b.write() = format!("Value is {}", a.get());
+        }
+    }));
+
+    // nothing runs until the executor polls the task
+    assert_eq!(b.read().as_str(), "");
+    run_until_stalled();
+    assert_eq!(b.read().as_str(), "Value is -1");
+
+    a.set(1);
+    assert_eq!(b.read().as_str(), "Value is -1");
+    run_until_stalled();
+    assert_eq!(b.read().as_str(), "Value is 1");
+}
+
+#[test]
+fn executor_can_only_be_set_once() {
+    _ = set_executor(Pool);
+    assert!(set_executor(Pool).is_err());
+}
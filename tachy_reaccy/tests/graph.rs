@@ -0,0 +1,169 @@
+use std::{mem, time::Duration};
+use tachy_reaccy::{
+    graph::{GraphEdge, NodeKind, ReactiveGraph, ReactiveNodeState},
+    prelude::*,
+    spawn::{set_executor, Executor, PinnedLocalFuture, PinnedSendFuture},
+    store::ArcStore,
+    Owner,
+};
+
+pub async fn tick() {
+    tokio::time::sleep(Duration::from_micros(1)).await;
+}
+
+// spawns effects on the test's tokio runtime, whichever features are enabled
+struct TokioExecutor;
+
+impl Executor for TokioExecutor {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        tokio::task::spawn(fut);
+    }
+
+    fn spawn_local(&self, fut: PinnedLocalFuture) {
+        tokio::task::spawn_local(fut);
+    }
+}
+
+fn install_tokio() {
+    _ = set_executor(TokioExecutor);
+}
+
+fn node_of(graph: &ReactiveGraph, kind: NodeKind) -> Vec<usize> {
+    graph
+        .nodes()
+        .iter()
+        .filter(|node| node.kind == kind)
+        .map(|node| node.id)
+        .collect()
+}
+
+#[tokio::test]
+async fn graph_contains_owned_nodes_and_their_dependencies() {
+    install_tokio();
+    // created outside the owner, but reachable through the memo
+    let a = RwSignal::new(1);
+
+    let owner = Owner::new();
+    let (b, sum, effect) = owner.with(|| {
+        let b = RwSignal::new(2);
+        let sum = Memo::new(move |_| a.get() + b.get());
+        let effect = Effect::new_sync(move |_| sum.get());
+        (b, sum, effect)
+    });
+    tick().await;
+
+    let graph = ReactiveGraph::from_owner(&owner);
+    let signals = node_of(&graph, NodeKind::Signal);
+    let memo = node_of(&graph, NodeKind::Memo)[0];
+    let effect_node = node_of(&graph, NodeKind::Effect)[0];
+    assert_eq!(graph.nodes().len(), 4);
+    assert_eq!(signals.len(), 2);
+    assert_eq!(graph.edges().len(), 3);
+    for signal in signals {
+        assert!(graph.edges().contains(&GraphEdge {
+            source: signal,
+            subscriber: memo,
+        }));
+    }
+    assert!(graph.edges().contains(&GraphEdge {
+        source: memo,
+        subscriber: effect_node,
+    }));
+    for node in graph.nodes() {
+        assert!(node.defined_at.unwrap().file().ends_with("graph.rs"));
+    }
+
+    // a signal on its own isn't connected to anything else
+    assert_eq!(ReactiveGraph::from_owner(&Owner::new()).nodes().len(), 0);
+    let lonely = Owner::new();
+    lonely.with(|| RwSignal::new(0));
+    let graph = ReactiveGraph::from_owner(&lonely);
+    assert_eq!(graph.nodes().len(), 1);
+    assert!(graph.edges().is_empty());
+
+    _ = (b, sum);
+    mem::forget(effect);
+}
+
+#[tokio::test]
+async fn graph_reports_node_state() {
+    let owner = Owner::new();
+    let (a, double) = owner.with(|| {
+        let a = RwSignal::new(1);
+        (a, ArcMemo::new(move |_| a.get() * 2))
+    });
+    let state = |graph: &ReactiveGraph| {
+        graph.nodes()[node_of(graph, NodeKind::Memo)[0]].state
+    };
+
+    assert_eq!(
+        state(&ReactiveGraph::from_owner(&owner)),
+        ReactiveNodeState::Dirty
+    );
+    assert_eq!(double.get(), 2);
+    assert_eq!(
+        state(&ReactiveGraph::from_owner(&owner)),
+        ReactiveNodeState::Clean
+    );
+    a.set(2);
+    assert_eq!(
+        state(&ReactiveGraph::from_owner(&owner)),
+        ReactiveNodeState::Check
+    );
+    assert_eq!(double.get(), 4);
+    assert_eq!(
+        state(&ReactiveGraph::from_owner(&owner)),
+        ReactiveNodeState::Clean
+    );
+}
+
+#[test]
+fn graph_labels_store_fields_with_their_path() {
+    let owner = Owner::new();
+    let (store, memo) = owner.with(|| {
+        let store = ArcStore::new(vec![1, 2, 3]);
+        let first = store.clone().index(1).arc_read();
+        let memo = ArcMemo::new({
+            let first = first.clone();
+            move |_| first.get() * 2
+        });
+        assert_eq!(memo.get(), 4);
+        (store, memo)
+    });
+
+    let graph = ReactiveGraph::from_owner(&owner);
+    let field = &graph.nodes()[node_of(&graph, NodeKind::StoreField)[0]];
+    assert_eq!(field.label.as_deref(), Some("1"));
+    assert_eq!(graph.edges().len(), 1);
+    _ = (store, memo);
+}
+
+#[test]
+fn graph_exports_to_dot_and_json() {
+    let owner = Owner::new();
+    let double = owner.with(|| {
+        let count = RwSignal::new(1);
+        ArcMemo::new(move |_| count.get() * 2)
+    });
+    assert_eq!(double.get(), 2);
+    let graph = ReactiveGraph::from_owner(&owner);
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph reactive_graph {"));
+    assert!(dot.contains("0 -> 1;") || dot.contains("1 -> 0;"));
+    assert!(dot.contains("shape=box"));
+    assert!(dot.contains("graph.rs:"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&graph.to_json()).unwrap();
+    let kinds = json["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node["kind"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert!(kinds.contains(&"signal"));
+    assert!(kinds.contains(&"memo"));
+    assert_eq!(json["nodes"][0]["state"], "clean");
+    assert_eq!(json["edges"].as_array().unwrap().len(), 1);
+}
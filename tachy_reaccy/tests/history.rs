@@ -0,0 +1,140 @@
+use std::time::Duration;
+use tachy_reaccy::{
+    effect::Effect,
+    history::{ArcHistory, History},
+    prelude::*,
+    store::{ArcStore, Subfield},
+    testing::{self, run_until_stalled},
+};
+
+#[test]
+fn undo_and_redo_restore_snapshots() {
+    let count = ArcRwSignal::new(0);
+    let history = ArcHistory::new(count.clone());
+
+    history.set(1);
+    history.update(|n| *n += 1);
+    assert_eq!(count.get_untracked(), 2);
+
+    assert!(history.undo());
+    assert_eq!(count.get_untracked(), 1);
+    assert!(history.undo());
+    assert_eq!(count.get_untracked(), 0);
+    assert!(!history.undo());
+
+    assert!(history.redo());
+    assert_eq!(count.get_untracked(), 1);
+
+    // a new edit discards the edits that could be redone
+    history.set(5);
+    assert!(!history.redo());
+    assert!(history.undo());
+    assert_eq!(count.get_untracked(), 1);
+}
+
+#[test]
+fn arena_histories_are_copy() {
+    let count = RwSignal::new(0);
+    let history = History::new(count);
+    let edit = move || history.update(|n| *n += 1);
+
+    edit();
+    edit();
+    assert!(history.undo());
+    assert_eq!(count.get_untracked(), 1);
+    assert_eq!(
+        history.can_redo().map(|can| can.get_untracked()),
+        Some(true)
+    );
+}
+
+#[test]
+fn capacity_discards_the_oldest_edits() {
+    let count = ArcRwSignal::new(0);
+    let history = ArcHistory::new(count.clone()).with_capacity(2);
+
+    for n in 1..=4 {
+        history.set(n);
+    }
+    assert!(history.undo());
+    assert!(history.undo());
+    assert!(!history.undo());
+    assert_eq!(count.get_untracked(), 2);
+}
+
+#[test]
+fn rapid_edits_and_transactions_are_undone_together() {
+    let text = ArcRwSignal::new(String::new());
+    let history =
+        ArcHistory::new(text.clone()).coalesce_within(Duration::from_secs(60));
+
+    for ch in "abc".chars() {
+        history.update(|text| text.push(ch));
+    }
+    assert!(history.undo());
+    assert_eq!(text.get_untracked(), "");
+
+    // without coalescing, the edits in a transaction are still one step
+    let history = ArcHistory::new(text.clone());
+    history.set("draft".to_string());
+    history.transaction(|| {
+        history.update(|text| text.push_str(", edited"));
+        history.transaction(|| history.update(|text| text.push('!')));
+    });
+    assert_eq!(text.get_untracked(), "draft, edited!");
+    assert!(history.undo());
+    assert_eq!(text.get_untracked(), "draft");
+    assert!(history.undo());
+    assert_eq!(text.get_untracked(), "");
+}
+
+#[test]
+fn store_fields_have_independent_histories() {
+    let store = ArcStore::new((String::from("title"), 0));
+    let title = Subfield::new(
+        store.clone(),
+        0.into(),
+        |(title, _)| title,
+        |(title, _)| title,
+    );
+    let count = Subfield::new(store.clone(), 1.into(), |(_, n)| n, |(_, n)| n);
+    let title_history = ArcHistory::new(title);
+    let count_history = ArcHistory::new(count);
+
+    title_history.set("edited".to_string());
+    count_history.set(1);
+    count_history.set(2);
+
+    assert!(title_history.undo());
+    assert_eq!(store.get_untracked(), ("title".to_string(), 2));
+    assert!(count_history.undo());
+    assert_eq!(store.get_untracked(), ("title".to_string(), 1));
+}
+
+#[test]
+fn can_undo_and_can_redo_are_signals() {
+    testing::install();
+
+    let count = ArcRwSignal::new(0);
+    let history = ArcHistory::new(count);
+    let can_undo = history.can_undo();
+    let can_redo = history.can_redo();
+    let effect = Effect::new(move |_| (can_undo.get(), can_redo.get()));
+    run_until_stalled();
+    assert_eq!(testing::run_count(&effect), 1);
+
+    history.set(1);
+    run_until_stalled();
+    assert!(history.can_undo().get_untracked());
+    assert_eq!(testing::run_count(&effect), 2);
+
+    // another edit does not change whether there is something to undo
+    history.set(2);
+    run_until_stalled();
+    assert_eq!(testing::run_count(&effect), 2);
+
+    history.undo();
+    run_until_stalled();
+    assert!(history.can_redo().get_untracked());
+    assert_eq!(testing::run_count(&effect), 3);
+}
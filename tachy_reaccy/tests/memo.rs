@@ -1,11 +1,31 @@
 use parking_lot::RwLock;
 use std::{mem, sync::Arc};
-use tachy_reaccy::prelude::*;
+use tachy_reaccy::{
+    prelude::*,
+    spawn::{set_executor, Executor, PinnedLocalFuture, PinnedSendFuture},
+};
 
 pub async fn tick() {
     tokio::time::sleep(std::time::Duration::from_micros(1)).await;
 }
 
+// spawns effects on the test's tokio runtime, whichever features are enabled
+struct TokioExecutor;
+
+impl Executor for TokioExecutor {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        tokio::task::spawn(fut);
+    }
+
+    fn spawn_local(&self, fut: PinnedLocalFuture) {
+        tokio::task::spawn_local(fut);
+    }
+}
+
+fn install_tokio() {
+    _ = set_executor(TokioExecutor);
+}
+
 #[test]
 fn memo_calculates_value() {
     let a = RwSignal::new(1);
@@ -153,6 +173,7 @@ fn diamond_problem() {
 
 #[tokio::test]
 async fn dynamic_dependencies() {
+    install_tokio();
     let first = RwSignal::new("Greg");
     let last = RwSignal::new("Johnston");
     let use_last = RwSignal::new(true);
@@ -217,3 +238,57 @@ async fn dynamic_dependencies() {
 
     assert_eq!(*combined_count.read(), 5);
 }
+
+#[test]
+fn memo_with_compare_only_notifies_when_not_equal() {
+    let calculations = Arc::new(RwLock::new(0));
+
+    let id = RwSignal::new(1);
+    let name = RwSignal::new("Alice");
+    let user = Memo::new_with_compare(
+        move |_| (id.get(), name.get()),
+        |a, b| a.0 == b.0,
+    );
+    let greeting = Memo::new({
+        let calculations = Arc::clone(&calculations);
+        move |_| {
+            *calculations.write() += 1;
+            format!("Hello, user {}", user.get().0)
+        }
+    });
+    assert_eq!(greeting.get(), "Hello, user 1");
+
+    // only the ID is compared, so this doesn't rerun the greeting
+    name.set("Bob");
+    assert_eq!(greeting.get(), "Hello, user 1");
+    assert_eq!(*calculations.read(), 1);
+    // but the memo holds the latest value
+    assert_eq!(user.get(), (1, "Bob"));
+
+    id.set(2);
+    assert_eq!(greeting.get(), "Hello, user 2");
+    assert_eq!(*calculations.read(), 2);
+}
+
+#[test]
+fn memo_always_notify_works_without_partial_eq() {
+    struct NotEq(i32);
+
+    let calculations = Arc::new(RwLock::new(0));
+
+    let a = RwSignal::new(1);
+    let parity = Memo::new_always_notify(move |_| NotEq(a.get() % 2));
+    let b = Memo::new({
+        let calculations = Arc::clone(&calculations);
+        move |_| {
+            *calculations.write() += 1;
+            parity.with(|p| p.0)
+        }
+    });
+    assert_eq!(b.get(), 1);
+
+    // the parity is the same, but subscribers are notified anyway
+    a.set(3);
+    assert_eq!(b.get(), 1);
+    assert_eq!(*calculations.read(), 2);
+}
@@ -0,0 +1,229 @@
+use parking_lot::RwLock;
+use std::{mem, sync::Arc};
+use tachy_reaccy::{
+    prelude::*,
+    render_effect::RenderEffect,
+    spawn::{set_executor, Executor, PinnedLocalFuture, PinnedSendFuture},
+    Owner,
+};
+
+pub async fn tick() {
+    tokio::time::sleep(std::time::Duration::from_micros(1)).await;
+}
+
+// spawns effects on the test's tokio runtime, whichever features are enabled
+struct TokioExecutor;
+
+impl Executor for TokioExecutor {
+    fn spawn(&self, fut: PinnedSendFuture) {
+        tokio::task::spawn(fut);
+    }
+
+    fn spawn_local(&self, fut: PinnedLocalFuture) {
+        tokio::task::spawn_local(fut);
+    }
+}
+
+fn install_tokio() {
+    _ = set_executor(TokioExecutor);
+}
+
+#[test]
+fn dispose_runs_cleanups_depth_first() {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let push = |name: &'static str| {
+        let log = Arc::clone(&log);
+        move || log.write().push(name)
+    };
+
+    let parent = Owner::new();
+    let (child, grandchild) = parent.with(|| {
+        on_cleanup(push("parent"));
+        let child = Owner::new();
+        let grandchild = child.with(|| {
+            on_cleanup(push("child"));
+            let grandchild = Owner::new();
+            grandchild.with(|| on_cleanup(push("grandchild")));
+            grandchild
+        });
+        (child, grandchild)
+    });
+    assert!(log.read().is_empty());
+
+    parent.dispose();
+    assert_eq!(*log.read(), ["grandchild", "child", "parent"]);
+
+    // cleanups only run once
+    drop((child, grandchild));
+    parent.dispose();
+    assert_eq!(log.read().len(), 3);
+}
+
+#[test]
+fn dispose_removes_stored_values_of_descendants() {
+    let parent = Owner::new();
+    let child = parent.with(Owner::new);
+    let (a, b) =
+        parent.with(|| (RwSignal::new(1), child.with(|| RwSignal::new(2))));
+    assert_eq!(a.try_get(), Some(1));
+    assert_eq!(b.try_get(), Some(2));
+
+    parent.dispose();
+    assert_eq!(a.try_get(), None);
+    assert_eq!(b.try_get(), None);
+}
+
+#[test]
+fn rerunning_cleans_up_children() {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let owner = Owner::new();
+    let run = || {
+        owner.with_cleanup(|| {
+            let log = Arc::clone(&log);
+            on_cleanup({
+                let log = Arc::clone(&log);
+                move || log.write().push("owner")
+            });
+            let child = Owner::new();
+            child.with(|| on_cleanup(move || log.write().push("child")));
+            child
+        })
+    };
+
+    let _child = run();
+    assert!(log.read().is_empty());
+
+    // the child is still alive, but the owner that created it is rerunning
+    let _child = run();
+    assert_eq!(*log.read(), ["child", "owner"]);
+}
+
+#[test]
+fn dropping_owner_disposes_children() {
+    let cleaned_up = Arc::new(RwLock::new(false));
+    let parent = Owner::new();
+    let child = parent.with(Owner::new);
+    child.with(|| {
+        let cleaned_up = Arc::clone(&cleaned_up);
+        on_cleanup(move || *cleaned_up.write() = true)
+    });
+
+    drop(parent);
+    assert!(*cleaned_up.read());
+}
+
+#[tokio::test]
+async fn dispose_stops_effects() {
+    install_tokio();
+    let a = RwSignal::new(0);
+    let runs = Arc::new(RwLock::new(0));
+    let cleanups = Arc::new(RwLock::new(0));
+
+    let owner = Owner::new();
+    let effect = owner.with(|| {
+        Effect::new_sync({
+            let runs = Arc::clone(&runs);
+            let cleanups = Arc::clone(&cleanups);
+            move |_| {
+                a.track();
+                *runs.write() += 1;
+                let cleanups = Arc::clone(&cleanups);
+                on_cleanup(move || *cleanups.write() += 1);
+            }
+        })
+    });
+
+    tick().await;
+    assert_eq!(*runs.read(), 1);
+
+    a.set(1);
+    tick().await;
+    assert_eq!(*runs.read(), 2);
+    assert_eq!(*cleanups.read(), 1);
+
+    owner.dispose();
+    assert_eq!(*cleanups.read(), 2);
+
+    a.set(2);
+    tick().await;
+    assert_eq!(*runs.read(), 2);
+    mem::forget(effect);
+}
+
+#[tokio::test]
+async fn dispose_stops_render_effects() {
+    install_tokio();
+
+    // render effects rerun on the current thread
+    tokio::task::LocalSet::new()
+        .run_until(async {
+            let a = RwSignal::new(0);
+            let runs = Arc::new(RwLock::new(0));
+
+            let owner = Owner::new();
+            let effect = owner.with(|| {
+                RenderEffect::new({
+                    let runs = Arc::clone(&runs);
+                    move |_| {
+                        a.track();
+                        *runs.write() += 1;
+                    }
+                })
+            });
+            assert_eq!(*runs.read(), 1);
+
+            a.set(1);
+            tick().await;
+            assert_eq!(*runs.read(), 2);
+
+            owner.dispose();
+            a.set(2);
+            tick().await;
+            assert_eq!(*runs.read(), 2);
+            mem::forget(effect);
+        })
+        .await;
+}
+
+#[test]
+fn owners_are_positioned_by_their_parents() {
+    let root = Owner::new();
+    let (first, second, grandchild) = root.with(|| {
+        let first = Owner::new();
+        let second = Owner::new();
+        let grandchild = second.with(Owner::new);
+        (first, second, grandchild)
+    });
+    assert_eq!(root.position(), Vec::<u16>::new());
+    assert_eq!(first.position(), [0]);
+    assert_eq!(second.position(), [1]);
+    assert_eq!(grandchild.position(), [1, 0]);
+
+    // an owner can be placed at a given position, without moving its siblings
+    let placed = root.with(|| Owner::new_at(vec![7, 2]));
+    let third = root.with(Owner::new);
+    assert_eq!(placed.position(), [7, 2]);
+    assert_eq!(third.position(), [2]);
+    assert_eq!(placed.with(Owner::new).position(), [7, 2, 0]);
+}
+
+#[test]
+fn effects_and_memos_do_not_take_positions() {
+    let root = Owner::new();
+    let created = Arc::new(RwLock::new(None));
+    let (first, second) = root.with(|| {
+        let first = Owner::new();
+        // e.g., a memo that only exists on the client
+        let memo = Memo::new({
+            let created = Arc::clone(&created);
+            move |_| *created.write() = Some(Owner::new())
+        });
+        memo.get_untracked();
+        let second = Owner::new();
+        (first, second)
+    });
+    assert_eq!(first.position(), [0]);
+    // owners created by the memo are placed as if the root created them
+    assert_eq!(created.read().as_ref().unwrap().position(), [1]);
+    assert_eq!(second.position(), [2]);
+}
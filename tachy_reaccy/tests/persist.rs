@@ -0,0 +1,190 @@
+use std::time::Duration;
+use tachy_reaccy::{
+    persist::{
+        ArcPersistedSignal, JsonFileStorage, MemoryStorage, PersistOptions,
+        PersistedSignal, Storage,
+    },
+    prelude::*,
+    serialization::Str,
+    Owner,
+};
+
+#[test]
+fn values_are_loaded_and_written_through() {
+    let storage = MemoryStorage::default();
+
+    let count = ArcPersistedSignal::new("count", storage.clone(), 0);
+    assert_eq!(count.get_untracked(), 0);
+    assert_eq!(storage.get("count").unwrap(), None);
+
+    count.update(|n| *n += 2);
+    assert_eq!(
+        storage.get("count").unwrap().as_deref(),
+        Some(r#"{"version":0,"data":"2"}"#)
+    );
+
+    let count = ArcPersistedSignal::new("count", storage.clone(), 0);
+    assert_eq!(count.get_untracked(), 2);
+
+    // values that cannot be loaded fall back to the initial value
+    storage.set("count", "not a saved value").unwrap();
+    let count = ArcPersistedSignal::new("count", storage, 5);
+    assert_eq!(count.get_untracked(), 5);
+}
+
+#[test]
+fn arena_signals_are_copy() {
+    let storage = MemoryStorage::default();
+    let name = PersistedSignal::new("name", storage.clone(), String::new());
+    let rename = move |new: &str| name.set(new.to_string());
+
+    rename("Bob");
+    let name = PersistedSignal::new("name", storage, String::new());
+    assert_eq!(name.get_untracked(), "Bob");
+}
+
+#[test]
+fn try_set_saves_until_the_signal_is_disposed() {
+    let storage = MemoryStorage::default();
+    let owner = Owner::new();
+    let count =
+        owner.with(|| PersistedSignal::new("count", storage.clone(), 0));
+
+    assert!(!count.is_disposed());
+    assert_eq!(count.try_set(5), None);
+    assert_eq!(count.get_untracked(), 5);
+    assert_eq!(
+        storage.get("count").unwrap().as_deref(),
+        Some(r#"{"version":0,"data":"5"}"#)
+    );
+
+    owner.dispose();
+    assert!(count.is_disposed());
+    assert_eq!(count.try_set(6), Some(6));
+    assert_eq!(
+        storage.get("count").unwrap().as_deref(),
+        Some(r#"{"version":0,"data":"5"}"#)
+    );
+}
+
+#[test]
+fn other_encodings_can_be_used() {
+    let storage = MemoryStorage::default();
+    let count = ArcPersistedSignal::<i32, Str>::new_with_encoding(
+        "count",
+        storage.clone(),
+        0,
+        PersistOptions::new(),
+    );
+
+    count.set(42);
+    assert_eq!(
+        storage.get("count").unwrap().as_deref(),
+        Some(r#"{"version":0,"data":"42"}"#)
+    );
+}
+
+// the debounce timer only moves when the test executor's clock is advanced
+#[cfg(feature = "testing")]
+#[test]
+fn debounced_updates_are_saved_together() {
+    use tachy_reaccy::testing::{self, advance, run_until_stalled};
+
+    testing::install();
+    let storage = MemoryStorage::default();
+    let options = PersistOptions::new().debounce(Duration::from_millis(50));
+    let count = ArcPersistedSignal::new_with_options(
+        "count",
+        storage.clone(),
+        0,
+        options,
+    );
+    let saved = || ArcPersistedSignal::new("count", storage.clone(), 0);
+
+    for n in 1..=3 {
+        count.set(n);
+    }
+    run_until_stalled();
+    assert_eq!(storage.get("count").unwrap(), None);
+
+    // each update restarts the wait
+    advance(Duration::from_millis(30));
+    run_until_stalled();
+    count.set(4);
+    advance(Duration::from_millis(30));
+    run_until_stalled();
+    assert_eq!(storage.get("count").unwrap(), None);
+
+    advance(Duration::from_millis(20));
+    run_until_stalled();
+    assert_eq!(saved().get_untracked(), 4);
+
+    // flushing saves the value without waiting
+    count.set(5);
+    count.flush();
+    assert_eq!(saved().get_untracked(), 5);
+    advance(Duration::from_millis(50));
+    run_until_stalled();
+    assert_eq!(testing::pending_tasks(), 0);
+}
+
+#[test]
+fn old_schema_versions_are_migrated() {
+    let storage = MemoryStorage::default();
+    storage
+        .set("size", r#"{"version":1,"data":"[3,4]"}"#)
+        .unwrap();
+
+    // without a migration, values saved with another version are ignored
+    let size = ArcPersistedSignal::new_with_options(
+        "size",
+        storage.clone(),
+        (0, 0, 0),
+        PersistOptions::new().version(2),
+    );
+    assert_eq!(size.get_untracked(), (0, 0, 0));
+
+    let options = PersistOptions::new().version(2).migrate(|version, data| {
+        assert_eq!(version, 1);
+        let (width, height): (u32, u32) = serde_json::from_str(&data).ok()?;
+        serde_json::to_string(&(width, height, 1)).ok()
+    });
+    let size = ArcPersistedSignal::new_with_options(
+        "size",
+        storage.clone(),
+        (0, 0, 0),
+        options,
+    );
+    assert_eq!(size.get_untracked(), (3, 4, 1));
+
+    // the migrated value is saved with the new version
+    assert_eq!(
+        storage.get("size").unwrap().as_deref(),
+        Some(r#"{"version":2,"data":"[3,4,1]"}"#)
+    );
+}
+
+#[test]
+fn json_files_hold_many_values() {
+    let path = std::env::temp_dir()
+        .join(format!("tachy_reaccy_persist_{}.json", std::process::id()));
+    let storage = JsonFileStorage::new(&path);
+
+    let count = ArcPersistedSignal::new("count", storage.clone(), 0);
+    let name = ArcPersistedSignal::new("name", storage.clone(), String::new());
+    count.set(7);
+    name.set("Alice".to_string());
+
+    // e.g., another process
+    let storage = JsonFileStorage::new(&path);
+    let count = ArcPersistedSignal::new("count", storage.clone(), 0);
+    let name = ArcPersistedSignal::new("name", storage.clone(), String::new());
+    assert_eq!(count.get_untracked(), 7);
+    assert_eq!(name.get_untracked(), "Alice");
+
+    storage.remove("count").unwrap();
+    assert_eq!(storage.get("count").unwrap(), None);
+    assert!(storage.get("name").unwrap().is_some());
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,497 @@
+use futures::{
+    channel::oneshot, executor::block_on, stream, FutureExt, StreamExt,
+};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tachy_reaccy::{
+    async_signal::{ArcResource, ArcStreamResource, AsyncError, AsyncState},
+    prelude::*,
+    serialization::{SerializableData, Serializer},
+    shared_context::{
+        SerializationError, SerializedDataId, SharedContext, SsrSharedContext,
+    },
+    store::ArcStore,
+    testing::{self, run_until_stalled},
+    Owner, PinnedFuture, PinnedStream, Root,
+};
+
+// a serializer that can never serialize anything
+struct Failing;
+
+impl Serializer for Failing {}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Data(i32);
+
+impl SerializableData<Failing> for Data {
+    type SerErr = &'static str;
+    type DeErr = ();
+
+    fn ser(&self) -> Result<String, Self::SerErr> {
+        Err("can't serialize this")
+    }
+
+    fn de(data: &str) -> Result<Self, Self::DeErr> {
+        data.parse().map(Data).map_err(|_| ())
+    }
+}
+
+#[test]
+fn serialization_errors_are_sent_to_the_client() {
+    testing::install();
+
+    let shared_context = Arc::new(SsrSharedContext::new());
+    let Root(_owner, resource) = Root::new_with_shared_context(
+        || {
+            ArcResource::<Data, Failing>::new_with_encoding(|| async {
+                Data(1)
+            })
+        },
+        Some(shared_context.clone()),
+    );
+    run_until_stalled();
+    assert_eq!(resource.get_untracked(), AsyncState::Complete(Data(1)));
+
+    let chunks =
+        block_on(shared_context.pending_data().unwrap().collect::<Vec<_>>());
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(
+        chunks[1],
+        r#"__RESOLVED_RESOURCES["0"] = {error: "\"can't serialize this\""};__RESOURCE_RESOLVERS["0"]?.();"#
+    );
+}
+
+#[test]
+fn data_ids_do_not_depend_on_creation_order() {
+    // creates data under two sibling owners, in either order
+    fn ids(first_a: bool) -> Vec<String> {
+        let Root(_owner, ids) = Root::new_with_shared_context(
+            || {
+                let a = Owner::new();
+                let b = Owner::new();
+                let next_id = |owner: &Owner| {
+                    owner.with(|| {
+                        Owner::shared_context().unwrap().next_id().to_string()
+                    })
+                };
+                if first_a {
+                    vec![next_id(&a), next_id(&b)]
+                } else {
+                    let b = next_id(&b);
+                    vec![next_id(&a), b]
+                }
+            },
+            Some(Arc::new(SsrSharedContext::new())),
+        );
+        ids
+    }
+
+    assert_eq!(ids(true), ["0-0", "1-0"]);
+    assert_eq!(ids(false), ["0-0", "1-0"]);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "was given out more than once")]
+fn colliding_data_ids_are_reported() {
+    Root::new_with_shared_context(
+        || {
+            for _ in 0..2 {
+                Owner::new_at(vec![3])
+                    .with(|| Owner::shared_context().unwrap().next_id());
+            }
+        },
+        Some(Arc::new(SsrSharedContext::new())),
+    );
+}
+
+type Received = Result<String, SerializationError>;
+
+// stands in for the data that the server sent to a hydrating client
+#[derive(Debug, Default)]
+struct ServerData {
+    // data that arrived before the client started hydrating
+    resolved: Option<Received>,
+    // data that the server is still streaming
+    pending: Mutex<Option<oneshot::Receiver<Received>>>,
+    // the items of a stream that the server sent
+    streamed: Option<Vec<Received>>,
+    // data that arrived before the client started hydrating, for each ID,
+    // in place of `resolved`
+    by_id: Option<HashMap<String, Received>>,
+}
+
+impl ServerData {
+    fn resolved(data: Received) -> Arc<Self> {
+        Arc::new(Self {
+            resolved: Some(data),
+            ..Default::default()
+        })
+    }
+
+    fn pending() -> (Arc<Self>, oneshot::Sender<Received>) {
+        let (tx, rx) = oneshot::channel();
+        let this = Self {
+            pending: Mutex::new(Some(rx)),
+            ..Default::default()
+        };
+        (Arc::new(this), tx)
+    }
+
+    fn streamed(items: Vec<Received>) -> Arc<Self> {
+        Arc::new(Self {
+            streamed: Some(items),
+            ..Default::default()
+        })
+    }
+
+    fn by_id<'a>(
+        data: impl IntoIterator<Item = (&'a str, Received)>,
+    ) -> Arc<Self> {
+        let data = data
+            .into_iter()
+            .map(|(id, data)| (id.to_string(), data))
+            .collect();
+        Arc::new(Self {
+            by_id: Some(data),
+            ..Default::default()
+        })
+    }
+}
+
+impl SharedContext for ServerData {
+    fn next_id(&self) -> SerializedDataId {
+        SerializedDataId::next()
+    }
+
+    fn write_async(
+        &self,
+        _id: SerializedDataId,
+        _fut: PinnedFuture<Result<String, SerializationError>>,
+    ) {
+    }
+
+    fn write_stream(
+        &self,
+        _id: SerializedDataId,
+        _stream: PinnedStream<Result<String, SerializationError>>,
+    ) {
+    }
+
+    fn read_data(
+        &self,
+        id: &SerializedDataId,
+    ) -> Option<Result<String, SerializationError>> {
+        match &self.by_id {
+            Some(by_id) => by_id.get(&id.to_string()).cloned(),
+            None => self.resolved.clone(),
+        }
+    }
+
+    fn await_data(
+        &self,
+        id: &SerializedDataId,
+    ) -> Option<PinnedFuture<Result<String, SerializationError>>> {
+        if let Some(data) = self.read_data(id) {
+            return Some(Box::pin(async move { data }));
+        }
+        let rx = self.pending.lock().take()?;
+        Some(Box::pin(async move { rx.await.unwrap() }))
+    }
+
+    fn read_stream(
+        &self,
+        _id: &SerializedDataId,
+    ) -> Option<PinnedStream<Result<String, SerializationError>>> {
+        let items = self.streamed.clone()?;
+        Some(Box::pin(stream::iter(items)))
+    }
+
+    fn pending_data(&self) -> Option<PinnedStream<String>> {
+        None
+    }
+}
+
+#[test]
+fn hydrating_client_turns_serialization_errors_into_error_state() {
+    testing::install();
+
+    let error = SerializationError("can't serialize this".into());
+    let Root(_owner, resource) = Root::new_with_shared_context(
+        || {
+            ArcResource::<Data, Failing>::new_with_encoding(|| async {
+                Data(2)
+            })
+        },
+        Some(ServerData::resolved(Err(error.clone()))),
+    );
+    assert_eq!(
+        resource.get_untracked(),
+        AsyncState::Error(AsyncError::Serialization(error.clone()))
+    );
+    assert_eq!(
+        resource.with_untracked(|state| state.error().cloned()),
+        Some(AsyncError::Serialization(error))
+    );
+
+    // the error isn't replaced until the resource runs again
+    run_until_stalled();
+    assert!(resource.get_untracked().error().is_some());
+    resource.refetch();
+    run_until_stalled();
+    assert_eq!(resource.get_untracked(), AsyncState::Complete(Data(2)));
+}
+
+#[test]
+fn hydrating_client_deserializes_data() {
+    testing::install();
+
+    let Root(_owner, resource) = Root::new_with_shared_context(
+        || {
+            ArcResource::<Data, Failing>::new_with_encoding(|| async {
+                Data(0)
+            })
+        },
+        Some(ServerData::resolved(Ok("3".into()))),
+    );
+    assert_eq!(resource.get_untracked(), AsyncState::Complete(Data(3)));
+}
+
+// counts how many times the client actually fetched the data
+fn counting_resource(
+    shared_context: Arc<ServerData>,
+) -> (ArcResource<Data, Failing>, Arc<AtomicUsize>) {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let Root(_owner, resource) = Root::new_with_shared_context(
+        || {
+            let fetches = Arc::clone(&fetches);
+            ArcResource::new_with_encoding(move || {
+                let fetches = Arc::clone(&fetches);
+                async move { Data(fetches.fetch_add(1, Ordering::Relaxed) as i32) }
+            })
+        },
+        Some(shared_context),
+    );
+    (resource, fetches)
+}
+
+#[test]
+fn hydrating_client_waits_for_streamed_data() {
+    testing::install();
+
+    let (shared_context, tx) = ServerData::pending();
+    let (resource, fetches) = counting_resource(shared_context);
+    run_until_stalled();
+    assert_eq!(resource.get_untracked(), AsyncState::Loading);
+
+    tx.send(Ok("5".into())).unwrap();
+    run_until_stalled();
+    assert_eq!(resource.get_untracked(), AsyncState::Complete(Data(5)));
+    assert_eq!(fetches.load(Ordering::Relaxed), 0);
+
+    // only the first run uses the data from the server
+    resource.refetch();
+    run_until_stalled();
+    assert_eq!(resource.get_untracked(), AsyncState::Complete(Data(0)));
+    assert_eq!(fetches.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn hydrating_client_receives_streamed_errors() {
+    testing::install();
+
+    let (shared_context, tx) = ServerData::pending();
+    let (resource, fetches) = counting_resource(shared_context);
+    run_until_stalled();
+
+    let error = SerializationError("can't serialize this".into());
+    tx.send(Err(error.clone())).unwrap();
+    run_until_stalled();
+    assert_eq!(
+        resource.get_untracked(),
+        AsyncState::Error(AsyncError::Serialization(error))
+    );
+    assert_eq!(fetches.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn client_fetches_data_the_server_does_not_send() {
+    testing::install();
+
+    let (resource, fetches) = counting_resource(Default::default());
+    run_until_stalled();
+    assert_eq!(resource.get_untracked(), AsyncState::Complete(Data(0)));
+    assert_eq!(fetches.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn stream_items_are_sent_to_the_client_one_at_a_time() {
+    testing::install();
+
+    let shared_context = Arc::new(SsrSharedContext::new());
+    let Root(_owner, resource) = Root::new_with_shared_context(
+        || ArcStreamResource::<i32, _>::new(stream::iter([1, 2])),
+        Some(shared_context.clone()),
+    );
+    run_until_stalled();
+    assert_eq!(resource.get_untracked(), [1, 2]);
+
+    let chunks =
+        block_on(shared_context.pending_data().unwrap().collect::<Vec<_>>());
+    assert!(chunks[0].contains(r#"__RESOURCE_STREAMS={"0":[],};"#));
+    assert_eq!(
+        &chunks[1..],
+        [
+            r#"__RESOURCE_STREAMS["0"].push("1");__RESOURCE_RESOLVERS["0"]?.();"#,
+            r#"__RESOURCE_STREAMS["0"].push("2");__RESOURCE_RESOLVERS["0"]?.();"#,
+            r#"__RESOURCE_STREAMS["0"].push(null);__RESOURCE_RESOLVERS["0"]?.();"#,
+        ]
+    );
+}
+
+#[test]
+fn streamed_items_cannot_close_the_script_tag() {
+    testing::install();
+
+    let shared_context = Arc::new(SsrSharedContext::new());
+    let Root(_owner, _resource) = Root::new_with_shared_context(
+        || {
+            ArcStreamResource::<String, _>::new(stream::iter([
+                "</script><script>alert(1)</script>".to_string(),
+            ]))
+        },
+        Some(shared_context.clone()),
+    );
+    run_until_stalled();
+
+    let chunks =
+        block_on(shared_context.pending_data().unwrap().collect::<Vec<_>>());
+    assert!(chunks.iter().all(|chunk| !chunk.contains('<')));
+    assert_eq!(
+        chunks[1],
+        r#"__RESOURCE_STREAMS["0"].push("\u003c/script>\u003cscript>alert(1)\u003c/script>");__RESOURCE_RESOLVERS["0"]?.();"#
+    );
+}
+
+#[test]
+fn hydrating_client_replays_streamed_items_then_listens_locally() {
+    testing::install();
+
+    let shared_context = ServerData::streamed(vec![
+        Ok("1".into()),
+        Err(SerializationError("can't serialize this".into())),
+        Ok("3".into()),
+    ]);
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let Root(_owner, resource) = Root::new_with_shared_context(
+        || ArcStreamResource::<i32, _>::new(rx),
+        Some(shared_context),
+    );
+    run_until_stalled();
+    // items that could not be serialized are skipped
+    assert_eq!(resource.get_untracked(), [1, 3]);
+    assert_eq!(resource.ready().now_or_never(), Some(()));
+
+    // the client's own stream starts with the items the server already sent
+    for item in 1..=4 {
+        tx.unbounded_send(item).unwrap();
+    }
+    run_until_stalled();
+    assert_eq!(resource.get_untracked(), [1, 3, 4]);
+}
+
+#[test]
+fn stream_resources_are_ready_to_render_once_the_stream_ends() {
+    testing::install();
+
+    let shared_context = Arc::new(SsrSharedContext::new());
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let Root(_owner, resource) = Root::new_with_shared_context(
+        || ArcStreamResource::<i32, _>::new(rx),
+        Some(shared_context),
+    );
+    let mut ready = Box::pin(resource.ready());
+    tx.unbounded_send(1).unwrap();
+    run_until_stalled();
+    assert_eq!(resource.get_untracked(), [1]);
+    assert_eq!(ready.as_mut().now_or_never(), None);
+
+    tx.unbounded_send(2).unwrap();
+    drop(tx);
+    run_until_stalled();
+    assert_eq!(ready.now_or_never(), Some(()));
+    assert_eq!(resource.get_untracked(), [1, 2]);
+}
+
+#[test]
+fn serialized_signals_send_their_final_value() {
+    testing::install();
+
+    let shared_context = Arc::new(SsrSharedContext::new());
+    Root::new_with_shared_context(
+        || {
+            let count = ArcRwSignal::new_serialized(0);
+            // e.g., derived from the request while rendering
+            count.set(5);
+        },
+        Some(shared_context.clone()),
+    );
+
+    let chunks =
+        block_on(shared_context.pending_data().unwrap().collect::<Vec<_>>());
+    assert_eq!(
+        chunks[1],
+        r#"__RESOLVED_RESOURCES["0"] = "5";__RESOURCE_RESOLVERS["0"]?.();"#
+    );
+}
+
+#[test]
+fn hydrating_client_restores_serialized_state() {
+    testing::install();
+
+    let Root(_owner, (count, store)) = Root::new_with_shared_context(
+        || {
+            (
+                ArcRwSignal::new_serialized(0),
+                ArcStore::new_serialized(vec![0]),
+            )
+        },
+        Some(ServerData::by_id([
+            ("0", Ok("5".into())),
+            ("1", Ok("[6, 7]".into())),
+        ])),
+    );
+    assert_eq!(count.get_untracked(), 5);
+    assert_eq!(store.with_untracked(|data| data.clone()), [6, 7]);
+
+    // values whose ID the server did not send keep their initial value
+    let Root(_owner, (count, other)) = Root::new_with_shared_context(
+        || (RwSignal::new_serialized(0), RwSignal::new_serialized(1)),
+        Some(ServerData::by_id([("1", Ok("8".into()))])),
+    );
+    assert_eq!(count.get_untracked(), 0);
+    assert_eq!(other.get_untracked(), 8);
+}
+
+#[test]
+fn hydrating_client_waits_for_streamed_state() {
+    testing::install();
+
+    let (shared_context, tx) = ServerData::pending();
+    let Root(_owner, count) = Root::new_with_shared_context(
+        || ArcRwSignal::new_serialized(0),
+        Some(shared_context),
+    );
+    run_until_stalled();
+    assert_eq!(count.get_untracked(), 0);
+
+    tx.send(Ok("5".into())).unwrap();
+    run_until_stalled();
+    assert_eq!(count.get_untracked(), 5);
+}
@@ -0,0 +1,105 @@
+use futures::channel::oneshot;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tachy_reaccy::{
+    async_signal::{ArcAsyncDerived, AsyncState},
+    prelude::*,
+    testing::{self, run_count, run_until_stalled, tick},
+};
+
+#[test]
+fn effects_only_run_when_ticked() {
+    testing::install();
+
+    let a = RwSignal::new(0);
+    let b = RwSignal::new(0);
+    let effect = Effect::new(move |_| a.get() + b.get());
+
+    assert_eq!(run_count(&effect), 0);
+    assert_eq!(tick(), 1);
+    assert_eq!(run_count(&effect), 1);
+
+    a.set(1);
+    b.set(1);
+    assert_eq!(run_count(&effect), 1);
+    run_until_stalled();
+    assert_eq!(run_count(&effect), 2);
+
+    // nothing changed, so there's nothing to run
+    assert_eq!(run_until_stalled(), 0);
+    assert_eq!(run_count(&effect), 2);
+}
+
+#[test]
+fn memos_rerun_once_per_change() {
+    testing::install();
+
+    let a = RwSignal::new(1);
+    let b = RwSignal::new(2);
+    let sum = ArcMemo::new(move |_| a.get() + b.get());
+    let is_even = ArcMemo::new({
+        let sum = sum.clone();
+        move |_| sum.get() % 2 == 0
+    });
+
+    // memos are lazy, so they only run when they're read
+    assert_eq!(run_count(&sum), 0);
+    assert!(!is_even.get());
+    assert!(!is_even.get());
+    assert_eq!(run_count(&sum), 1);
+    assert_eq!(run_count(&is_even), 1);
+
+    a.set(3);
+    b.set(4);
+    assert_eq!(run_count(&sum), 1);
+    assert!(!is_even.get());
+    assert_eq!(run_count(&sum), 2);
+    assert_eq!(run_count(&is_even), 2);
+
+    testing::reset_run_counts();
+    assert_eq!(sum.get(), 7);
+    assert_eq!(run_count(&sum), 0);
+}
+
+#[test]
+fn effects_that_set_signals_run_on_later_ticks() {
+    testing::install();
+
+    let a = RwSignal::new(0);
+    let b = RwSignal::new(0);
+    let read = Effect::new(move |_| b.get());
+    let copy = Effect::new(move |_| b.set(a.get()));
+
+    assert_eq!(tick(), 2);
+    // `read` was woken by `copy` setting `b` during the first tick
+    assert_eq!(tick(), 1);
+    assert_eq!(tick(), 0);
+    assert_eq!(run_count(&copy), 1);
+    assert_eq!(run_count(&read), 2);
+}
+
+#[test]
+fn async_derived_resolves_when_ticked() {
+    testing::install();
+
+    let (tx, rx) = oneshot::channel::<i32>();
+    let rx = Arc::new(Mutex::new(Some(rx)));
+    let derived = ArcAsyncDerived::new_unsync(move || {
+        let rx = rx.lock().take();
+        async move {
+            match rx {
+                Some(rx) => rx.await.unwrap_or_default(),
+                None => 0,
+            }
+        }
+    });
+
+    run_until_stalled();
+    assert_eq!(run_count(&derived), 1);
+    assert_eq!(derived.get_untracked(), AsyncState::Loading);
+
+    tx.send(42).unwrap();
+    run_until_stalled();
+    assert_eq!(derived.get_untracked(), AsyncState::Complete(42));
+    assert_eq!(testing::pending_tasks(), 1);
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use tachy_reaccy::serialization::{
+    Compressed, Postcard, SerdeJson, SerializableData,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Story {
+    id: u32,
+    points: u32,
+    comments_count: u32,
+    title: String,
+}
+
+// a large hydration payload, like a page of stories
+fn stories() -> Vec<Story> {
+    (0..200)
+        .map(|id| Story {
+            id,
+            points: id * 7 % 500,
+            comments_count: id * 3 % 120,
+            title: format!("Story {id}"),
+        })
+        .collect()
+}
+
+#[test]
+fn binary_and_compressed_encodings_round_trip() {
+    let data = stories();
+
+    let postcard = SerializableData::<Postcard>::ser(&data).unwrap();
+    let de: Vec<Story> = SerializableData::<Postcard>::de(&postcard).unwrap();
+    assert_eq!(de, data);
+
+    let compressed =
+        SerializableData::<Compressed<SerdeJson>>::ser(&data).unwrap();
+    let de: Vec<Story> =
+        SerializableData::<Compressed<SerdeJson>>::de(&compressed).unwrap();
+    assert_eq!(de, data);
+
+    assert!(
+        <Vec<Story> as SerializableData<Postcard>>::de("not base64!").is_err()
+    );
+    assert!(<Vec<Story> as SerializableData<Compressed<SerdeJson>>>::de(
+        "bm90IGRlZmxhdGVk"
+    )
+    .is_err());
+}
+
+#[test]
+fn compact_encodings_are_smaller_than_json() {
+    let data = stories();
+    let json = SerializableData::<SerdeJson>::ser(&data).unwrap().len();
+    let postcard = SerializableData::<Postcard>::ser(&data).unwrap().len();
+    let compressed = SerializableData::<Compressed<SerdeJson>>::ser(&data)
+        .unwrap()
+        .len();
+    let compressed_postcard =
+        SerializableData::<Compressed<Postcard>>::ser(&data)
+            .unwrap()
+            .len();
+
+    assert!(postcard * 2 < json);
+    assert!(compressed * 3 < json);
+    assert!(compressed_postcard < postcard);
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr, sync::OnceLock};
+use tachy_reaccy::{
+    serialization::{SerdeJson, Str},
+    server_fn::{
+        self, set_client, Client, Request, Response, ServerFn, ServerFnError,
+        ServerFnFuture, ServerFnRegistry,
+    },
+    PinnedFuture,
+};
+
+// the arguments of `double(n: i32)`, as `#[server(encoding = "Str")]` defines them
+#[derive(Debug, Clone)]
+struct Double {
+    n: i32,
+}
+
+impl fmt::Display for Double {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.n, f)
+    }
+}
+
+impl FromStr for Double {
+    type Err = <i32 as FromStr>::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self { n: s.parse()? })
+    }
+}
+
+impl ServerFn for Double {
+    const PATH: &'static str = "/api/double";
+    type Encoding = Str;
+    type Output = i32;
+
+    fn run_body(self) -> ServerFnFuture<i32> {
+        Box::pin(async move { Ok(self.n * 2) })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Story {
+    id: usize,
+    title: String,
+}
+
+// the arguments of `get_story(id: usize)`, as `#[server]` defines them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GetStory {
+    id: usize,
+}
+
+impl ServerFn for GetStory {
+    const PATH: &'static str = "/api/get_story";
+    type Encoding = SerdeJson;
+    type Output = Story;
+
+    fn run_body(self) -> ServerFnFuture<Story> {
+        Box::pin(async move {
+            if self.id == 0 {
+                Err(ServerFnError::new("there is no story 0"))
+            } else {
+                Ok(Story {
+                    id: self.id,
+                    title: format!("Story {}", self.id),
+                })
+            }
+        })
+    }
+}
+
+// a server function that the server does not register
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Unregistered;
+
+impl ServerFn for Unregistered {
+    const PATH: &'static str = "/api/unregistered";
+    type Encoding = SerdeJson;
+    type Output = ();
+
+    fn run_body(self) -> ServerFnFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+fn registry() -> &'static ServerFnRegistry {
+    static REGISTRY: OnceLock<ServerFnRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        ServerFnRegistry::new()
+            .register::<Double>()
+            .register::<GetStory>()
+    })
+}
+
+// stands in for an HTTP client and server, by handing requests to the
+// registry in the same process
+struct InProcess;
+
+impl Client for InProcess {
+    fn send(
+        &self,
+        req: Request,
+    ) -> PinnedFuture<Result<Response, ServerFnError>> {
+        let res = tokio::spawn(registry().handle(req));
+        Box::pin(async move {
+            res.await.map_err(|e| ServerFnError::Request(e.to_string()))
+        })
+    }
+}
+
+#[tokio::test]
+async fn server_fns_are_called_through_the_client() {
+    _ = set_client(InProcess);
+
+    assert_eq!(server_fn::call(Double { n: 21 }).await, Ok(42));
+    assert_eq!(
+        server_fn::call(GetStory { id: 1 }).await,
+        Ok(Story {
+            id: 1,
+            title: "Story 1".to_string()
+        })
+    );
+}
+
+#[tokio::test]
+async fn server_fn_errors_are_sent_to_the_client() {
+    _ = set_client(InProcess);
+
+    assert_eq!(
+        server_fn::call(GetStory { id: 0 }).await,
+        Err(ServerFnError::ServerError(
+            "there is no story 0".to_string()
+        ))
+    );
+    assert_eq!(
+        server_fn::call(Unregistered).await,
+        Err(ServerFnError::NotFound("/api/unregistered".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn registry_rejects_invalid_arguments() {
+    let mut paths = registry().paths().collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(paths, ["/api/double", "/api/get_story"]);
+
+    let res = registry()
+        .handle(Request {
+            path: "/api/double".to_string(),
+            body: "twenty-one".to_string(),
+        })
+        .await;
+    assert_eq!(res.status, 400);
+    assert!(matches!(
+        serde_json::from_str(&res.body),
+        Ok(ServerFnError::Deserialization(_))
+    ));
+}
@@ -0,0 +1,90 @@
+use futures::{channel::mpsc, FutureExt, StreamExt};
+use tachy_reaccy::{
+    prelude::*,
+    signal::ArcRwSignal,
+    testing::{self, run_until_stalled},
+    Owner,
+};
+
+#[test]
+fn streams_yield_each_new_value() {
+    let count = ArcRwSignal::new(0);
+    let mut values = count.read_only().to_stream();
+
+    // the current value is not yielded, only changes to it
+    assert_eq!(values.next().now_or_never(), None);
+
+    count.set(1);
+    assert_eq!(values.next().now_or_never(), Some(Some(1)));
+    assert_eq!(values.next().now_or_never(), None);
+
+    // changes made between polls are yielded once, with the latest value
+    count.set(2);
+    count.set(3);
+    assert_eq!(values.next().now_or_never(), Some(Some(3)));
+    assert_eq!(values.next().now_or_never(), None);
+}
+
+#[test]
+fn streams_end_with_their_owner() {
+    let owner = Owner::new();
+    let count = RwSignal::new(0);
+    let mut values = owner.with(|| count.to_stream());
+
+    count.set(1);
+    owner.dispose();
+    assert_eq!(values.next().now_or_never(), Some(None));
+}
+
+#[test]
+fn signals_hold_the_latest_item_of_a_stream() {
+    testing::install();
+
+    let (tx, rx) = mpsc::unbounded();
+    let signal = ArcRwSignal::from_stream(0, rx);
+    let effect = {
+        let signal = signal.clone();
+        Effect::new(move |_| signal.get())
+    };
+    run_until_stalled();
+    assert_eq!(signal.get_untracked(), 0);
+
+    tx.unbounded_send(1).unwrap();
+    tx.unbounded_send(2).unwrap();
+    run_until_stalled();
+    assert_eq!(signal.get_untracked(), 2);
+    assert!(testing::run_count(&effect) > 1);
+
+    // the stream is dropped once it ends
+    drop(tx);
+    run_until_stalled();
+    assert_eq!(signal.get_untracked(), 2);
+}
+
+#[test]
+fn streams_stop_updating_signals_when_the_owner_is_disposed() {
+    testing::install();
+
+    let owner = Owner::new();
+    let (tx, rx) = mpsc::unbounded();
+    let signal = owner.with(|| ArcRwSignal::from_stream(0, rx));
+
+    tx.unbounded_send(1).unwrap();
+    run_until_stalled();
+    assert_eq!(signal.get_untracked(), 1);
+
+    owner.dispose();
+    run_until_stalled();
+    assert!(tx.unbounded_send(2).is_err());
+    assert_eq!(signal.get_untracked(), 1);
+}
+
+#[test]
+fn non_send_streams_can_be_followed() {
+    testing::install();
+
+    let items = futures::stream::iter([1, 2, 3]).map(std::rc::Rc::new);
+    let last = ArcRwSignal::from_stream_local(std::rc::Rc::new(0), items);
+    run_until_stalled();
+    assert_eq!(*last.get_untracked(), 3);
+}
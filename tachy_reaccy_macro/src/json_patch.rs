@@ -0,0 +1,251 @@
+use crate::patch::{member, PatchModel, PatchTy};
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::{quote, ToTokens};
+use syn::{
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    token, Attribute, Expr, Fields, LitStr, Member, Result, Token, Type,
+};
+
+/// `#[derive(JsonPatch)]` accepts the same types as `#[derive(Patch)]`.
+pub struct JsonPatchModel(PatchModel);
+
+impl Parse for JsonPatchModel {
+    fn parse(input: ParseStream) -> Result<Self> {
+        PatchModel::parse(input).map(Self)
+    }
+}
+
+/// A field that can be addressed by a store path segment.
+struct PathField<'a> {
+    /// The pattern that matches the struct or variant that holds the field.
+    pattern: TokenStream,
+    member: Member,
+    ty: &'a Type,
+    idx: usize,
+    /// The reference tokens of the JSON pointer to the field, within the
+    /// value serialized by serde.
+    tokens: Vec<String>,
+}
+
+impl ToTokens for JsonPatchModel {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let library_path = quote! { ::tachys::tachy_reaccy::store };
+        let PatchModel {
+            attrs,
+            name,
+            generics,
+            ty,
+        } = &self.0;
+        let (impl_generics, ty_generics, where_clause) =
+            generics.split_for_impl();
+        let predicates = where_clause.map(|w| &w.predicates);
+
+        // pointers follow serde's default, externally tagged representation
+        serde_attrs(
+            attrs,
+            &[
+                "rename_all",
+                "rename_all_fields",
+                "tag",
+                "content",
+                "untagged",
+                "transparent",
+                "from",
+                "try_from",
+                "into",
+                "remote",
+            ],
+        );
+
+        // each field has the same path as the accessor that `#[derive(Store)]`
+        // generates for it
+        let fields = match ty {
+            PatchTy::Struct {
+                members,
+                tys,
+                attrs,
+            } => members
+                .iter()
+                .zip(tys)
+                .zip(attrs)
+                .enumerate()
+                .map(|(idx, ((member, ty), attrs))| PathField {
+                    pattern: quote! { Self },
+                    member: member.clone(),
+                    ty,
+                    idx,
+                    tokens: match member {
+                        Member::Named(ident) => {
+                            vec![field_name(attrs, ident.unraw().to_string())]
+                        }
+                        // newtype structs are serialized as the value they
+                        // hold
+                        Member::Unnamed(_) if members.len() == 1 => vec![],
+                        Member::Unnamed(idx) => vec![idx.index.to_string()],
+                    },
+                })
+                .collect::<Vec<_>>(),
+            PatchTy::Enum { variants } => variants
+                .iter()
+                .flat_map(|variant| {
+                    let variant_name = &variant.ident;
+                    let tag = serde_attrs(
+                        &variant.attrs,
+                        &["rename_all", "untagged"],
+                    )
+                    .unwrap_or_else(|| variant_name.unraw().to_string());
+                    let newtype = matches!(
+                        &variant.fields,
+                        Fields::Unnamed(fields) if fields.unnamed.len() == 1
+                    );
+                    variant.fields.iter().enumerate().map(
+                        move |(field_idx, field)| PathField {
+                            pattern: quote! { Self::#variant_name },
+                            member: member(field_idx, &field.ident),
+                            ty: &field.ty,
+                            idx: 0,
+                            tokens: match &field.ident {
+                                Some(ident) => vec![
+                                    tag.clone(),
+                                    field_name(
+                                        &field.attrs,
+                                        ident.unraw().to_string(),
+                                    ),
+                                ],
+                                None if newtype => vec![tag.clone()],
+                                None => {
+                                    vec![tag.clone(), field_idx.to_string()]
+                                }
+                            },
+                        },
+                    )
+                })
+                .enumerate()
+                .map(|(idx, field)| PathField { idx, ..field })
+                .collect(),
+        };
+
+        let field_tys = fields.iter().map(|field| field.ty);
+        let json_at = fields.iter().map(|field| {
+            let PathField {
+                pattern,
+                member,
+                idx,
+                tokens,
+                ..
+            } = field;
+            quote! {
+                (#pattern { #member: field, .. }, Some((segment, rest)))
+                    if usize::from(*segment) == #idx =>
+                {
+                    #(pointer.push(#tokens.to_string());)*
+                    #library_path::JsonPatch::json_at(field, rest, pointer)
+                }
+            }
+        });
+        let apply_json = fields.iter().map(|field| {
+            let PathField {
+                pattern,
+                member,
+                idx,
+                tokens,
+                ..
+            } = field;
+            quote! {
+                if let (#pattern { #member: field, .. }, Some(rest)) = (
+                    &mut *self,
+                    #library_path::strip_pointer(pointer, &[#(#tokens),*]),
+                ) {
+                    found.push(#idx);
+                    return #library_path::JsonPatch::apply_json(
+                        field, rest, op, found,
+                    );
+                }
+            }
+        });
+
+        tokens.extend(quote! {
+            impl #impl_generics #library_path::JsonPatch for #name #ty_generics
+            where
+                Self: ::tachys::serde::Serialize
+                    + ::tachys::serde::de::DeserializeOwned,
+                #(#field_tys: #library_path::JsonPatch,)*
+                #predicates
+            {
+                fn json_at(
+                    &self,
+                    path: &[#library_path::StorePathSegment],
+                    pointer: &mut Vec<String>,
+                ) -> Result<::tachys::serde_json::Value, ::tachys::serde_json::Error> {
+                    match (self, path.split_first()) {
+                        #(#json_at)*
+                        _ => ::tachys::serde_json::to_value(self),
+                    }
+                }
+
+                fn apply_json(
+                    &mut self,
+                    pointer: &[String],
+                    op: #library_path::StoreOp,
+                    found: &mut #library_path::StorePath,
+                ) -> Result<(), #library_path::JsonPatchError> {
+                    if pointer.is_empty() {
+                        return #library_path::apply_json_whole(self, pointer, op);
+                    }
+                    #(#apply_json)*
+                    Err(#library_path::JsonPatchError::PathNotFound)
+                }
+            }
+        });
+    }
+}
+
+/// The name serde gives to a field.
+fn field_name(attrs: &[Attribute], name: String) -> String {
+    serde_attrs(attrs, &["flatten"]).unwrap_or(name)
+}
+
+/// Returns the name given by `#[serde(rename)]`, if any, and aborts on any of
+/// the `unsupported` attributes, which would move fields elsewhere in the
+/// serialized value.
+fn serde_attrs(attrs: &[Attribute], unsupported: &[&str]) -> Option<String> {
+    let mut rename = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        let res = attr.parse_nested_meta(|meta| {
+            if let Some(key) =
+                unsupported.iter().find(|key| meta.path.is_ident(key))
+            {
+                abort!(
+                    meta.path,
+                    "`JsonPatch` does not support `#[serde({})]`",
+                    key
+                );
+            }
+            if meta.path.is_ident("rename") && meta.input.peek(Token![=]) {
+                rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("rename") {
+                meta.parse_nested_meta(|inner| {
+                    let value = inner.value()?.parse::<LitStr>()?;
+                    if inner.path.is_ident("serialize") {
+                        rename = Some(value.value());
+                    }
+                    Ok(())
+                })?;
+            } else if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            } else if meta.input.peek(token::Paren) {
+                let content;
+                parenthesized!(content in meta.input);
+                content.parse::<TokenStream>()?;
+            }
+            Ok(())
+        });
+        if let Err(e) = res {
+            abort!(attr, e);
+        }
+    }
+    rename
+}
@@ -1,14 +1,28 @@
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream};
 use proc_macro_error::{abort, abort_call_site, proc_macro_error};
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
     parse::{Parse, ParseStream, Parser},
     punctuated::Punctuated,
     token::Comma,
-    Data, Field, Fields, Generics, Ident, Meta, MetaList, Result, Visibility,
-    WhereClause, Type, Token, Index,
+    Field, Fields, GenericParam, Generics, Ident, Index, Member, Meta, Result,
+    Token, Type, Variant, Visibility,
 };
 
+mod json_patch;
+mod patch;
+
+/// Derives an extension trait, `{Name}StoreFields`, with an accessor for each
+/// field of a struct, which can be used on any store field that holds the
+/// struct, including the `Subfield` returned by another accessor. Deriving
+/// `Store` for the type of a field lets its own fields be accessed in turn.
+///
+/// For an enum, the trait has an `is_{variant}` method for each variant, and
+/// an accessor for each field of each variant, named `{variant}_{field}` (or
+/// `{variant}_{index}` for tuple variants), which returns [`None`] unless the
+/// enum holds that variant. Both track the enum itself, so they run again when
+/// it changes to a different variant, and a `VariantField` that is kept after
+/// that happens returns [`None`] from `try_get` and the other `try_*` methods.
 #[proc_macro_error]
 #[proc_macro_derive(Store, attributes(store))]
 pub fn derive_store(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -17,39 +31,87 @@ pub fn derive_store(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         .into()
 }
 
+/// Derives `PatchField`, which lets a store holding the type be updated with
+/// `patch`, notifying only the fields whose values changed.
+///
+/// Each field is compared at the same path as the accessor derived by
+/// `#[derive(Store)]`, so the type of every field must implement `PatchField`
+/// itself, either by deriving `Patch` or, for primitives, strings and the
+/// standard collections, through the implementations in `tachy_reaccy`. An
+/// enum that changes to a different variant is replaced as a whole.
+#[proc_macro_error]
+#[proc_macro_derive(Patch)]
+pub fn derive_patch(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    syn::parse_macro_input!(input as patch::PatchModel)
+        .into_token_stream()
+        .into()
+}
+
+/// Derives `JsonPatch`, which lets the writes to a store holding the type be
+/// observed as JSON Patch operations with `on_change`, and replayed with
+/// `apply_patch`.
+///
+/// The path of the accessor derived by `#[derive(Store)]` for each field is
+/// reported as the JSON pointer to the field in the value serialized by serde,
+/// using its Rust name or its `#[serde(rename)]`. The type must also implement
+/// `Serialize` and `Deserialize` with serde's default enum representation,
+/// and the type of every field must implement `JsonPatch`.
+#[proc_macro_error]
+#[proc_macro_derive(JsonPatch)]
+pub fn derive_json_patch(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    syn::parse_macro_input!(input as json_patch::JsonPatchModel)
+        .into_token_stream()
+        .into()
+}
+
 struct Model {
     pub vis: Visibility,
     pub struct_name: Ident,
     pub generics: Generics,
-    pub fields: Vec<Field>,
+    pub ty: ModelTy,
+}
+
+enum ModelTy {
+    Struct { fields: Vec<Field> },
+    Enum { variants: Vec<Variant> },
 }
 
 impl Parse for Model {
     fn parse(input: ParseStream) -> Result<Self> {
         let input = syn::DeriveInput::parse(input)?;
 
-        let syn::Data::Struct(s) = input.data else {
-            abort_call_site!("only structs can be used with `Store`");
-        };
-
-        let (fields) = match s.fields {
-            syn::Fields::Unit => {
-                abort!(s.semi_token, "unit structs are not supported");
+        let ty = match input.data {
+            syn::Data::Struct(s) => {
+                let fields = match s.fields {
+                    Fields::Unit => {
+                        abort!(s.semi_token, "unit structs are not supported");
+                    }
+                    Fields::Named(fields) => {
+                        fields.named.into_iter().collect::<Vec<_>>()
+                    }
+                    Fields::Unnamed(fields) => {
+                        fields.unnamed.into_iter().collect::<Vec<_>>()
+                    }
+                };
+                ModelTy::Struct { fields }
             }
-            syn::Fields::Named(fields) => {
-                fields.named.into_iter().collect::<Vec<_>>()
+            syn::Data::Enum(e) => ModelTy::Enum {
+                variants: e.variants.into_iter().collect(),
+            },
+            syn::Data::Union(_) => {
+                abort_call_site!(
+                    "only structs and enums can be used with `Store`"
+                );
             }
-            syn::Fields::Unnamed(fields) => fields
-                    .unnamed
-                    .into_iter()
-                    .collect::<Vec<_>>(),
         };
 
         Ok(Self {
             vis: input.vis,
             struct_name: input.ident,
             generics: input.generics,
-            fields,
+            ty,
         })
     }
 }
@@ -74,8 +136,49 @@ impl Parse for SubfieldMode {
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn field_to_tokens(idx: usize, include_body: bool, modes: Option<&[SubfieldMode]>, library_path: &proc_macro2::TokenStream, orig_ident: Option<&Ident>, generics: &Generics, any_store_field: &Ident, struct_name: &Ident, ty: &Type) -> proc_macro2::TokenStream {
+fn subfield_modes(field: &Field) -> Option<Vec<SubfieldMode>> {
+    field
+        .attrs
+        .iter()
+        .find_map(|attr| {
+            attr.meta.path().is_ident("store").then(|| match &attr.meta {
+                Meta::List(list) => {
+                    match Punctuated::<SubfieldMode, Comma>::parse_terminated
+                        .parse2(list.tokens.clone())
+                    {
+                        Ok(modes) => Some(modes.into_iter().collect()),
+                        Err(e) => abort!(list, e),
+                    }
+                }
+                _ => None,
+            })
+        })
+        .flatten()
+}
+
+/// The names shared by the accessors of all the fields of a type.
+struct Context<'a> {
+    library_path: TokenStream,
+    any_store_field: Ident,
+    struct_name: &'a Ident,
+    /// The type the accessors are defined for, e.g. `State<T>`.
+    struct_ty: TokenStream,
+}
+
+fn field_to_tokens(
+    idx: usize,
+    include_body: bool,
+    modes: Option<&[SubfieldMode]>,
+    orig_ident: Option<&Ident>,
+    cx: &Context,
+    ty: &Type,
+) -> TokenStream {
+    let Context {
+        library_path,
+        any_store_field,
+        struct_ty,
+        ..
+    } = cx;
     let ident = if orig_ident.is_none() {
         let idx = Ident::new(&format!("field{idx}"), Span::call_site());
         quote! { #idx }
@@ -93,9 +196,9 @@ fn field_to_tokens(idx: usize, include_body: bool, modes: Option<&[SubfieldMode]
         if modes.len() == 1 {
             let mode = &modes[0];
             // Can replace with a match if additional modes added
-            let SubfieldMode::Keyed(keyed_by, key_ty) = mode;
+            let SubfieldMode::Keyed(_keyed_by, key_ty) = mode;
             let signature = quote! {
-                fn #ident(self) ->  #library_path::KeyedField<#any_store_field, #struct_name #generics, #ty, #key_ty>
+                fn #ident(self) -> #library_path::KeyedField<#any_store_field, #struct_ty, #ty, #key_ty>
             };
             return if include_body {
                 quote! {
@@ -105,16 +208,24 @@ fn field_to_tokens(idx: usize, include_body: bool, modes: Option<&[SubfieldMode]
                 }
             } else {
                 quote! { #signature; }
-            }
+            };
         } else {
-            abort!(orig_ident.map(|ident| ident.span()).unwrap_or_else(Span::call_site), "multiple modes not currently supported");
+            abort!(
+                orig_ident
+                    .map(|ident| ident.span())
+                    .unwrap_or_else(Span::call_site),
+                "multiple modes not currently supported"
+            );
         }
     }
 
     // default subfield
+    let signature = quote! {
+        fn #ident(self) -> #library_path::Subfield<#any_store_field, #struct_ty, #ty>
+    };
     if include_body {
         quote! {
-            fn #ident(self) ->  #library_path::Subfield<#any_store_field, #struct_name #generics, #ty> {
+            #signature {
                 #library_path::Subfield::new(
                     self,
                     #idx.into(),
@@ -124,84 +235,214 @@ fn field_to_tokens(idx: usize, include_body: bool, modes: Option<&[SubfieldMode]
             }
         }
     } else {
+        quote! { #signature; }
+    }
+}
+
+// e.g. `LoadedPage` -> `loaded_page`
+fn to_snake_case(ident: &Ident) -> String {
+    let mut snake = String::new();
+    for (idx, ch) in ident.to_string().chars().enumerate() {
+        if ch.is_uppercase() {
+            if idx > 0 {
+                snake.push('_');
+            }
+            snake.extend(ch.to_lowercase());
+        } else {
+            snake.push(ch);
+        }
+    }
+    snake
+}
+
+// accessors for an enum: a check for each variant, and an optional accessor
+// for each field of each variant
+fn variant_to_tokens(
+    variant: &Variant,
+    next_idx: &mut usize,
+    include_body: bool,
+    cx: &Context,
+) -> TokenStream {
+    let Context {
+        library_path,
+        any_store_field,
+        struct_name,
+        struct_ty,
+    } = cx;
+    let variant_name = &variant.ident;
+    let snake = to_snake_case(variant_name);
+    let is_variant = format_ident!("is_{snake}");
+
+    let mut tokens = if include_body {
         quote! {
-            fn #ident(self) ->  #library_path::Subfield<#any_store_field, #struct_name #generics, #ty>;
+            fn #is_variant(&self) -> bool {
+                <Self as #library_path::StoreField<#struct_ty>>::track_field(self);
+                <Self as #library_path::StoreField<#struct_ty>>::with_field_untracked(self, |prev| {
+                    matches!(prev, #struct_name::#variant_name { .. })
+                })
+            }
+        }
+    } else {
+        quote! { fn #is_variant(&self) -> bool; }
+    };
+
+    for (field_idx, field) in variant.fields.iter().enumerate() {
+        if field.attrs.iter().any(|attr| attr.path().is_ident("store")) {
+            abort!(
+                field,
+                "`#[store]` attributes are not supported on the fields of \
+                 enum variants"
+            );
+        }
+        let (ident, member) = match &field.ident {
+            Some(ident) => (
+                format_ident!("{snake}_{ident}"),
+                Member::Named(ident.clone()),
+            ),
+            None => (
+                format_ident!("{snake}_{field_idx}"),
+                Member::Unnamed(Index::from(field_idx)),
+            ),
+        };
+        let ty = &field.ty;
+        // each field of each variant has its own path segment
+        let idx = *next_idx;
+        *next_idx += 1;
+
+        let signature = quote! {
+            fn #ident(self) -> Option<#library_path::VariantField<#any_store_field, #struct_ty, #ty>>
+        };
+        if include_body {
+            tokens.extend(quote! {
+                #signature {
+                    self.#is_variant().then(|| {
+                        #library_path::VariantField::new(
+                            self,
+                            #idx.into(),
+                            |prev| match prev {
+                                #struct_name::#variant_name { #member: field, .. } => Some(field),
+                                _ => None,
+                            },
+                            |prev| match prev {
+                                #struct_name::#variant_name { #member: field, .. } => Some(field),
+                                _ => None,
+                            },
+                        )
+                    })
+                }
+            });
+        } else {
+            tokens.extend(quote! { #signature; });
         }
     }
+    tokens
 }
 
 impl ToTokens for Model {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
         let library_path = quote! { ::tachys::tachy_reaccy::store };
         let Model {
             vis,
             struct_name,
             generics,
-            fields,
+            ty,
         } = &self;
         let any_store_field = Ident::new("AnyStoreField", Span::call_site());
         let trait_name = Ident::new(
             &format!("{struct_name}StoreFields"),
             struct_name.span(),
         );
-        let generics_with_orig = {
-            let params = &generics.params;
-            quote! { <#any_store_field, #params> }
+
+        // the trait is generic over the generics of the type, as well as the
+        // store field it is implemented for
+        let params = &generics.params;
+        let param_names = params
+            .iter()
+            .map(|param| match param {
+                GenericParam::Lifetime(param) => {
+                    param.lifetime.to_token_stream()
+                }
+                GenericParam::Type(param) => param.ident.to_token_stream(),
+                GenericParam::Const(param) => param.ident.to_token_stream(),
+            })
+            .collect::<Vec<_>>();
+        let (_, ty_generics, where_clause) = generics.split_for_impl();
+        let struct_ty = quote! { #struct_name #ty_generics };
+        let predicates = where_clause.map(|w| &w.predicates);
+        let where_with_orig = quote! {
+            where
+                #any_store_field: #library_path::StoreField<#struct_ty>,
+                #predicates
         };
-        let where_with_orig = {
-            generics
-                .where_clause
-                .as_ref()
-                .map(|w| {
-                    let WhereClause {
-                        where_token,
-                        predicates,
-                    } = &w;
-                    quote! {
-                        #where_token
-                            #any_store_field: #library_path::StoreField<#struct_name #generics>,
-                            #predicates
-                    }
-                })
-                .unwrap_or_else(|| quote! { where #any_store_field: #library_path::StoreField<#struct_name #generics> })
+
+        let cx = Context {
+            library_path: library_path.clone(),
+            any_store_field: any_store_field.clone(),
+            struct_name,
+            struct_ty,
         };
 
-        // define an extension trait that matches this struct
-        let all_field_data = fields.iter().enumerate().map(|(idx, field)| {
-            let Field { ident, ty, attrs, .. } = &field;
-            let modes = attrs.iter().find_map(|attr| {
-                attr.meta.path().is_ident("store").then(|| {
-                    match &attr.meta {
-                        Meta::List(list) => {
-                            match Punctuated::<SubfieldMode, Comma>::parse_terminated.parse2(list.tokens.clone()) {
-                                Ok(modes) => Some(modes.iter().cloned().collect::<Vec<_>>()),
-                                Err(e) => abort!(list, e)
-                            }
-                        },
-                        _ => None
-                    }
+        let (trait_fields, read_fields): (Vec<_>, Vec<_>) = match ty {
+            ModelTy::Struct { fields } => fields
+                .iter()
+                .enumerate()
+                .map(|(idx, field)| {
+                    let modes = subfield_modes(field);
+                    let ident = field.ident.as_ref();
+                    (
+                        field_to_tokens(
+                            idx,
+                            false,
+                            modes.as_deref(),
+                            ident,
+                            &cx,
+                            &field.ty,
+                        ),
+                        field_to_tokens(
+                            idx,
+                            true,
+                            modes.as_deref(),
+                            ident,
+                            &cx,
+                            &field.ty,
+                        ),
+                    )
                 })
-            }).flatten();
-
-            (
-                field_to_tokens(idx, false, modes.as_deref(), &library_path, ident.as_ref(), generics, &any_store_field, struct_name, ty),
-                field_to_tokens(idx, true, modes.as_deref(), &library_path, ident.as_ref(), generics, &any_store_field, struct_name, ty),
-            )
-                       
-        });
-
-        // implement that trait for all StoreFields
-        let (trait_fields, read_fields) = all_field_data.unzip();
+                .unzip(),
+            ModelTy::Enum { variants } => {
+                let (mut trait_idx, mut read_idx) = (0, 0);
+                variants
+                    .iter()
+                    .map(|variant| {
+                        (
+                            variant_to_tokens(
+                                variant,
+                                &mut trait_idx,
+                                false,
+                                &cx,
+                            ),
+                            variant_to_tokens(
+                                variant,
+                                &mut read_idx,
+                                true,
+                                &cx,
+                            ),
+                        )
+                    })
+                    .unzip()
+            }
+        };
 
-        // read access
+        // define an extension trait that matches this type, and implement it
+        // for all StoreFields
         tokens.extend(quote! {
-            #vis trait #trait_name <AnyStoreField>
+            #vis trait #trait_name <#any_store_field, #params>
             #where_with_orig
             {
                 #(#trait_fields)*
             }
 
-            impl #generics_with_orig #trait_name <AnyStoreField> for AnyStoreField
+            impl <#any_store_field, #params> #trait_name <#any_store_field, #(#param_names),*> for #any_store_field
             #where_with_orig
             {
                #(#read_fields)*
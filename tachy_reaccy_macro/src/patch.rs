@@ -0,0 +1,185 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::{abort, abort_call_site};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    Attribute, Data, DeriveInput, Fields, Generics, Ident, Index, Member,
+    Result, Type, Variant,
+};
+
+pub struct PatchModel {
+    pub(crate) attrs: Vec<Attribute>,
+    pub(crate) name: Ident,
+    pub(crate) generics: Generics,
+    pub(crate) ty: PatchTy,
+}
+
+pub(crate) enum PatchTy {
+    Struct {
+        members: Vec<Member>,
+        tys: Vec<Type>,
+        attrs: Vec<Vec<Attribute>>,
+    },
+    Enum {
+        variants: Vec<Variant>,
+    },
+}
+
+impl Parse for PatchModel {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let input = DeriveInput::parse(input)?;
+
+        let ty = match input.data {
+            Data::Struct(s) => {
+                if let Fields::Unit = s.fields {
+                    abort!(s.semi_token, "unit structs are not supported");
+                }
+                let (members, tys) = s
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, field)| {
+                        (member(idx, &field.ident), field.ty.clone())
+                    })
+                    .unzip();
+                let attrs =
+                    s.fields.iter().map(|field| field.attrs.clone()).collect();
+                PatchTy::Struct {
+                    members,
+                    tys,
+                    attrs,
+                }
+            }
+            Data::Enum(e) => PatchTy::Enum {
+                variants: e.variants.into_iter().collect(),
+            },
+            Data::Union(_) => {
+                abort_call_site!(
+                    "only structs and enums can be used with `Patch`"
+                );
+            }
+        };
+
+        Ok(Self {
+            attrs: input.attrs,
+            name: input.ident,
+            generics: input.generics,
+            ty,
+        })
+    }
+}
+
+pub(crate) fn member(idx: usize, ident: &Option<Ident>) -> Member {
+    match ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(idx)),
+    }
+}
+
+impl ToTokens for PatchModel {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let library_path = quote! { ::tachys::tachy_reaccy::store };
+        let Self {
+            name, generics, ty, ..
+        } = self;
+        let (impl_generics, ty_generics, where_clause) =
+            generics.split_for_impl();
+        let predicates = where_clause.map(|w| &w.predicates);
+
+        // each field is patched at the same path as the accessor that
+        // `#[derive(Store)]` generates for it
+        let (field_tys, body) = match ty {
+            PatchTy::Struct { members, tys, .. } => {
+                let idx = 0..members.len();
+                let body = quote! {
+                    #(
+                        let mut field_path = path.clone();
+                        field_path.push(#idx);
+                        #library_path::PatchField::patch_field(
+                            &mut self.#members,
+                            new.#members,
+                            &field_path,
+                            keys,
+                            notify,
+                        );
+                    )*
+                };
+                (tys.iter().collect::<Vec<_>>(), body)
+            }
+            PatchTy::Enum { variants } => {
+                let mut next_idx = 0;
+                let arms = variants.iter().map(|variant| {
+                    let variant_name = &variant.ident;
+                    let members = variant
+                        .fields
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, field)| member(idx, &field.ident))
+                        .collect::<Vec<_>>();
+                    let old = (0..members.len())
+                        .map(|idx| format_ident!("old_{idx}"))
+                        .collect::<Vec<_>>();
+                    let new = (0..members.len())
+                        .map(|idx| format_ident!("new_{idx}"))
+                        .collect::<Vec<_>>();
+                    let idx = next_idx..next_idx + members.len();
+                    next_idx += members.len();
+                    quote! {
+                        (
+                            Self::#variant_name { #(#members: #old),* },
+                            Self::#variant_name { #(#members: #new),* },
+                        ) => {
+                            #(
+                                let mut field_path = path.clone();
+                                field_path.push(#idx);
+                                #library_path::PatchField::patch_field(
+                                    #old,
+                                    #new,
+                                    &field_path,
+                                    keys,
+                                    notify,
+                                );
+                            )*
+                        }
+                    }
+                });
+                let body = quote! {
+                    match (self, new) {
+                        #(#arms)*
+                        // a different variant replaces the whole value
+                        #[allow(unreachable_patterns)]
+                        (this, new) => {
+                            *this = new;
+                            notify(path);
+                        }
+                    }
+                };
+                let tys = variants
+                    .iter()
+                    .flat_map(|variant| &variant.fields)
+                    .map(|field| &field.ty)
+                    .collect();
+                (tys, body)
+            }
+        };
+
+        tokens.extend(quote! {
+            impl #impl_generics #library_path::PatchField for #name #ty_generics
+            where
+                #(#field_tys: #library_path::PatchField,)*
+                #predicates
+            {
+                #[allow(unused_variables)]
+                fn patch_field(
+                    &mut self,
+                    new: Self,
+                    path: &#library_path::StorePath,
+                    keys: &#library_path::KeyMap,
+                    notify: &mut dyn FnMut(&#library_path::StorePath),
+                ) {
+                    #body
+                }
+            }
+        });
+    }
+}
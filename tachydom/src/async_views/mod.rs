@@ -8,9 +8,16 @@ use crate::{
         Mountable, Position, PositionState, Render, RenderHtml,
     },
 };
-use futures::FutureExt;
+use futures::{
+    future::{AbortHandle, Abortable},
+    FutureExt,
+};
 use parking_lot::RwLock;
-use std::{fmt::Debug, future::Future, sync::Arc};
+use std::{
+    fmt::Debug,
+    future::Future,
+    sync::{Arc, Weak},
+};
 
 pub trait FutureViewExt: Sized {
     fn suspend(self) -> Suspend<false, (), Self>
@@ -54,7 +61,114 @@ impl<const TRANSITION: bool, Fal, Fut> Debug for Suspend<TRANSITION, Fal, Fut> {
     }
 }
 
-// TODO make this cancelable
+/// The state of a [`Suspend`] view.
+///
+/// Waiting for the future is cancelled when the view is dropped, or when it
+/// is rebuilt with a new future before the old one has resolved. A view that
+/// is unmounted while it is pending keeps waiting, so that it can be moved:
+/// if the future resolves before the view is mounted again, its value is
+/// shown once it is.
+pub struct SuspendState<Fal, Output, Rndr>
+where
+    Fal: Render<Rndr>,
+    Output: Render<Rndr>,
+    Rndr: Renderer,
+{
+    inner: Arc<RwLock<SuspendInner<Fal, Output, Rndr>>>,
+    abort: Option<AbortHandle>,
+}
+
+struct SuspendInner<Fal, Output, Rndr>
+where
+    Fal: Render<Rndr>,
+    Output: Render<Rndr>,
+    Rndr: Renderer,
+{
+    state: EitherState<Fal, Output, Rndr>,
+    mounted: bool,
+    // the value of a future that resolved while the view was unmounted
+    resolved: Option<Output>,
+}
+
+impl<Fal, Output, Rndr> SuspendInner<Fal, Output, Rndr>
+where
+    Fal: Render<Rndr>,
+    Output: Render<Rndr>,
+    Rndr: Renderer,
+{
+    fn resolve(&mut self, value: Output) {
+        // the new view is mounted next to the current one, so it can only be
+        // built while the current one is mounted
+        if self.mounted {
+            Either::Right(value).rebuild(&mut self.state);
+        } else {
+            self.resolved = Some(value);
+        }
+    }
+}
+
+impl<Fal, Output, Rndr> SuspendState<Fal, Output, Rndr>
+where
+    Fal: Render<Rndr> + 'static,
+    Output: Render<Rndr> + 'static,
+    Rndr: SpawningRenderer + 'static,
+{
+    fn new(state: EitherState<Fal, Output, Rndr>, mounted: bool) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(SuspendInner {
+                state,
+                mounted,
+                resolved: None,
+            })),
+            abort: None,
+        }
+    }
+
+    /// Spawns the future, and rebuilds the state when it resolves, unless
+    /// it has been cancelled first.
+    fn spawn<Fut>(&mut self, fut: Fut)
+    where
+        Fut: Future<Output = Output> + 'static,
+    {
+        let (abort, registration) = AbortHandle::new_pair();
+        self.cancel();
+        self.abort = Some(abort);
+        self.inner.write().resolved = None;
+
+        let state = Arc::downgrade(&self.inner);
+        Rndr::Spawn::spawn_local(async move {
+            let value = Abortable::new(fut, registration).await;
+            if let (Ok(value), Some(state)) = (value, Weak::upgrade(&state)) {
+                state.write().resolve(value);
+            }
+        });
+    }
+}
+
+impl<Fal, Output, Rndr> SuspendState<Fal, Output, Rndr>
+where
+    Fal: Render<Rndr>,
+    Output: Render<Rndr>,
+    Rndr: Renderer,
+{
+    fn cancel(&mut self) {
+        if let Some(abort) = self.abort.take() {
+            abort.abort();
+        }
+    }
+}
+
+impl<Fal, Output, Rndr> Drop for SuspendState<Fal, Output, Rndr>
+where
+    Fal: Render<Rndr>,
+    Output: Render<Rndr>,
+    Rndr: Renderer,
+{
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
 impl<const TRANSITION: bool, Fal, Fut, Rndr> Render<Rndr>
     for Suspend<TRANSITION, Fal, Fut>
 where
@@ -63,7 +177,7 @@ where
     Fut::Output: Render<Rndr>,
     Rndr: SpawningRenderer + 'static,
 {
-    type State = Arc<RwLock<EitherState<Fal, Fut::Output, Rndr>>>;
+    type State = SuspendState<Fal, Fut::Output, Rndr>;
 
     fn build(self) -> Self::State {
         // poll the future once immediately
@@ -79,20 +193,14 @@ where
         // by the time we need to know, we will have consumed `initial`
         let initially_pending = matches!(initial, Either::Left(_));
 
-        // now we can build the initial state
-        let state = Arc::new(RwLock::new(initial.build()));
+        // now we can build the initial state, which is mounted later
+        let mut state = SuspendState::new(initial.build(), false);
 
         // if the initial state was pending, spawn a future to wait for it
         // spawning immediately means that our now_or_never poll result isn't lost
         // if it wasn't pending at first, we don't need to poll the Future again
         if initially_pending {
-            Rndr::Spawn::spawn_local({
-                let state = Arc::clone(&state);
-                async move {
-                    let value = fut.as_mut().await;
-                    Either::Right(value).rebuild(&mut *state.write());
-                }
-            });
+            state.spawn(fut);
         }
 
         state
@@ -101,17 +209,12 @@ where
     fn rebuild(self, state: &mut Self::State) {
         if !TRANSITION {
             // fall back to fallback state
-            Either::Left(self.fallback).rebuild(&mut *state.write());
+            Either::Left(self.fallback).rebuild(&mut state.inner.write().state);
         }
 
         // spawn the future, and rebuild the state when it resolves
-        Rndr::Spawn::spawn_local({
-            let state = Arc::clone(state);
-            async move {
-                let value = self.fut.await;
-                Either::Right(value).rebuild(&mut *state.write());
-            }
-        });
+        // this cancels the previous future, if it hasn't resolved yet
+        state.spawn(self.fut);
     }
 }
 
@@ -198,30 +301,24 @@ where
         // by the time we need to know, we will have consumed `initial`
         let initially_pending = matches!(initial, Either::Left(_));
 
-        // now we can build the initial state
-        let state = Arc::new(RwLock::new(
+        // now we can build the initial state, which is already in the DOM
+        let mut state = SuspendState::new(
             initial.hydrate::<FROM_SERVER>(cursor, position),
-        ));
+            true,
+        );
 
         // if the initial state was pending, spawn a future to wait for it
         // spawning immediately means that our now_or_never poll result isn't lost
         // if it wasn't pending at first, we don't need to poll the Future again
         if initially_pending {
-            Rndr::Spawn::spawn_local({
-                let state = Arc::clone(&state);
-                async move {
-                    let value = fut.as_mut().await;
-                    Either::Right(value).rebuild(&mut *state.write());
-                }
-            });
+            state.spawn(fut);
         }
 
         state
     }
 }
 
-impl<Rndr, Fal, Output> Mountable<Rndr>
-    for Arc<RwLock<EitherState<Fal, Output, Rndr>>>
+impl<Rndr, Fal, Output> Mountable<Rndr> for SuspendState<Fal, Output, Rndr>
 where
     Fal: Render<Rndr>,
     Fal::State: Mountable<Rndr>,
@@ -230,7 +327,9 @@ where
     Rndr: Renderer,
 {
     fn unmount(&mut self) {
-        self.write().unmount();
+        let mut inner = self.inner.write();
+        inner.mounted = false;
+        inner.state.unmount();
     }
 
     fn mount(
@@ -238,7 +337,12 @@ where
         parent: &<Rndr as Renderer>::Element,
         marker: Option<&<Rndr as Renderer>::Node>,
     ) {
-        self.write().mount(parent, marker);
+        let mut inner = self.inner.write();
+        inner.state.mount(parent, marker);
+        inner.mounted = true;
+        if let Some(value) = inner.resolved.take() {
+            inner.resolve(value);
+        }
     }
 
     fn insert_before_this(
@@ -246,6 +350,130 @@ where
         parent: &<Rndr as Renderer>::Element,
         child: &mut dyn Mountable<Rndr>,
     ) -> bool {
-        self.write().insert_before_this(parent, child)
+        self.inner.write().state.insert_before_this(parent, child)
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::FutureViewExt;
+    use crate::{
+        html::element,
+        renderer::{mock_dom::MockDom, Renderer},
+        view::{Mountable, Render},
+    };
+    use futures::channel::oneshot;
+    use tokio::task::{yield_now, LocalSet};
+
+    async fn run_spawned_tasks() {
+        for _ in 0..4 {
+            yield_now().await;
+        }
+    }
+
+    fn view(
+        rx: oneshot::Receiver<()>,
+    ) -> impl Render<MockDom, State = impl Mountable<MockDom>> {
+        async move {
+            _ = rx.await;
+            "Loaded"
+        }
+        .suspend()
+        .with_fallback("Loading...")
+    }
+
+    #[tokio::test]
+    async fn suspend_resolves_while_mounted() {
+        LocalSet::new()
+            .run_until(async {
+                let (tx, rx) = oneshot::channel();
+                let parent = MockDom::create_element(element::Div);
+                let mut state = view(rx).build();
+                state.mount(&parent, None);
+                run_spawned_tasks().await;
+                assert!(parent.to_debug_html().contains("Loading..."));
+
+                tx.send(()).unwrap();
+                run_spawned_tasks().await;
+                assert!(parent.to_debug_html().contains("Loaded"));
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn dropping_suspend_cancels_future() {
+        LocalSet::new()
+            .run_until(async {
+                let (tx, rx) = oneshot::channel();
+                let parent = MockDom::create_element(element::Div);
+                let mut state = view(rx).build();
+                state.mount(&parent, None);
+                run_spawned_tasks().await;
+                assert!(!tx.is_canceled());
+
+                state.unmount();
+                run_spawned_tasks().await;
+                assert!(!tx.is_canceled());
+
+                drop(state);
+                run_spawned_tasks().await;
+                assert!(tx.is_canceled());
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn moved_suspend_resolves_after_remounting() {
+        LocalSet::new()
+            .run_until(async {
+                let (tx, rx) = oneshot::channel();
+                let first = MockDom::create_element(element::Div);
+                let second = MockDom::create_element(element::Div);
+                let mut state = view(rx).build();
+                state.mount(&first, None);
+
+                // resolves while the view is being moved
+                state.unmount();
+                tx.send(()).unwrap();
+                run_spawned_tasks().await;
+                state.mount(&second, None);
+                assert!(second.to_debug_html().contains("Loaded"));
+                assert!(!first.to_debug_html().contains("Loading..."));
+
+                // resolves after it has been moved
+                let (tx, rx) = oneshot::channel();
+                let first = MockDom::create_element(element::Div);
+                let second = MockDom::create_element(element::Div);
+                let mut state = view(rx).build();
+                state.mount(&first, None);
+                state.unmount();
+                state.mount(&second, None);
+                run_spawned_tasks().await;
+                tx.send(()).unwrap();
+                run_spawned_tasks().await;
+                assert!(second.to_debug_html().contains("Loaded"));
+                assert!(!first.to_debug_html().contains("Loading..."));
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn rebuilding_suspend_cancels_previous_future() {
+        LocalSet::new()
+            .run_until(async {
+                let (first_tx, first_rx) = oneshot::channel();
+                let (second_tx, second_rx) = oneshot::channel();
+                let parent = MockDom::create_element(element::Div);
+                let mut state = view(first_rx).build();
+                state.mount(&parent, None);
+                view(second_rx).rebuild(&mut state);
+                run_spawned_tasks().await;
+                assert!(first_tx.is_canceled());
+
+                second_tx.send(()).unwrap();
+                run_spawned_tasks().await;
+                assert!(parent.to_debug_html().contains("Loaded"));
+            })
+            .await;
     }
 }
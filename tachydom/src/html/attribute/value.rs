@@ -1,5 +1,4 @@
-use crate::renderer::Renderer;
-use std::borrow::Cow;
+use crate::{html::escape::escape_attr, renderer::Renderer};
 
 pub trait AttributeValue<R: Renderer> {
     type State;
@@ -292,8 +291,3 @@ where
         }
     }
 }
-
-// TODO
-fn escape_attr(value: &str) -> Cow<'_, str> {
-    value.into()
-}
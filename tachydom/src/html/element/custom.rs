@@ -22,6 +22,15 @@ pub struct Custom<E>(E)
 where
     E: CustomElementKey;
 
+impl<E> Custom<E>
+where
+    E: CustomElementKey,
+{
+    pub fn new(tag: E) -> Self {
+        Self(tag)
+    }
+}
+
 impl<E> ElementType for Custom<E>
 where
     E: CustomElementKey,
@@ -6,6 +6,11 @@ use crate::{
 };
 use std::marker::PhantomData;
 
+/// Sets the inner HTML of an element.
+///
+/// Unlike text and attribute values, this is not escaped when rendered to HTML:
+/// it is the way to deliberately insert markup into the page, and should never
+/// be used with untrusted input.
 #[inline(always)]
 pub fn inner_html<T, R>(value: T) -> InnerHtml<T, R>
 where
@@ -18,6 +23,7 @@ where
     }
 }
 
+/// An attribute that sets the raw, unescaped inner HTML of an element.
 #[derive(Debug, Clone, Copy)]
 pub struct InnerHtml<T, R>
 where
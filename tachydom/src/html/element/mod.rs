@@ -1,5 +1,5 @@
 use crate::{
-    html::attribute::Attribute,
+    html::{attribute::Attribute, escape::escape_attr},
     hydration::Cursor,
     renderer::{CastFrom, Renderer},
     ssr::StreamBuilder,
@@ -234,19 +234,22 @@ where
         if !class.is_empty() {
             buf.push(' ');
             buf.push_str("class=\"");
-            buf.push_str(class.trim_start().trim_end());
+            buf.push_str(&escape_attr(class.trim_start().trim_end()));
             buf.push('"');
         }
         if !style.is_empty() {
             buf.push(' ');
             buf.push_str("style=\"");
-            buf.push_str(style.trim_start().trim_end());
+            buf.push_str(&escape_attr(style.trim_start().trim_end()));
             buf.push('"');
         }
 
         buf.push('>');
 
         if !E::SELF_CLOSING {
+            // inner HTML is inserted as-is, and is not escaped
+            buf.push_str(&inner_html);
+
             // children
             *position = Position::FirstChild;
             self.children.to_html_with_buf(buf, position);
@@ -297,17 +300,22 @@ where
         if !class.is_empty() {
             buf.push(' ');
             buf.push_str("class=\"");
-            buf.push_str(class.trim_start().trim_end());
+            buf.push_str(&escape_attr(class.trim_start().trim_end()));
             buf.push('"');
         }
         if !style.is_empty() {
             buf.push(' ');
             buf.push_str("style=\"");
-            buf.push_str(style.trim_start().trim_end());
+            buf.push_str(&escape_attr(style.trim_start().trim_end()));
             buf.push('"');
         }
 
         buf.push('>');
+
+        if !E::SELF_CLOSING {
+            // inner HTML is inserted as-is, and is not escaped
+            buf.push_str(&inner_html);
+        }
         buffer.push_sync(&buf);
 
         if !E::SELF_CLOSING {
@@ -448,13 +456,13 @@ where
             if !class.is_empty() {
                 buf.push(' ');
                 buf.push_str("class=\"");
-                buf.push_str(class.trim_start().trim_end());
+                buf.push_str(&escape_attr(class.trim_start().trim_end()));
                 buf.push('"');
             }
             if !style.is_empty() {
                 buf.push(' ');
                 buf.push_str("style=\"");
-                buf.push_str(style.trim_start().trim_end());
+                buf.push_str(&escape_attr(style.trim_start().trim_end()));
                 buf.push('"');
             }
             buf.push('>');
@@ -486,9 +494,8 @@ mod tests {
     use super::{main, p, HtmlElement};
     use crate::{
         html::{
-            attribute::{global::GlobalAttributes, id, src},
-            class::class,
-            element::{em, ElementChild, Main},
+            attribute::global::GlobalAttributes,
+            element::{em, ElementChild, InnerHtmlAttribute, Main},
         },
         renderer::mock_dom::MockDom,
         view::{Render, RenderHtml},
@@ -501,7 +508,7 @@ mod tests {
         let el = el.build();
         assert_eq!(
             el.el.to_debug_html(),
-            "<main><p lang=\"en\" id=\"test\">Hello, world!</p></main>"
+            "<main><p id=\"test\" lang=\"en\">Hello, world!</p></main>"
         );
     }
 
@@ -519,6 +526,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn html_render_escapes_text_and_attributes() {
+        let el: HtmlElement<Main, _, _, MockDom> = main().child(
+            p().id("\"><script>").child(("<b>Tom & Jerry</b>", '<', 42)),
+        );
+        assert_eq!(
+            el.to_html(),
+            "<main><p id=\"&quot;&gt;&lt;script&gt;\">&lt;b&gt;Tom &amp; \
+             Jerry&lt;/b&gt;<!>&lt;<!>42</p></main>"
+        );
+    }
+
+    #[test]
+    fn html_render_does_not_escape_inner_html() {
+        let el: HtmlElement<Main, _, _, MockDom> =
+            main().child(p().inner_html("<b>bold</b>"));
+        assert_eq!(el.to_html(), "<main><p><b>bold</b></p></main>");
+    }
+
     #[cfg(feature = "nightly")]
     #[test]
     fn html_render_allocates_appropriate_buffer() {
@@ -0,0 +1,97 @@
+//! Escaping for text and attribute values rendered to HTML.
+//!
+//! Everything that is rendered to an HTML string is escaped by default, so that
+//! user-provided data can't be used to inject markup into the page. Use
+//! [`inner_html`](crate::html::element::inner_html) to deliberately insert
+//! HTML that should not be escaped.
+
+use std::{borrow::Cow, fmt};
+
+/// Escapes a string so that it can be used as the content of a text node.
+///
+/// Returns the original string, without allocating, if nothing needs to be
+/// escaped.
+pub fn escape_text(value: &str) -> Cow<'_, str> {
+    escape(value, |c| match c {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        _ => None,
+    })
+}
+
+/// Escapes a string so that it can be used as the value of a double-quoted
+/// attribute.
+///
+/// Returns the original string, without allocating, if nothing needs to be
+/// escaped.
+pub fn escape_attr(value: &str) -> Cow<'_, str> {
+    escape(value, |c| match c {
+        '&' => Some("&amp;"),
+        '<' => Some("&lt;"),
+        '>' => Some("&gt;"),
+        '"' => Some("&quot;"),
+        _ => None,
+    })
+}
+
+fn escape(
+    value: &str,
+    replacement: impl Fn(char) -> Option<&'static str>,
+) -> Cow<'_, str> {
+    let first = match value.find(|c| replacement(c).is_some()) {
+        Some(idx) => idx,
+        None => return Cow::Borrowed(value),
+    };
+
+    let mut escaped = String::with_capacity(value.len() + 8);
+    escaped.push_str(&value[..first]);
+    for c in value[first..].chars() {
+        match replacement(c) {
+            Some(entity) => escaped.push_str(entity),
+            None => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Wraps an HTML buffer so that anything formatted into it is escaped as text.
+///
+/// This allows `Display` types to be written into the buffer without
+/// allocating an intermediate `String`.
+pub(crate) struct EscapeText<'a>(pub &'a mut String);
+
+impl fmt::Write for EscapeText<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.push_str(&escape_text(s));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_attr, escape_text};
+    use std::borrow::Cow;
+
+    #[test]
+    fn text_without_special_characters_is_borrowed() {
+        assert!(matches!(escape_text("Hello, world!"), Cow::Borrowed(_)));
+        assert!(matches!(escape_attr("foo bar"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(
+            escape_text("<script>alert(\"pwned\")</script> & more"),
+            "&lt;script&gt;alert(\"pwned\")&lt;/script&gt; &amp; more"
+        );
+    }
+
+    #[test]
+    fn attr_is_escaped() {
+        assert_eq!(
+            escape_attr("\" onload=\"alert('pwned')"),
+            "&quot; onload=&quot;alert('pwned')"
+        );
+    }
+}
@@ -18,6 +18,10 @@ where
         setup: Box::new(move |el| {
             let cb = Box::new(move |ev: R::Event| {
                 let specific_event = ev.into();
+                // signal updates in the handler only notify subscribers once
+                #[cfg(feature = "reaccy")]
+                ::tachy_reaccy::batch(|| cb(specific_event));
+                #[cfg(not(feature = "reaccy"))]
                 cb(specific_event);
             }) as Box<dyn FnMut(R::Event)>;
 
@@ -385,3 +389,49 @@ pub use web_sys::{
     StorageEvent, SubmitEvent, TouchEvent, TransitionEvent, UiEvent,
     WheelEvent,
 };
+
+#[cfg(all(test, feature = "reaccy"))]
+mod tests {
+    use crate::{
+        html::{
+            attribute::global::OnAttribute,
+            element::{button, HtmlElement},
+            event,
+        },
+        renderer::mock_dom::MockDom,
+        view::Render,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tachy_reaccy::prelude::*;
+
+    #[test]
+    fn handlers_batch_signal_updates() {
+        let a = RwSignal::new(0);
+        let b = RwSignal::new(0);
+        let runs = Arc::new(AtomicUsize::new(0));
+        let sum = Memo::new({
+            let runs = Arc::clone(&runs);
+            move |_| {
+                runs.fetch_add(1, Ordering::Relaxed);
+                a.get() + b.get()
+            }
+        });
+        assert_eq!(sum.get_untracked(), 0);
+
+        let app: HtmlElement<_, _, _, MockDom> =
+            button().on(event::click, move |_| {
+                a.set(1);
+                // the memo is only marked as changed once the handler returns
+                assert_eq!(sum.get_untracked(), 0);
+                b.set(2);
+            });
+        let el = app.build();
+        el.el.dispatch_event(event::click);
+
+        assert_eq!(sum.get_untracked(), 3);
+        assert_eq!(runs.load(Ordering::Relaxed), 2);
+    }
+}
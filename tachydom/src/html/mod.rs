@@ -7,6 +7,7 @@ use std::marker::PhantomData;
 pub mod attribute;
 pub mod class;
 pub mod element;
+pub mod escape;
 pub mod event;
 pub mod node_ref;
 pub mod property;
@@ -94,12 +94,13 @@ pub trait IntoProperty<R: DomRenderer> {
 
 macro_rules! prop_type {
     ($prop_type:ty) => {
-        impl<R> IntoProperty<R> for $prop_type
+        impl<'a, R> IntoProperty<R> for $prop_type
         where
             R: DomRenderer,
             R::Element: Clone,
+            R::PropertyValue: From<$prop_type> + PartialEq,
         {
-            type State = (R::Element, JsValue);
+            type State = (R::Element, R::PropertyValue);
 
             fn hydrate<const FROM_SERVER: bool>(
                 self,
@@ -131,8 +132,8 @@ macro_rules! prop_type {
 
 prop_type!(JsValue);
 prop_type!(String);
-prop_type!(&String);
-prop_type!(&str);
+prop_type!(&'a String);
+prop_type!(&'a str);
 prop_type!(usize);
 prop_type!(u8);
 prop_type!(u16);
@@ -0,0 +1,432 @@
+//! Islands: server-rendered pages in which only some components are hydrated.
+//!
+//! On the server, an island component is wrapped in an [`Island`], which
+//! renders it inside a `<tachy-island>` element that records the name of the
+//! component and its serialized props. The rest of the page is plain HTML.
+//!
+//! On the client, an [`IslandRegistry`] maps the name of each island
+//! component to a function that creates its view.
+//! [`IslandRegistry::hydrate`] finds the islands in the page and hydrates
+//! only those subtrees. Everything else stays static HTML, and never needs to
+//! be created on the client.
+
+use crate::{
+    html::{
+        element::{CreateElement, Custom},
+        escape::escape_attr,
+    },
+    hydration::Cursor,
+    renderer::{CastFrom, DomRenderer, Renderer},
+    ssr::StreamBuilder,
+    view::{Mountable, Position, PositionState, Render, RenderHtml},
+};
+use rustc_hash::FxHashMap;
+use std::{any::Any, fmt::Write};
+
+/// The tag of the element that wraps each island.
+pub const ISLAND_TAG: &str = "tachy-island";
+const COMPONENT_ATTR: &str = "data-island";
+const PROPS_ATTR: &str = "data-props";
+const POSITION_ATTR: &str = "data-hk";
+
+/// A component that is hydrated on its own, in a page that is otherwise
+/// static HTML.
+pub struct Island<View> {
+    component: &'static str,
+    props: Option<String>,
+    // the position of the owner of the island, on the server
+    position: Option<String>,
+    view: View,
+}
+
+impl<View> Island<View> {
+    /// Creates the view of the island component with the given name. This
+    /// name is used to find the component in the [`IslandRegistry`].
+    pub fn new(component: &'static str, view: impl FnOnce() -> View) -> Self {
+        // the island is created under its own owner, so that the client can
+        // create the data in it with the same IDs
+        #[cfg(feature = "reaccy")]
+        let (view, position) =
+            match ::tachy_reaccy::shared_context::island_owner() {
+                Some((owner, position)) => (owner.with(view), Some(position)),
+                None => (view(), None),
+            };
+        #[cfg(not(feature = "reaccy"))]
+        let (view, position) = (view(), None);
+        Self {
+            component,
+            props: None,
+            position,
+            view,
+        }
+    }
+
+    /// Sets the serialized props of the island, which are sent to the client
+    /// so that it can create the same view again.
+    pub fn with_props(mut self, props: String) -> Self {
+        self.props = Some(props);
+        self
+    }
+
+    fn open_tag(&self, buf: &mut String) {
+        _ = write!(
+            buf,
+            "<{ISLAND_TAG} {COMPONENT_ATTR}=\"{}\"",
+            escape_attr(self.component)
+        );
+        if let Some(props) = &self.props {
+            _ = write!(buf, " {PROPS_ATTR}=\"{}\"", escape_attr(props));
+        }
+        if let Some(position) = &self.position {
+            _ = write!(buf, " {POSITION_ATTR}=\"{position}\"");
+        }
+        buf.push('>');
+    }
+}
+
+impl<View, R> Render<R> for Island<View>
+where
+    View: Render<R>,
+    R: Renderer,
+    Custom<&'static str>: CreateElement<R>,
+{
+    type State = IslandState<View::State, R>;
+
+    fn build(self) -> Self::State {
+        let el = R::create_element(Custom::new(ISLAND_TAG));
+        R::set_attribute(&el, COMPONENT_ATTR, self.component);
+        if let Some(props) = &self.props {
+            R::set_attribute(&el, PROPS_ATTR, props);
+        }
+        if let Some(position) = &self.position {
+            R::set_attribute(&el, POSITION_ATTR, position);
+        }
+        let mut view = self.view.build();
+        view.mount(&el, None);
+        IslandState { el, view }
+    }
+
+    fn rebuild(self, state: &mut Self::State) {
+        self.view.rebuild(&mut state.view);
+    }
+}
+
+impl<View, R> RenderHtml<R> for Island<View>
+where
+    View: RenderHtml<R>,
+    R: Renderer,
+    R::Node: Clone,
+    R::Element: Clone,
+    Custom<&'static str>: CreateElement<R>,
+{
+    const MIN_LENGTH: usize = View::MIN_LENGTH;
+
+    fn to_html_with_buf(self, buf: &mut String, position: &mut Position) {
+        self.open_tag(buf);
+        *position = Position::FirstChild;
+        self.view.to_html_with_buf(buf, position);
+        _ = write!(buf, "</{ISLAND_TAG}>");
+        *position = Position::NextChild;
+    }
+
+    fn to_html_async_with_buf<const OUT_OF_ORDER: bool>(
+        self,
+        buf: &mut StreamBuilder,
+        position: &mut Position,
+    ) where
+        Self: Sized,
+    {
+        buf.with_buf(|buf| self.open_tag(buf));
+        *position = Position::FirstChild;
+        self.view
+            .to_html_async_with_buf::<OUT_OF_ORDER>(buf, position);
+        buf.push_sync(&format!("</{ISLAND_TAG}>"));
+        *position = Position::NextChild;
+    }
+
+    fn hydrate<const FROM_SERVER: bool>(
+        self,
+        cursor: &Cursor<R>,
+        position: &PositionState,
+    ) -> Self::State {
+        if position.get() == Position::FirstChild {
+            cursor.child();
+        } else {
+            cursor.sibling();
+        }
+        let el = R::Element::cast_from(cursor.current()).unwrap();
+
+        position.set(Position::FirstChild);
+        let view = self.view.hydrate::<FROM_SERVER>(cursor, position);
+        cursor.set(el.as_ref().clone());
+        position.set(Position::NextChild);
+
+        IslandState { el, view }
+    }
+}
+
+/// The state of an [`Island`].
+pub struct IslandState<S, R: Renderer> {
+    el: R::Element,
+    view: S,
+}
+
+impl<S, R> Mountable<R> for IslandState<S, R>
+where
+    R: Renderer,
+{
+    fn unmount(&mut self) {
+        R::remove(self.el.as_ref());
+    }
+
+    fn mount(&mut self, parent: &R::Element, marker: Option<&R::Node>) {
+        R::insert_node(parent, self.el.as_ref(), marker);
+    }
+
+    fn insert_before_this(
+        &self,
+        parent: &R::Element,
+        child: &mut dyn Mountable<R>,
+    ) -> bool {
+        child.mount(parent, Some(self.el.as_ref()));
+        true
+    }
+}
+
+type HydrateIsland<R> =
+    Box<dyn Fn(&<R as Renderer>::Element, Option<&str>) -> Box<dyn Any>>;
+
+/// The island components that can be hydrated on the client, by name.
+pub struct IslandRegistry<R: Renderer> {
+    islands: FxHashMap<&'static str, HydrateIsland<R>>,
+}
+
+impl<R: Renderer> Default for IslandRegistry<R> {
+    fn default() -> Self {
+        Self {
+            islands: Default::default(),
+        }
+    }
+}
+
+impl<R> IslandRegistry<R>
+where
+    R: DomRenderer,
+    R::Node: Clone,
+    R::Element: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the island component with the given name. `view` creates
+    /// the view of the component from its serialized props, if it has any.
+    pub fn register<V>(
+        mut self,
+        component: &'static str,
+        view: impl Fn(Option<&str>) -> V + 'static,
+    ) -> Self
+    where
+        V: RenderHtml<R>,
+        V::State: 'static,
+    {
+        self.islands.insert(
+            component,
+            Box::new(move |el, props| {
+                Box::new(view(props).hydrate_from::<true>(el))
+            }),
+        );
+        self
+    }
+
+    /// Hydrates every island inside `root`, leaving everything else as it
+    /// is. Islands that are nested inside another island are hydrated as
+    /// part of the outer island, and islands whose component has not been
+    /// registered are left as static HTML.
+    ///
+    /// The islands stay interactive for as long as the returned
+    /// [`HydratedIslands`] is kept alive. To hydrate the data that was sent
+    /// from the server, this should be called under a hydrating root.
+    pub fn hydrate(&self, root: &R::Element) -> HydratedIslands {
+        let mut islands = HydratedIslands(Vec::new());
+        self.hydrate_children(root.as_ref(), &mut islands);
+        islands
+    }
+
+    fn hydrate_children(
+        &self,
+        parent: &R::Node,
+        islands: &mut HydratedIslands,
+    ) {
+        let mut next = R::first_child(parent);
+        while let Some(node) = next {
+            next = R::next_sibling(&node);
+            let Some(el) = R::Element::cast_from(node.clone()) else {
+                continue;
+            };
+            match R::get_attribute(&el, COMPONENT_ATTR) {
+                Some(component) => {
+                    if let Some(hydrate) = self.islands.get(component.as_str())
+                    {
+                        let props = R::get_attribute(&el, PROPS_ATTR);
+                        let hydrate = || hydrate(&el, props.as_deref());
+                        #[cfg(feature = "reaccy")]
+                        let island = hydrate_with_owner(
+                            R::get_attribute(&el, POSITION_ATTR),
+                            hydrate,
+                        );
+                        #[cfg(not(feature = "reaccy"))]
+                        let island = hydrate();
+                        islands.0.push(island);
+                    }
+                }
+                None => self.hydrate_children(&node, islands),
+            }
+        }
+    }
+}
+
+// creates the island under an owner at the same position as on the server, and
+// keeps the owner alive along with the island
+#[cfg(feature = "reaccy")]
+fn hydrate_with_owner(
+    position: Option<String>,
+    hydrate: impl FnOnce() -> Box<dyn Any>,
+) -> Box<dyn Any> {
+    use ::tachy_reaccy::shared_context::hydrate_island_owner;
+
+    match position.and_then(|position| hydrate_island_owner(&position)) {
+        Some(owner) => Box::new((owner.with(hydrate), owner)),
+        None => hydrate(),
+    }
+}
+
+/// The state of the islands hydrated by [`IslandRegistry::hydrate`].
+pub struct HydratedIslands(Vec<Box<dyn Any>>);
+
+impl HydratedIslands {
+    /// The number of islands that were hydrated.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Island, IslandRegistry};
+    use crate::{
+        html::{
+            attribute::global::OnAttribute,
+            element::{button, div, main, p, ElementChild, HtmlElement},
+            event,
+        },
+        renderer::{
+            mock_dom::{Element, MockDom},
+            CastFrom, Renderer,
+        },
+        view::{Mountable, Render, RenderHtml},
+    };
+    use std::{cell::Cell, rc::Rc};
+
+    fn counter(clicks: Rc<Cell<usize>>) -> impl RenderHtml<MockDom> {
+        button()
+            .on(event::click, move |_| clicks.set(clicks.get() + 1))
+            .child("Click")
+    }
+
+    fn find(parent: &Element, tag: &str) -> Option<Element> {
+        let mut next = MockDom::first_child(parent.as_ref());
+        while let Some(node) = next {
+            next = MockDom::next_sibling(&node);
+            if let Some(el) = Element::cast_from(node) {
+                if el.tag_name() == tag {
+                    return Some(el);
+                }
+                if let Some(found) = find(&el, tag) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn islands_render_as_marker_elements_with_props() {
+        let island = Island::new("Counter", || p().child("0"))
+            .with_props(r#"{"initial":0}"#.to_string());
+        let view: HtmlElement<_, _, _, MockDom> = div().child(island);
+        assert_eq!(
+            view.to_html(),
+            "<div><tachy-island data-island=\"Counter\" \
+             data-props=\"{&quot;initial&quot;:0}\"><p>0</p></tachy-island></\
+             div>"
+        );
+    }
+
+    #[test]
+    fn only_registered_islands_are_hydrated() {
+        // the page as it was rendered on the server
+        let page: HtmlElement<_, _, _, MockDom> = main().child((
+            p().child("Static"),
+            Island::new("Counter", || button().child("Click")),
+            div().child(Island::new("Unknown", || button().child("Other"))),
+        ));
+        let container = MockDom::create_element(crate::html::element::Div);
+        let mut state = page.build();
+        state.mount(&container, None);
+
+        let clicks = Rc::new(Cell::new(0));
+        let registry = IslandRegistry::new().register("Counter", {
+            let clicks = Rc::clone(&clicks);
+            move |props| {
+                assert_eq!(props, None);
+                counter(Rc::clone(&clicks))
+            }
+        });
+        let islands = registry.hydrate(&container);
+        assert_eq!(islands.len(), 1);
+
+        find(&container, "button")
+            .unwrap()
+            .dispatch_event(event::click);
+        assert_eq!(clicks.get(), 1);
+        // nothing outside the island was touched
+        assert_eq!(
+            container.to_debug_html(),
+            "<div><main><p>Static</p><tachy-island \
+             data-island=\"Counter\"><button>Click</button></\
+             tachy-island><div><tachy-island \
+             data-island=\"Unknown\"><button>Other</button></tachy-island></\
+             div></main></div>"
+        );
+    }
+
+    #[test]
+    fn islands_receive_their_props() {
+        let page: HtmlElement<_, _, _, MockDom> = main().child(
+            Island::new("Counter", || button().child("Click"))
+                .with_props("5".to_string()),
+        );
+        let container = MockDom::create_element(crate::html::element::Div);
+        let mut state = page.build();
+        state.mount(&container, None);
+
+        let clicks = Rc::new(Cell::new(0));
+        let registry = IslandRegistry::new().register("Counter", {
+            let clicks = Rc::clone(&clicks);
+            move |props| {
+                clicks.set(props.unwrap().parse().unwrap());
+                counter(Rc::clone(&clicks))
+            }
+        });
+        let _islands = registry.hydrate(&container);
+        find(&container, "button")
+            .unwrap()
+            .dispatch_event(event::click);
+        assert_eq!(clicks.get(), 6);
+    }
+}
@@ -31,11 +31,14 @@ pub mod dom;
 pub mod error;
 pub mod html;
 pub mod hydration;
+pub mod islands;
 pub mod mathml;
 pub mod renderer;
 pub mod spawner;
 pub mod ssr;
 pub mod svg;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod view;
 
 #[cfg(all(feature = "leptos", not(feature = "reaccy")))]
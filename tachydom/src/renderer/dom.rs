@@ -1,4 +1,4 @@
-#[cfg(any(feature = "tokio", feature = "web"))]
+#[cfg(any(feature = "reaccy", feature = "web", feature = "tokio"))]
 use super::SpawningRenderer;
 use super::{CastFrom, DomRenderer, Renderer};
 use crate::{
@@ -154,6 +154,7 @@ impl DomRenderer for Dom {
     type Event = JsValue;
     type ClassList = DomTokenList;
     type CssStyleDeclaration = CssStyleDeclaration;
+    type PropertyValue = JsValue;
 
     fn set_property(el: &Self::Element, key: &str, value: &JsValue) {
         or_debug!(
@@ -329,6 +330,10 @@ impl DomRenderer for Dom {
     fn set_inner_html(el: &Self::Element, html: &str) {
         el.set_inner_html(html);
     }
+
+    fn get_attribute(el: &Self::Element, name: &str) -> Option<String> {
+        el.get_attribute(name)
+    }
 }
 
 impl Mountable<Dom> for Node {
@@ -444,12 +449,17 @@ impl CastFrom<Node> for Element {
     }
 }
 
-#[cfg(feature = "web")]
+#[cfg(feature = "reaccy")]
+impl SpawningRenderer for Dom {
+    type Spawn = crate::spawner::reaccy::Reaccy;
+}
+
+#[cfg(all(feature = "web", not(feature = "reaccy")))]
 impl SpawningRenderer for Dom {
     type Spawn = crate::spawner::wasm::Wasm;
 }
 
-#[cfg(all(feature = "tokio", not(feature = "web")))]
+#[cfg(all(feature = "tokio", not(feature = "web"), not(feature = "reaccy")))]
 impl SpawningRenderer for Dom {
     type Spawn = crate::spawner::tokio::Tokio;
 }
@@ -1,17 +1,30 @@
-#![allow(unused)]
-
-//! A stupidly-simple mock DOM implementation that can be used for testing.
+//! A headless mock DOM implementation that can be used for testing.
+//!
+//! All nodes are stored in a thread-local [`Document`]. The mock DOM tracks
+//! attributes, classes, inline styles, properties, event listeners, and inner
+//! HTML, and any element can be serialized back to HTML with
+//! [`Element::to_debug_html`], so that the output of a view can be checked
+//! natively, without a browser.
 //!
 //! Do not use this for anything real.
 
+#[cfg(any(feature = "reaccy", feature = "web", feature = "tokio"))]
+use super::SpawningRenderer;
 use super::{CastFrom, DomRenderer, Renderer};
 use crate::{
-    html::element::{CreateElement, ElementType},
+    html::{
+        element::{CreateElement, ElementType},
+        escape::{escape_attr, escape_text},
+        event::EventDescriptor,
+    },
     view::Mountable,
 };
-use slotmap::{new_key_type, SlotMap};
-use std::{borrow::Cow, cell::RefCell, collections::HashMap, rc::Rc};
+use indexmap::IndexMap;
+use rustc_hash::FxHashMap;
+use slotmap::{new_key_type, SecondaryMap, SlotMap};
+use std::{borrow::Cow, cell::RefCell, rc::Rc};
 use wasm_bindgen::JsValue;
+
 pub struct MockDom;
 
 new_key_type! {
@@ -30,6 +43,80 @@ pub struct Text(Node);
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Placeholder(Node);
 
+/// The list of CSS classes for an element, which is kept in sync with its
+/// `class` attribute.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClassList(Element);
+
+/// The inline styles of an element, which are kept in sync with its `style`
+/// attribute.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CssStyleDeclaration(Element);
+
+/// A value that has been set as a property on an element.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+    /// A JavaScript value, which is opaque when not running in a browser.
+    Js(JsValue),
+}
+
+impl From<JsValue> for PropertyValue {
+    fn from(value: JsValue) -> Self {
+        PropertyValue::Js(value)
+    }
+}
+
+impl From<bool> for PropertyValue {
+    fn from(value: bool) -> Self {
+        PropertyValue::Bool(value)
+    }
+}
+
+impl From<String> for PropertyValue {
+    fn from(value: String) -> Self {
+        PropertyValue::String(value)
+    }
+}
+
+impl From<&String> for PropertyValue {
+    fn from(value: &String) -> Self {
+        PropertyValue::String(value.to_owned())
+    }
+}
+
+impl From<&str> for PropertyValue {
+    fn from(value: &str) -> Self {
+        PropertyValue::String(value.to_owned())
+    }
+}
+
+macro_rules! number_property {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for PropertyValue {
+                fn from(value: $ty) -> Self {
+                    PropertyValue::Number(value as f64)
+                }
+            }
+        )*
+    };
+}
+
+number_property!(
+    usize, u8, u16, u32, u64, u128, isize, i8, i16, i32, i64, i128, f32, f64
+);
+
+type ListenerFn = Rc<RefCell<Box<dyn FnMut(JsValue)>>>;
+
+struct Listener {
+    name: Cow<'static, str>,
+    delegated: bool,
+    cb: ListenerFn,
+}
+
 impl AsRef<Node> for Node {
     fn as_ref(&self) -> &Node {
         self
@@ -76,12 +163,176 @@ impl From<Placeholder> for Node {
     }
 }
 
+impl Node {
+    /// Returns the text content of this node and all its descendants.
+    ///
+    /// Inner HTML is not parsed, so it is included as-is.
+    pub fn text_content(&self) -> String {
+        let mut buf = String::new();
+        Document::with_node(self.0, |node| node.text_content(&mut buf));
+        buf
+    }
+
+    /// Returns the children of this node.
+    pub fn child_nodes(&self) -> Vec<Node> {
+        Document::with_node(self.0, |node| match &node.ty {
+            NodeType::Element { children, .. } => children.clone(),
+            _ => Vec::new(),
+        })
+        .unwrap_or_default()
+    }
+}
+
 impl Element {
     pub fn to_debug_html(&self) -> String {
         let mut buf = String::new();
         self.debug_html(&mut buf);
         buf
     }
+
+    /// Returns the text content of this element and its descendants.
+    pub fn text_content(&self) -> String {
+        self.0.text_content()
+    }
+
+    /// Returns the tag name of this element.
+    pub fn tag_name(&self) -> String {
+        Document::with_node(self.0 .0, |node| match &node.ty {
+            NodeType::Element { tag, .. } => tag.to_string(),
+            _ => String::new(),
+        })
+        .unwrap_or_default()
+    }
+
+    /// Returns the current value of the given attribute, if it is set.
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        Document::with_node(self.0 .0, |node| match &node.ty {
+            NodeType::Element { attrs, .. } => attrs.get(name).cloned(),
+            _ => None,
+        })
+        .flatten()
+    }
+
+    /// Returns the current value of the given property, if it has been set.
+    pub fn property(&self, name: &str) -> Option<PropertyValue> {
+        DOCUMENT.with(|d| {
+            d.properties
+                .borrow()
+                .get(self.0 .0)
+                .and_then(|props| props.get(name).cloned())
+        })
+    }
+
+    /// Dispatches an event with this element as its target.
+    ///
+    /// Listeners receive an `undefined` [`JsValue`] as the event. If the event
+    /// bubbles, listeners on all the ancestors of this element are also called,
+    /// followed by delegated listeners, in the same order in which they run in
+    /// the browser.
+    pub fn dispatch_event<E: EventDescriptor>(&self, event: E) {
+        let name = event.name();
+        let mut path = vec![self.0 .0];
+        if E::BUBBLES {
+            let mut current = self.0.clone();
+            while let Some(parent) = MockDom::get_parent(&current) {
+                path.push(parent.0);
+                current = parent;
+            }
+        }
+
+        for id in &path {
+            for cb in Document::listeners(*id, &name, false) {
+                (cb.borrow_mut())(JsValue::UNDEFINED);
+            }
+        }
+
+        // delegated listeners are called by a single global handler once the
+        // event has bubbled up to the window, which skips disabled elements
+        if E::BUBBLES {
+            for id in &path {
+                if Document::is_disabled(*id) {
+                    continue;
+                }
+                for cb in Document::listeners(*id, &name, true) {
+                    (cb.borrow_mut())(JsValue::UNDEFINED);
+                }
+            }
+        }
+    }
+}
+
+impl ClassList {
+    /// Checks whether the list contains the given class.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0
+            .attribute("class")
+            .map(|classes| {
+                classes.split_whitespace().any(|class| class == name)
+            })
+            .unwrap_or(false)
+    }
+
+    fn update(&self, fun: impl FnOnce(&mut Vec<String>)) {
+        let mut classes = self
+            .0
+            .attribute("class")
+            .map(|classes| {
+                classes.split_whitespace().map(str::to_owned).collect()
+            })
+            .unwrap_or_default();
+        fun(&mut classes);
+        if classes.is_empty() {
+            MockDom::remove_attribute(&self.0, "class");
+        } else {
+            MockDom::set_attribute(&self.0, "class", &classes.join(" "));
+        }
+    }
+}
+
+impl CssStyleDeclaration {
+    /// Returns the value of the given CSS property, if it is set.
+    pub fn get_property_value(&self, name: &str) -> Option<String> {
+        self.properties()
+            .into_iter()
+            .find(|(prop, _)| prop == name)
+            .map(|(_, value)| value)
+    }
+
+    fn properties(&self) -> Vec<(String, String)> {
+        self.0
+            .attribute("style")
+            .map(|style| {
+                style
+                    .split(';')
+                    .filter_map(|decl| {
+                        let (name, value) = decl.split_once(':')?;
+                        Some((name.trim().to_owned(), value.trim().to_owned()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn set_property(&self, name: &str, value: &str) {
+        let mut props = self.properties();
+        match props.iter_mut().find(|(prop, _)| prop == name) {
+            Some((_, prev)) => *prev = value.to_owned(),
+            None => props.push((name.to_owned(), value.to_owned())),
+        }
+        // setting a property to an empty string removes it
+        props.retain(|(_, value)| !value.is_empty());
+
+        if props.is_empty() {
+            MockDom::remove_attribute(&self.0, "style");
+        } else {
+            let style = props
+                .iter()
+                .map(|(name, value)| format!("{name}: {value};"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            MockDom::set_attribute(&self.0, "style", &style);
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -90,6 +341,22 @@ pub struct NodeData {
     pub ty: NodeType,
 }
 
+impl NodeData {
+    fn text_content(&self, buf: &mut String) {
+        match &self.ty {
+            NodeType::Text(text) | NodeType::RawHtml(text) => {
+                buf.push_str(text)
+            }
+            NodeType::Element { children, .. } => {
+                for child in children {
+                    Document::with_node(child.0, |node| node.text_content(buf));
+                }
+            }
+            NodeType::Placeholder => {}
+        }
+    }
+}
+
 trait DebugHtml {
     fn debug_html(&self, buf: &mut String);
 }
@@ -121,9 +388,11 @@ impl DebugHtml for Node {
 impl DebugHtml for NodeData {
     fn debug_html(&self, buf: &mut String) {
         match &self.ty {
-            NodeType::Text(text) => buf.push_str(text),
+            NodeType::Text(text) => buf.push_str(&escape_text(text)),
+            NodeType::RawHtml(html) => buf.push_str(html),
             NodeType::Element {
                 tag,
+                self_closing,
                 attrs,
                 children,
             } => {
@@ -132,19 +401,24 @@ impl DebugHtml for NodeData {
                 for (k, v) in attrs {
                     buf.push(' ');
                     buf.push_str(k);
-                    buf.push_str("=\"");
-                    buf.push_str(v);
-                    buf.push('"');
+                    // boolean attributes are rendered without a value, as in SSR
+                    if !v.is_empty() {
+                        buf.push_str("=\"");
+                        buf.push_str(&escape_attr(v));
+                        buf.push('"');
+                    }
                 }
                 buf.push('>');
 
-                for child in children {
-                    child.debug_html(buf);
-                }
+                if !self_closing {
+                    for child in children {
+                        child.debug_html(buf);
+                    }
 
-                buf.push_str("</");
-                buf.push_str(tag);
-                buf.push('>');
+                    buf.push_str("</");
+                    buf.push_str(tag);
+                    buf.push('>');
+                }
             }
             NodeType::Placeholder => buf.push_str("<!>"),
         }
@@ -152,16 +426,25 @@ impl DebugHtml for NodeData {
 }
 
 #[derive(Clone)]
-pub struct Document(Rc<RefCell<SlotMap<NodeId, NodeData>>>);
+pub struct Document {
+    nodes: Rc<RefCell<SlotMap<NodeId, NodeData>>>,
+    properties:
+        Rc<RefCell<SecondaryMap<NodeId, FxHashMap<String, PropertyValue>>>>,
+    listeners: Rc<RefCell<SecondaryMap<NodeId, Vec<Listener>>>>,
+}
 
 impl Document {
     pub fn new() -> Self {
-        Document(Default::default())
+        Document {
+            nodes: Default::default(),
+            properties: Default::default(),
+            listeners: Default::default(),
+        }
     }
 
     fn with_node<U>(id: NodeId, f: impl FnOnce(&NodeData) -> U) -> Option<U> {
         DOCUMENT.with(|d| {
-            let data = d.0.borrow();
+            let data = d.nodes.borrow();
             let data = data.get(id);
             data.map(f)
         })
@@ -172,50 +455,134 @@ impl Document {
         f: impl FnOnce(&mut NodeData) -> U,
     ) -> Option<U> {
         DOCUMENT.with(|d| {
-            let mut data = d.0.borrow_mut();
+            let mut data = d.nodes.borrow_mut();
             let data = data.get_mut(id);
             data.map(f)
         })
     }
 
+    /// Returns the listeners for an event on this node. They are cloned out of
+    /// the document, so that they are free to modify the DOM when called.
+    fn listeners(id: NodeId, name: &str, delegated: bool) -> Vec<ListenerFn> {
+        DOCUMENT.with(|d| {
+            d.listeners
+                .borrow()
+                .get(id)
+                .map(|listeners| {
+                    listeners
+                        .iter()
+                        .filter(|l| l.delegated == delegated && l.name == name)
+                        .map(|l| Rc::clone(&l.cb))
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    fn add_listener(
+        el: &Element,
+        name: Cow<'static, str>,
+        delegated: bool,
+        cb: Box<dyn FnMut(JsValue)>,
+    ) -> Box<dyn FnOnce(&Element)> {
+        let cb: ListenerFn = Rc::new(RefCell::new(cb));
+        DOCUMENT.with(|d| {
+            let mut listeners = d.listeners.borrow_mut();
+            if let Some(entry) = listeners.entry(el.0 .0) {
+                let listeners = entry.or_default();
+                // a delegated handler is stored on the element under its
+                // delegation key, so it replaces any previous one
+                if delegated {
+                    listeners.retain(|l| !(l.delegated && l.name == name));
+                }
+                listeners.push(Listener {
+                    name,
+                    delegated,
+                    cb: Rc::clone(&cb),
+                });
+            }
+        });
+
+        // return the remover
+        Box::new(move |el| {
+            DOCUMENT.with(|d| {
+                if let Some(listeners) =
+                    d.listeners.borrow_mut().get_mut(el.0 .0)
+                {
+                    listeners.retain(|l| !Rc::ptr_eq(&l.cb, &cb));
+                }
+            })
+        })
+    }
+
+    fn is_disabled(id: NodeId) -> bool {
+        let disabled_attr = Document::with_node(id, |node| match &node.ty {
+            NodeType::Element { attrs, .. } => attrs.contains_key("disabled"),
+            _ => false,
+        })
+        .unwrap_or(false);
+        disabled_attr
+            || DOCUMENT.with(|d| {
+                d.properties.borrow().get(id).and_then(|props| {
+                    props
+                        .get("disabled")
+                        .map(|disabled| *disabled == PropertyValue::Bool(true))
+                }) == Some(true)
+            })
+    }
+
     pub fn reset(&self) {
-        self.0.borrow_mut().clear();
+        self.nodes.borrow_mut().clear();
+        self.properties.borrow_mut().clear();
+        self.listeners.borrow_mut().clear();
     }
 
-    fn create_element(&self, tag: &str) -> Element {
-        Element(Node(self.0.borrow_mut().insert(NodeData {
+    fn create_element(&self, tag: &str, self_closing: bool) -> Element {
+        Element(Node(self.nodes.borrow_mut().insert(NodeData {
             parent: None,
             ty: NodeType::Element {
                 tag: tag.to_string().into(),
-                attrs: HashMap::new(),
+                self_closing,
+                attrs: IndexMap::new(),
                 children: Vec::new(),
             },
         })))
     }
 
     fn create_text_node(&self, data: &str) -> Text {
-        Text(Node(self.0.borrow_mut().insert(NodeData {
+        Text(Node(self.nodes.borrow_mut().insert(NodeData {
             parent: None,
             ty: NodeType::Text(data.to_string()),
         })))
     }
 
     fn create_placeholder(&self) -> Placeholder {
-        Placeholder(Node(self.0.borrow_mut().insert(NodeData {
+        Placeholder(Node(self.nodes.borrow_mut().insert(NodeData {
             parent: None,
             ty: NodeType::Placeholder,
         })))
     }
+
+    fn create_raw_html(&self, html: &str) -> Node {
+        Node(self.nodes.borrow_mut().insert(NodeData {
+            parent: None,
+            ty: NodeType::RawHtml(html.to_string()),
+        }))
+    }
 }
 
-// TODO!
 impl DomRenderer for MockDom {
-    type Event = ();
-    type ClassList = ();
-    type CssStyleDeclaration = ();
+    type Event = JsValue;
+    type ClassList = ClassList;
+    type CssStyleDeclaration = CssStyleDeclaration;
+    type PropertyValue = PropertyValue;
 
-    fn set_property(el: &Self::Element, key: &str, value: &JsValue) {
-        todo!()
+    fn set_property(el: &Self::Element, key: &str, value: &PropertyValue) {
+        DOCUMENT.with(|d| {
+            if let Some(entry) = d.properties.borrow_mut().entry(el.0 .0) {
+                entry.or_default().insert(key.to_string(), value.clone());
+            }
+        });
     }
 
     fn add_event_listener(
@@ -223,32 +590,36 @@ impl DomRenderer for MockDom {
         name: &str,
         cb: Box<dyn FnMut(Self::Event)>,
     ) -> Box<dyn FnOnce(&Self::Element)> {
-        todo!()
+        Document::add_listener(el, name.to_owned().into(), false, cb)
     }
 
     fn add_event_listener_delegated(
         el: &Self::Element,
         name: Cow<'static, str>,
-        delegation_key: Cow<'static, str>,
+        _delegation_key: Cow<'static, str>,
         cb: Box<dyn FnMut(Self::Event)>,
     ) -> Box<dyn FnOnce(&Self::Element)> {
-        todo!()
+        Document::add_listener(el, name, true, cb)
     }
 
     fn class_list(el: &Self::Element) -> Self::ClassList {
-        todo!()
+        ClassList(el.clone())
     }
 
     fn add_class(class_list: &Self::ClassList, name: &str) {
-        todo!()
+        class_list.update(|classes| {
+            if !classes.iter().any(|class| class == name) {
+                classes.push(name.to_owned());
+            }
+        });
     }
 
     fn remove_class(class_list: &Self::ClassList, name: &str) {
-        todo!()
+        class_list.update(|classes| classes.retain(|class| class != name));
     }
 
     fn style(el: &Self::Element) -> Self::CssStyleDeclaration {
-        todo!()
+        CssStyleDeclaration(el.clone())
     }
 
     fn set_css_property(
@@ -256,11 +627,19 @@ impl DomRenderer for MockDom {
         name: &str,
         value: &str,
     ) {
-        todo!()
+        style.set_property(name, value);
     }
 
     fn set_inner_html(el: &Self::Element, html: &str) {
-        todo!()
+        // the HTML is not parsed, but kept as a single node that replaces
+        // all the children of the element
+        MockDom::clear_children(el);
+        let node = document().create_raw_html(html);
+        MockDom::insert_node(el, &node, None);
+    }
+
+    fn get_attribute(el: &Self::Element, name: &str) -> Option<String> {
+        el.attribute(name)
     }
 }
 
@@ -283,15 +662,18 @@ pub enum NodeType {
     Text(String),
     Element {
         tag: Cow<'static, str>,
-        attrs: HashMap<String, String>,
+        self_closing: bool,
+        attrs: IndexMap<String, String>,
         children: Vec<Node>,
     },
     Placeholder,
+    /// HTML set with `set_inner_html`, which is rendered as-is.
+    RawHtml(String),
 }
 
 impl Mountable<MockDom> for Node {
     fn unmount(&mut self) {
-        todo!()
+        MockDom::remove(self);
     }
 
     fn mount(&mut self, parent: &Element, marker: Option<&Node>) {
@@ -310,7 +692,7 @@ impl Mountable<MockDom> for Node {
 
 impl Mountable<MockDom> for Text {
     fn unmount(&mut self) {
-        todo!()
+        MockDom::remove(self.as_ref());
     }
 
     fn mount(&mut self, parent: &Element, marker: Option<&Node>) {
@@ -329,7 +711,7 @@ impl Mountable<MockDom> for Text {
 
 impl Mountable<MockDom> for Element {
     fn unmount(&mut self) {
-        todo!()
+        MockDom::remove(self.as_ref());
     }
 
     fn mount(&mut self, parent: &Element, marker: Option<&Node>) {
@@ -348,7 +730,7 @@ impl Mountable<MockDom> for Element {
 
 impl Mountable<MockDom> for Placeholder {
     fn unmount(&mut self) {
-        todo!()
+        MockDom::remove(self.as_ref());
     }
 
     fn mount(&mut self, parent: &Element, marker: Option<&Node>) {
@@ -367,7 +749,8 @@ impl Mountable<MockDom> for Placeholder {
 
 impl<E: ElementType> CreateElement<MockDom> for E {
     fn create_element(&self) -> <MockDom as Renderer>::Element {
-        document().create_element(E::TAG)
+        // custom elements only know their tag at runtime
+        document().create_element(self.tag(), E::SELF_CLOSING)
     }
 }
 
@@ -404,7 +787,7 @@ impl Renderer for MockDom {
     fn remove_attribute(node: &Self::Element, name: &str) {
         Document::with_node_mut(node.0 .0, |node| {
             if let NodeType::Element { ref mut attrs, .. } = node.ty {
-                attrs.remove(name);
+                attrs.shift_remove(name);
             }
         });
     }
@@ -472,12 +855,10 @@ impl Renderer for MockDom {
     }
 
     fn remove(node: &Self::Node) {
-        let parent = Element(Node(
-            Self::get_parent(node)
-                .expect("tried to remove a parentless node")
-                .0,
-        ));
-        Self::remove_node(&parent, node);
+        // like `ChildNode.remove()`, this does nothing if there is no parent
+        if let Some(parent) = Self::get_parent(node) {
+            Self::remove_node(&Element(parent), node);
+        }
     }
 
     fn get_parent(node: &Self::Node) -> Option<Self::Node> {
@@ -488,9 +869,8 @@ impl Renderer for MockDom {
 
     fn first_child(node: &Self::Node) -> Option<Self::Node> {
         Document::with_node(node.0, |node| match &node.ty {
-            NodeType::Text(_) => None,
-            NodeType::Element { children, .. } => children.get(0).cloned(),
-            NodeType::Placeholder => None,
+            NodeType::Element { children, .. } => children.first().cloned(),
+            _ => None,
         })
         .flatten()
     }
@@ -565,13 +945,29 @@ impl CastFrom<Node> for Placeholder {
     }
 }
 
+#[cfg(feature = "reaccy")]
+impl SpawningRenderer for MockDom {
+    type Spawn = crate::spawner::reaccy::Reaccy;
+}
+
+#[cfg(all(feature = "web", not(feature = "reaccy")))]
+impl SpawningRenderer for MockDom {
+    type Spawn = crate::spawner::wasm::Wasm;
+}
+
+#[cfg(all(feature = "tokio", not(feature = "web"), not(feature = "reaccy")))]
+impl SpawningRenderer for MockDom {
+    type Spawn = crate::spawner::tokio::Tokio;
+}
+
 #[cfg(test)]
 mod tests {
-    use super::MockDom;
+    use super::{MockDom, PropertyValue};
     use crate::{
-        html::element,
-        renderer::{mock_dom::node_eq, Renderer},
+        html::{element, event},
+        renderer::{mock_dom::node_eq, DomRenderer, Renderer},
     };
+    use std::{cell::RefCell, rc::Rc};
 
     #[test]
     fn html_debugging_works() {
@@ -688,4 +1084,191 @@ mod tests {
             Some(text.as_ref())
         );
     }
+
+    #[test]
+    fn html_debugging_escapes_and_closes_elements_like_ssr() {
+        let p = MockDom::create_element(element::P);
+        let input = MockDom::create_element(element::Input);
+        MockDom::set_attribute(&input, "value", "\"quoted\"");
+        MockDom::set_attribute(&input, "disabled", "");
+        let text = MockDom::create_text_node("<b>not bold</b>");
+        MockDom::insert_node(&p, text.as_ref(), None);
+        MockDom::insert_node(&p, input.as_ref(), None);
+        assert_eq!(
+            p.to_debug_html(),
+            "<p>&lt;b&gt;not bold&lt;/b&gt;<input \
+             value=\"&quot;quoted&quot;\" disabled></p>"
+        );
+    }
+
+    #[test]
+    fn class_list_updates_class_attribute() {
+        let p = MockDom::create_element(element::P);
+        MockDom::set_attribute(&p, "class", "foo");
+        let class_list = MockDom::class_list(&p);
+        MockDom::add_class(&class_list, "bar");
+        MockDom::add_class(&class_list, "foo");
+        assert!(class_list.contains("bar"));
+        assert_eq!(p.to_debug_html(), "<p class=\"foo bar\"></p>");
+        MockDom::remove_class(&class_list, "foo");
+        MockDom::remove_class(&class_list, "bar");
+        assert!(!class_list.contains("bar"));
+        assert_eq!(p.to_debug_html(), "<p></p>");
+    }
+
+    #[test]
+    fn style_updates_style_attribute() {
+        let p = MockDom::create_element(element::P);
+        let style = MockDom::style(&p);
+        MockDom::set_css_property(&style, "color", "red");
+        MockDom::set_css_property(&style, "height", "40px");
+        MockDom::set_css_property(&style, "color", "blue");
+        assert_eq!(style.get_property_value("color").as_deref(), Some("blue"));
+        assert_eq!(
+            p.to_debug_html(),
+            "<p style=\"color: blue; height: 40px;\"></p>"
+        );
+        MockDom::set_css_property(&style, "color", "");
+        assert_eq!(p.to_debug_html(), "<p style=\"height: 40px;\"></p>");
+    }
+
+    #[test]
+    fn set_property_works() {
+        let input = MockDom::create_element(element::Input);
+        MockDom::set_property(&input, "checked", &true.into());
+        MockDom::set_property(&input, "value", &"foo".into());
+        assert_eq!(input.property("checked"), Some(PropertyValue::Bool(true)));
+        assert_eq!(
+            input.property("value"),
+            Some(PropertyValue::String("foo".into()))
+        );
+        assert_eq!(input.property("indeterminate"), None);
+        // properties are not reflected as attributes
+        assert_eq!(input.to_debug_html(), "<input>");
+    }
+
+    #[test]
+    fn set_inner_html_replaces_children() {
+        let div = MockDom::create_element(element::Div);
+        let text = MockDom::create_text_node("old");
+        MockDom::insert_node(&div, text.as_ref(), None);
+        MockDom::set_inner_html(&div, "<b>new</b>");
+        assert_eq!(div.to_debug_html(), "<div><b>new</b></div>");
+        assert_eq!(MockDom::get_parent(text.as_ref()), None);
+    }
+
+    #[test]
+    fn unmount_removes_node() {
+        use crate::view::Mountable;
+
+        let main = MockDom::create_element(element::Main);
+        let mut p = MockDom::create_element(element::P);
+        let mut text = MockDom::create_text_node("Hello, world!");
+        MockDom::insert_node(&main, p.as_ref(), None);
+        MockDom::insert_node(&main, text.as_ref(), None);
+        p.unmount();
+        assert_eq!(main.to_debug_html(), "<main>Hello, world!</main>");
+        text.unmount();
+        assert_eq!(main.to_debug_html(), "<main></main>");
+        // unmounting a node without a parent does nothing
+        text.unmount();
+    }
+
+    #[test]
+    fn event_listeners_bubble_and_can_be_removed() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let main = MockDom::create_element(element::Main);
+        let button = MockDom::create_element(element::Button);
+        MockDom::insert_node(&main, button.as_ref(), None);
+        let remove_main = MockDom::add_event_listener(
+            &main,
+            "click",
+            Box::new({
+                let log = Rc::clone(&log);
+                move |_| log.borrow_mut().push("main")
+            }),
+        );
+        let remove_button = MockDom::add_event_listener(
+            &button,
+            "click",
+            Box::new({
+                let log = Rc::clone(&log);
+                move |_| log.borrow_mut().push("button")
+            }),
+        );
+
+        button.dispatch_event(event::click);
+        assert_eq!(*log.borrow(), ["button", "main"]);
+
+        // non-bubbling events only fire on their target
+        _ = MockDom::add_event_listener(
+            &main,
+            "focus",
+            Box::new({
+                let log = Rc::clone(&log);
+                move |_| log.borrow_mut().push("focus")
+            }),
+        );
+        button.dispatch_event(event::focus);
+        assert_eq!(log.borrow().len(), 2);
+
+        remove_button(&button);
+        remove_main(&main);
+        button.dispatch_event(event::click);
+        assert_eq!(log.borrow().len(), 2);
+    }
+
+    #[test]
+    fn delegated_listeners_run_after_direct_listeners() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let main = MockDom::create_element(element::Main);
+        let button = MockDom::create_element(element::Button);
+        MockDom::insert_node(&main, button.as_ref(), None);
+        _ = MockDom::add_event_listener_delegated(
+            &button,
+            "click".into(),
+            "$$$click".into(),
+            Box::new({
+                let log = Rc::clone(&log);
+                move |_| log.borrow_mut().push("delegated")
+            }),
+        );
+        _ = MockDom::add_event_listener(
+            &main,
+            "click",
+            Box::new({
+                let log = Rc::clone(&log);
+                move |_| log.borrow_mut().push("direct")
+            }),
+        );
+
+        button.dispatch_event(event::click);
+        assert_eq!(*log.borrow(), ["direct", "delegated"]);
+
+        // delegated handlers are skipped for disabled elements
+        MockDom::set_attribute(&button, "disabled", "");
+        button.dispatch_event(event::click);
+        assert_eq!(*log.borrow(), ["direct", "delegated", "direct"]);
+    }
+
+    #[test]
+    fn on_attribute_adds_listener() {
+        use crate::{
+            html::{
+                attribute::global::OnAttribute,
+                element::{button, HtmlElement},
+            },
+            view::Render,
+        };
+
+        let clicks = Rc::new(RefCell::new(0));
+        let el: HtmlElement<_, _, _, MockDom> = button().on(event::click, {
+            let clicks = Rc::clone(&clicks);
+            move |_| *clicks.borrow_mut() += 1
+        });
+        let el = el.build();
+        el.el.dispatch_event(event::click);
+        el.el.dispatch_event(event::click);
+        assert_eq!(*clicks.borrow(), 2);
+    }
 }
@@ -1,6 +1,5 @@
 use crate::{html::element::CreateElement, spawner::Spawner, view::Mountable};
 use std::borrow::Cow;
-use wasm_bindgen::JsValue;
 
 pub mod dom;
 #[cfg(feature = "testing")]
@@ -99,9 +98,11 @@ pub trait DomRenderer: Renderer {
     type ClassList;
     /// The CSS styles for an element.
     type CssStyleDeclaration;
+    /// A value that can be set as a property of an element.
+    type PropertyValue;
 
     /// Sets a JavaScript object property on a DOM element.
-    fn set_property(el: &Self::Element, key: &str, value: &JsValue);
+    fn set_property(el: &Self::Element, key: &str, value: &Self::PropertyValue);
 
     /// Adds an event listener to an element.
     ///
@@ -143,6 +144,9 @@ pub trait DomRenderer: Renderer {
 
     /// Sets the `innerHTML` of a DOM element, without escaping any values.
     fn set_inner_html(el: &Self::Element, html: &str);
+
+    /// Returns the value of an attribute of a DOM element, if it is set.
+    fn get_attribute(el: &Self::Element, name: &str) -> Option<String>;
 }
 
 /// A renderer that is able to spawn async tasks during rendering.
@@ -91,3 +91,33 @@ pub mod tokio {
         }
     }
 }
+
+#[cfg(feature = "reaccy")]
+pub mod reaccy {
+    use super::Spawner;
+
+    /// Spawns tasks with the [`Executor`](tachy_reaccy::spawn::Executor) used
+    /// by the reactive system, so that views and effects share one runtime.
+    #[derive(Debug, Copy, Clone)]
+    pub struct Reaccy;
+
+    impl Spawner for Reaccy {
+        fn spawn<Fut>(fut: Fut)
+        where
+            Fut: futures::Future + Send + Sync + 'static,
+        {
+            tachy_reaccy::spawn::spawn(async move {
+                fut.await;
+            });
+        }
+
+        fn spawn_local<Fut>(fut: Fut)
+        where
+            Fut: futures::Future + 'static,
+        {
+            tachy_reaccy::spawn::spawn_local(async move {
+                fut.await;
+            });
+        }
+    }
+}
@@ -10,7 +10,9 @@ use crate::{
     },
 };
 use std::mem;
-use tachy_reaccy::{async_signal::ScopedFuture, render_effect::RenderEffect};
+use tachy_reaccy::{
+    async_signal::ScopedFuture, render_effect::RenderEffect, Owner,
+};
 
 mod class;
 pub mod node_ref;
@@ -46,14 +48,16 @@ where
 
     #[track_caller]
     fn build(mut self) -> Self::State {
-        RenderEffect::new(move |prev| {
-            let value = self();
-            if let Some(mut state) = prev {
-                value.rebuild(&mut state);
-                state
-            } else {
-                value.build()
-            }
+        Owner::with_child(|| {
+            RenderEffect::new(move |prev| {
+                let value = self();
+                if let Some(mut state) = prev {
+                    value.rebuild(&mut state);
+                    state
+                } else {
+                    value.build()
+                }
+            })
         })
         .into()
     }
@@ -153,9 +157,13 @@ where
 {
     const MIN_LENGTH: usize = 0;
 
+    // on the server, each reactive part of the view is rendered under its own
+    // owner, like the owner of its render effect on the client
     fn to_html_with_buf(mut self, buf: &mut String, position: &mut Position) {
-        let value = self();
-        value.to_html_with_buf(buf, position)
+        Owner::with_child(|| {
+            let value = self();
+            value.to_html_with_buf(buf, position)
+        })
     }
 
     fn to_html_async_with_buf<const OUT_OF_ORDER: bool>(
@@ -165,8 +173,10 @@ where
     ) where
         Self: Sized,
     {
-        let value = self();
-        value.to_html_async_with_buf::<OUT_OF_ORDER>(buf, position);
+        Owner::with_child(|| {
+            let value = self();
+            value.to_html_async_with_buf::<OUT_OF_ORDER>(buf, position);
+        })
     }
 
     fn hydrate<const FROM_SERVER: bool>(
@@ -176,14 +186,16 @@ where
     ) -> Self::State {
         let cursor = cursor.clone();
         let position = position.clone();
-        RenderEffect::new(move |prev| {
-            let value = self();
-            if let Some(mut state) = prev {
-                value.rebuild(&mut state);
-                state
-            } else {
-                value.hydrate::<FROM_SERVER>(&cursor, &position)
-            }
+        Owner::with_child(|| {
+            RenderEffect::new(move |prev| {
+                let value = self();
+                if let Some(mut state) = prev {
+                    value.rebuild(&mut state);
+                    state
+                } else {
+                    value.hydrate::<FROM_SERVER>(&cursor, &position)
+                }
+            })
         })
         .into()
     }
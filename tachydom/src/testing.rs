@@ -0,0 +1,471 @@
+//! Utilities for testing views natively, in the style of
+//! [Testing Library](https://testing-library.com/).
+//!
+//! [`render`] builds a view into the [`MockDom`] and returns a [`Screen`],
+//! which can be used to find rendered elements the way a user would (by their
+//! text, their role, or a test ID) and to interact with them.
+//!
+//! ```
+//! use tachydom::{
+//!     html::element::{button, ElementChild, HtmlElement},
+//!     renderer::mock_dom::MockDom,
+//!     testing::render,
+//! };
+//!
+//! let view: HtmlElement<_, _, _, MockDom> = button().child("Click me");
+//! let screen = render(view);
+//! let button = screen.get_by_role("button");
+//! assert_eq!(button.text_content(), "Click me");
+//! assert_eq!(screen.to_html(), "<button>Click me</button>");
+//! ```
+
+use crate::{
+    html::{element::Div, event},
+    renderer::{
+        mock_dom::{Element, MockDom, PropertyValue, Text},
+        CastFrom, DomRenderer, Renderer,
+    },
+    view::{Mountable, Render},
+};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// How long [`Screen::wait_for`] waits before giving up.
+pub const WAIT_FOR_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Builds the view and mounts it into a new container element.
+///
+/// The view is unmounted when the returned [`Screen`] is dropped.
+pub fn render<V>(view: V) -> Screen<V::State>
+where
+    V: Render<MockDom>,
+{
+    let container = MockDom::create_element(Div);
+    let mut state = view.build();
+    state.mount(&container, None);
+    Screen { container, state }
+}
+
+/// A handle to a rendered view, which can be used to query and interact with it.
+pub struct Screen<S>
+where
+    S: Mountable<MockDom>,
+{
+    container: Element,
+    state: S,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Query<'a> {
+    Text(&'a str),
+    Role(&'a str),
+    TestId(&'a str),
+}
+
+impl fmt::Display for Query<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Query::Text(text) => write!(f, "with the text {text:?}"),
+            Query::Role(role) => write!(f, "with the role {role:?}"),
+            Query::TestId(id) => write!(f, "with data-testid {id:?}"),
+        }
+    }
+}
+
+impl Query<'_> {
+    fn matches(&self, el: &Element) -> bool {
+        match self {
+            Query::Text(text) => own_text(el) == normalize(text),
+            Query::Role(role) => {
+                el.attribute("role")
+                    .as_deref()
+                    .or_else(|| implicit_role(el))
+                    == Some(*role)
+            }
+            Query::TestId(id) => {
+                el.attribute("data-testid").as_deref() == Some(*id)
+            }
+        }
+    }
+}
+
+impl<S> Screen<S>
+where
+    S: Mountable<MockDom>,
+{
+    /// The element into which the view has been mounted.
+    pub fn container(&self) -> &Element {
+        &self.container
+    }
+
+    /// The view state that was created by building the view.
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Renders the current contents of the container to HTML.
+    pub fn to_html(&self) -> String {
+        let html = self.container.to_debug_html();
+        html.strip_prefix("<div>")
+            .and_then(|html| html.strip_suffix("</div>"))
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Returns all the elements whose own text matches the given text, after
+    /// trimming and collapsing whitespace.
+    pub fn get_all_by_text(&self, text: &str) -> Vec<Element> {
+        self.query_all(Query::Text(text))
+    }
+
+    /// Returns the element whose own text matches the given text, if any.
+    ///
+    /// ## Panics
+    /// Panics if more than one element matches.
+    #[track_caller]
+    pub fn query_by_text(&self, text: &str) -> Option<Element> {
+        self.query(Query::Text(text))
+    }
+
+    /// Returns the element whose own text matches the given text.
+    ///
+    /// ## Panics
+    /// Panics if no element, or more than one element, matches.
+    #[track_caller]
+    pub fn get_by_text(&self, text: &str) -> Element {
+        self.get(Query::Text(text))
+    }
+
+    /// Returns all the elements with the given ARIA role, either set explicitly
+    /// with the `role` attribute or implied by the element.
+    pub fn get_all_by_role(&self, role: &str) -> Vec<Element> {
+        self.query_all(Query::Role(role))
+    }
+
+    /// Returns the element with the given ARIA role, if any.
+    ///
+    /// ## Panics
+    /// Panics if more than one element matches.
+    #[track_caller]
+    pub fn query_by_role(&self, role: &str) -> Option<Element> {
+        self.query(Query::Role(role))
+    }
+
+    /// Returns the element with the given ARIA role.
+    ///
+    /// ## Panics
+    /// Panics if no element, or more than one element, matches.
+    #[track_caller]
+    pub fn get_by_role(&self, role: &str) -> Element {
+        self.get(Query::Role(role))
+    }
+
+    /// Returns all the elements with the given `data-testid` attribute.
+    pub fn get_all_by_test_id(&self, id: &str) -> Vec<Element> {
+        self.query_all(Query::TestId(id))
+    }
+
+    /// Returns the element with the given `data-testid` attribute, if any.
+    ///
+    /// ## Panics
+    /// Panics if more than one element matches.
+    #[track_caller]
+    pub fn query_by_test_id(&self, id: &str) -> Option<Element> {
+        self.query(Query::TestId(id))
+    }
+
+    /// Returns the element with the given `data-testid` attribute.
+    ///
+    /// ## Panics
+    /// Panics if no element, or more than one element, matches.
+    #[track_caller]
+    pub fn get_by_test_id(&self, id: &str) -> Element {
+        self.get(Query::TestId(id))
+    }
+
+    /// Dispatches a `click` event on the element.
+    pub fn fire_click(&self, el: &Element) {
+        el.dispatch_event(event::click);
+    }
+
+    /// Types the text into an input, one character at a time.
+    ///
+    /// For each character, this updates the `value` property of the element
+    /// and dispatches an `input` event. Event objects are not available outside
+    /// the browser, so listeners should read the new value from the element
+    /// itself rather than from the event.
+    pub fn type_into(&self, el: &Element, text: &str) {
+        let mut value = el
+            .property("value")
+            .and_then(|value| match value {
+                PropertyValue::String(value) => Some(value),
+                _ => None,
+            })
+            .or_else(|| el.attribute("value"))
+            .unwrap_or_default();
+        for c in text.chars() {
+            value.push(c);
+            MockDom::set_property(el, "value", &value.as_str().into());
+            el.dispatch_event(event::input);
+        }
+    }
+
+    /// Waits until `check` returns `Some(_)`, and returns its value.
+    ///
+    /// Between each attempt, this yields to the async runtime, so that pending
+    /// effects and other async tasks are able to run and update the view.
+    ///
+    /// ## Panics
+    /// Panics if `check` has not succeeded within [`WAIT_FOR_TIMEOUT`].
+    pub async fn wait_for<T>(
+        &self,
+        mut check: impl FnMut(&Self) -> Option<T>,
+    ) -> T {
+        let deadline = Instant::now() + WAIT_FOR_TIMEOUT;
+        loop {
+            if let Some(value) = check(self) {
+                return value;
+            }
+            if Instant::now() >= deadline {
+                panic!(
+                    "timed out after {WAIT_FOR_TIMEOUT:?} waiting for the \
+                     view to update:\n{}",
+                    self.to_html()
+                );
+            }
+            YieldNow(false).await;
+        }
+    }
+
+    fn query_all(&self, query: Query) -> Vec<Element> {
+        let mut found = Vec::new();
+        descendants(&self.container, &mut |el| {
+            if query.matches(el) {
+                found.push(el.clone());
+            }
+        });
+        found
+    }
+
+    #[track_caller]
+    fn query(&self, query: Query) -> Option<Element> {
+        let mut found = self.query_all(query);
+        if found.len() > 1 {
+            panic!(
+                "found {} elements {query}, but expected at most one:\n{}",
+                found.len(),
+                self.to_html()
+            );
+        }
+        found.pop()
+    }
+
+    #[track_caller]
+    fn get(&self, query: Query) -> Element {
+        self.query(query).unwrap_or_else(|| {
+            panic!("unable to find an element {query} in:\n{}", self.to_html())
+        })
+    }
+}
+
+impl<S> Drop for Screen<S>
+where
+    S: Mountable<MockDom>,
+{
+    fn drop(&mut self) {
+        self.state.unmount();
+    }
+}
+
+fn descendants(el: &Element, fun: &mut impl FnMut(&Element)) {
+    for child in el.as_ref().child_nodes() {
+        if let Some(child) = Element::cast_from(child) {
+            fun(&child);
+            descendants(&child, fun);
+        }
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The text of the element's own text nodes, ignoring its descendant elements.
+fn own_text(el: &Element) -> String {
+    let text = el
+        .as_ref()
+        .child_nodes()
+        .into_iter()
+        .filter_map(Text::cast_from)
+        .map(|text| text.as_ref().text_content())
+        .collect::<String>();
+    normalize(&text)
+}
+
+fn implicit_role(el: &Element) -> Option<&'static str> {
+    let role = match el.tag_name().as_str() {
+        "a" | "area" if el.attribute("href").is_some() => "link",
+        "article" => "article",
+        "aside" => "complementary",
+        "button" => "button",
+        "dialog" => "dialog",
+        "footer" => "contentinfo",
+        "form" => "form",
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => "heading",
+        "header" => "banner",
+        "hr" => "separator",
+        "img" => "img",
+        "input" => match el.attribute("type").as_deref() {
+            Some("button" | "image" | "reset" | "submit") => "button",
+            Some("checkbox") => "checkbox",
+            Some("number") => "spinbutton",
+            Some("radio") => "radio",
+            Some("range") => "slider",
+            Some("search") => "searchbox",
+            _ => "textbox",
+        },
+        "li" => "listitem",
+        "main" => "main",
+        "nav" => "navigation",
+        "ol" | "ul" => "list",
+        "option" => "option",
+        "progress" => "progressbar",
+        "section" => "region",
+        "select" => "combobox",
+        "table" => "table",
+        "tbody" | "tfoot" | "thead" => "rowgroup",
+        "td" => "cell",
+        "textarea" => "textbox",
+        "th" => "columnheader",
+        "tr" => "row",
+        _ => return None,
+    };
+    Some(role)
+}
+
+/// Yields to the async runtime once, so that other tasks can run.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::{
+        html::{
+            attribute::{custom::CustomAttribute, global::OnAttribute},
+            element::{
+                button, h1, input, li, main, ul, ElementChild, HtmlElement,
+            },
+            event,
+        },
+        renderer::mock_dom::MockDom,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn queries_find_elements() {
+        let view: HtmlElement<_, _, _, MockDom> = main().child((
+            h1().child("Todos"),
+            ul().attr("data-testid", "list").child((
+                li().child("  Buy   milk "),
+                li().child("Walk the dog"),
+            )),
+            button().attr("role", "switch").child("Dark mode"),
+        ));
+        let screen = render(view);
+
+        assert_eq!(screen.get_by_role("heading").text_content(), "Todos");
+        assert_eq!(screen.get_all_by_role("listitem").len(), 2);
+        assert_eq!(screen.get_by_text("Buy milk").tag_name(), "li");
+        assert_eq!(screen.get_by_test_id("list").tag_name(), "ul");
+        assert_eq!(screen.get_by_role("switch").text_content(), "Dark mode");
+        assert!(screen.query_by_role("button").is_none());
+        assert!(screen.query_by_text("Walk").is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "found 2 elements with the role \"listitem\"")]
+    fn get_by_panics_on_multiple_matches() {
+        let view: HtmlElement<_, _, _, MockDom> =
+            ul().child((li().child("a"), li().child("b")));
+        let screen = render(view);
+        screen.get_by_role("listitem");
+    }
+
+    #[test]
+    fn fire_click_and_type_into_dispatch_events() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let view: HtmlElement<_, _, _, MockDom> = main().child((
+            button().on(event::click, {
+                let log = Rc::clone(&log);
+                move |_| log.borrow_mut().push("click")
+            }),
+            input().r#type("text").on(event::input, {
+                let log = Rc::clone(&log);
+                move |_| log.borrow_mut().push("input")
+            }),
+        ));
+        let screen = render(view);
+
+        screen.fire_click(&screen.get_by_role("button"));
+        let textbox = screen.get_by_role("textbox");
+        screen.type_into(&textbox, "hi");
+        assert_eq!(*log.borrow(), ["click", "input", "input"]);
+        assert_eq!(textbox.property("value"), Some("hi".into()));
+    }
+
+    #[test]
+    fn dropping_screen_unmounts_view() {
+        let view: HtmlElement<_, _, _, MockDom> = main().child("Hello");
+        let screen = render(view);
+        let container = screen.container().clone();
+        assert_eq!(container.to_debug_html(), "<div><main>Hello</main></div>");
+        drop(screen);
+        assert_eq!(container.to_debug_html(), "<div></div>");
+    }
+
+    #[cfg(all(feature = "reaccy", feature = "tokio"))]
+    #[tokio::test]
+    async fn wait_for_runs_pending_effects() {
+        use tachy_reaccy::prelude::*;
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                let count = RwSignal::new(0);
+                let view: HtmlElement<_, _, _, MockDom> = button()
+                    .on(event::click, move |_| count.update(|n| *n += 1))
+                    .child(move || format!("Clicked {} times", count.get()));
+                let screen = render(view);
+                assert_eq!(
+                    screen.to_html(),
+                    "<button>Clicked 0 times</button>"
+                );
+
+                screen.fire_click(&screen.get_by_role("button"));
+                screen.fire_click(&screen.get_by_role("button"));
+                let button = screen
+                    .wait_for(|s| s.query_by_text("Clicked 2 times"))
+                    .await;
+                assert_eq!(button.tag_name(), "button");
+            })
+            .await;
+    }
+}
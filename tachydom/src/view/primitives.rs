@@ -2,6 +2,7 @@ use super::{
     InfallibleRender, Mountable, Position, PositionState, Render, RenderHtml,
 };
 use crate::{
+    html::escape::EscapeText,
     hydration::Cursor,
     renderer::{CastFrom, Renderer},
     view::ToTemplate,
@@ -77,7 +78,7 @@ macro_rules! render_primitive {
 					if matches!(position, Position::NextChildAfterText) {
 						buf.push_str("<!>")
 					}
-					write!(buf, "{}", self);
+					_ = write!(EscapeText(buf), "{}", self);
 					*position = Position::NextChildAfterText;
 				}
 
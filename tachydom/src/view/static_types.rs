@@ -6,6 +6,7 @@ use crate::{
     html::{
         attribute::{Attribute, AttributeKey, AttributeValue},
         class::IntoClass,
+        escape::{escape_attr, escape_text},
         style::IntoStyle,
     },
     hydration::Cursor,
@@ -59,7 +60,7 @@ where
         buf.push(' ');
         buf.push_str(K::KEY);
         buf.push_str("=\"");
-        buf.push_str(V);
+        buf.push_str(&escape_attr(V));
         buf.push('"');
     }
 }
@@ -140,7 +141,7 @@ where
         if matches!(position, Position::NextChildAfterText) {
             buf.push_str("<!>")
         }
-        buf.push_str(V);
+        buf.push_str(&escape_text(V));
         *position = Position::NextChildAfterText;
     }
 
@@ -177,7 +178,7 @@ impl<const V: &'static str> ToTemplate for Static<V> {
         if matches!(*position, Position::NextChildAfterText) {
             buf.push_str("<!>")
         }
-        buf.push_str(V);
+        buf.push_str(&escape_text(V));
         *position = Position::NextChildAfterText;
     }
 }
@@ -1,6 +1,6 @@
 pub mod prelude {
-    pub use tachy_maccy::{component, view};
-    pub use tachy_reaccy::prelude::*;
+    pub use tachy_maccy::{component, island, server, view};
+    pub use tachy_reaccy::{prelude::*, server_fn::ServerFnError};
     pub use tachydom::prelude::*;
 }
 
@@ -10,8 +10,13 @@ pub mod children; // TODO fix children
 pub mod component;
 pub mod show;
 
+#[doc(hidden)]
+pub use serde;
+#[doc(hidden)]
+pub use serde_json;
 pub use tachy_maccy::*;
 pub use tachy_reaccy;
+pub use tachy_reaccy_macro::{JsonPatch, Patch, Store};
 pub use tachydom;
 #[doc(hidden)]
 pub use typed_builder;
@@ -0,0 +1,233 @@
+use futures::{executor::block_on, StreamExt};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+use tachys::{
+    serde_json,
+    tachy_reaccy::{
+        prelude::*,
+        shared_context::{
+            SerializationError, SerializedDataId, SharedContext,
+            SsrSharedContext,
+        },
+        testing::{self, run_until_stalled},
+        PinnedFuture, PinnedStream, Root,
+    },
+    tachydom::{
+        html::element::{main, p, Div, ElementChild},
+        renderer::{mock_dom::MockDom, Renderer},
+        view::{either::Either, Mountable, Render, RenderHtml},
+    },
+};
+
+type Counts = Arc<Mutex<Vec<ArcRwSignal<i32>>>>;
+
+fn counter(
+    counts: &Counts,
+    on_server: bool,
+    server_value: i32,
+) -> impl RenderHtml<MockDom> {
+    let count = ArcRwSignal::new_serialized(0);
+    if on_server {
+        count.set(server_value);
+    } else {
+        // a memo that only exists on the client should not change the IDs of
+        // the data that is created after it
+        let doubled = Memo::new({
+            let count = count.clone();
+            move |_| count.get() * 2
+        });
+        assert_eq!(doubled.get_untracked(), server_value * 2);
+    }
+    counts.lock().unwrap().push(count.clone());
+    p().child(move || count.get().to_string())
+}
+
+fn app(counts: Counts, on_server: bool) -> impl RenderHtml<MockDom> {
+    let other = Arc::clone(&counts);
+    main().child((
+        move || counter(&counts, on_server, 5),
+        move || counter(&other, on_server, 7),
+    ))
+}
+
+/// Serves the data that the server wrote into the page to the client, as a
+/// hydrating shared context would in the browser.
+#[derive(Debug, Default)]
+struct ClientData {
+    resolved: HashMap<String, String>,
+    issued: Mutex<HashSet<SerializedDataId>>,
+}
+
+impl ClientData {
+    /// Reads the `__RESOLVED_RESOURCES["<id>"] = <value>;` entries written by
+    /// [`SsrSharedContext`].
+    fn from_chunks(chunks: &[String]) -> Self {
+        let resolved = chunks
+            .iter()
+            .flat_map(|chunk| chunk.split(';'))
+            .filter_map(|entry| {
+                let entry = entry.strip_prefix("__RESOLVED_RESOURCES[\"")?;
+                let (id, value) = entry.split_once("\"] = ")?;
+                let value = serde_json::from_str(value).unwrap();
+                Some((id.to_string(), value))
+            })
+            .collect();
+        Self {
+            resolved,
+            ..Default::default()
+        }
+    }
+}
+
+impl SharedContext for ClientData {
+    fn next_id(&self) -> SerializedDataId {
+        let id = SerializedDataId::next();
+        assert!(
+            self.issued.lock().unwrap().insert(id.clone()),
+            "{id} was given out more than once"
+        );
+        id
+    }
+
+    fn write_async(
+        &self,
+        _id: SerializedDataId,
+        _fut: PinnedFuture<Result<String, SerializationError>>,
+    ) {
+    }
+
+    fn write_stream(
+        &self,
+        _id: SerializedDataId,
+        _stream: PinnedStream<Result<String, SerializationError>>,
+    ) {
+    }
+
+    fn read_data(
+        &self,
+        id: &SerializedDataId,
+    ) -> Option<Result<String, SerializationError>> {
+        self.resolved.get(&id.to_string()).cloned().map(Ok)
+    }
+
+    fn await_data(
+        &self,
+        _id: &SerializedDataId,
+    ) -> Option<PinnedFuture<Result<String, SerializationError>>> {
+        None
+    }
+
+    fn read_stream(
+        &self,
+        _id: &SerializedDataId,
+    ) -> Option<PinnedStream<Result<String, SerializationError>>> {
+        None
+    }
+
+    fn pending_data(&self) -> Option<PinnedStream<String>> {
+        None
+    }
+}
+
+#[test]
+fn server_state_is_restored_while_hydrating() {
+    testing::install();
+    let shared_context = Arc::new(SsrSharedContext::new());
+    let Root(_owner, html) = Root::new_with_shared_context(
+        || app(Default::default(), true).to_html(),
+        Some(shared_context.clone()),
+    );
+    assert_eq!(html, "<main><p>5</p><p>7</p></main>");
+    let chunks =
+        block_on(shared_context.pending_data().unwrap().collect::<Vec<_>>());
+
+    // the page as it arrives in the browser
+    let container = MockDom::create_element(Div);
+    let page = main().child((p().child("5"), p().child("7")));
+    let mut page_state = Render::<MockDom>::build(page);
+    page_state.mount(&container, None);
+
+    let counts = Counts::default();
+    let Root(_owner, _state) = Root::new_with_shared_context(
+        || app(Arc::clone(&counts), false).hydrate_from::<true>(&container),
+        Some(Arc::new(ClientData::from_chunks(&chunks))),
+    );
+    let counts = counts.lock().unwrap().clone();
+    assert_eq!(counts[0].get_untracked(), 5);
+    assert_eq!(counts[1].get_untracked(), 7);
+
+    counts[1].set(8);
+    run_until_stalled();
+    assert_eq!(
+        container.to_debug_html(),
+        "<div><main><p>5</p><p>8</p></main></div>"
+    );
+}
+
+// the first counter is shown in one branch of an `Either`, which also creates
+// some data on the client that the server never sends
+fn branching_app(
+    counts: Counts,
+    on_server: bool,
+    show: ArcRwSignal<bool>,
+) -> impl RenderHtml<MockDom> {
+    let other = Arc::clone(&counts);
+    main().child((
+        move || {
+            if show.get() {
+                let counter = counter(&counts, on_server, 5);
+                if !on_server {
+                    _ = ArcRwSignal::new_serialized(0);
+                }
+                Either::Left(counter)
+            } else {
+                Either::Right(p().child("hidden"))
+            }
+        },
+        move || counter(&other, on_server, 7),
+    ))
+}
+
+#[test]
+fn data_created_in_one_branch_does_not_shift_the_ids_of_the_next() {
+    testing::install();
+    let shared_context = Arc::new(SsrSharedContext::new());
+    let Root(_owner, html) = Root::new_with_shared_context(
+        || {
+            branching_app(Default::default(), true, ArcRwSignal::new(true))
+                .to_html()
+        },
+        Some(shared_context.clone()),
+    );
+    assert_eq!(html, "<main><p>5</p><!><p>7</p></main>");
+    let chunks =
+        block_on(shared_context.pending_data().unwrap().collect::<Vec<_>>());
+
+    let container = MockDom::create_element(Div);
+    let page =
+        main().child((Either::<_, &str>::Left(p().child("5")), p().child("7")));
+    let mut page_state = Render::<MockDom>::build(page);
+    page_state.mount(&container, None);
+
+    let counts = Counts::default();
+    let show = ArcRwSignal::new(true);
+    let Root(_owner, _state) = Root::new_with_shared_context(
+        || {
+            branching_app(Arc::clone(&counts), false, show.clone())
+                .hydrate_from::<true>(&container)
+        },
+        Some(Arc::new(ClientData::from_chunks(&chunks))),
+    );
+    let counts = counts.lock().unwrap().clone();
+    assert_eq!(counts[0].get_untracked(), 5);
+    assert_eq!(counts[1].get_untracked(), 7);
+
+    show.set(false);
+    run_until_stalled();
+    assert_eq!(
+        container.to_debug_html(),
+        "<div><main><p>hidden</p><!><p>7</p></main></div>"
+    );
+}
@@ -0,0 +1,75 @@
+use tachys::{
+    prelude::*,
+    tachy_reaccy::{testing, Root},
+    tachydom::{
+        html::{
+            element::{button, main, Div},
+            event,
+        },
+        islands::IslandRegistry,
+        renderer::{
+            mock_dom::{Element, MockDom},
+            CastFrom,
+        },
+    },
+};
+
+/// A counter that starts at `initial`.
+#[island]
+fn Counter(initial: i32) -> impl RenderHtml<MockDom> {
+    let count = RwSignal::new(initial);
+    button()
+        .on(event::click, move |_| count.update(|n| *n += 1))
+        .child(move || count.get().to_string())
+}
+
+fn find_button(parent: &Element) -> Option<Element> {
+    let mut next = MockDom::first_child(parent.as_ref());
+    while let Some(node) = next {
+        next = MockDom::next_sibling(&node);
+        if let Some(el) = Element::cast_from(node) {
+            if el.tag_name() == "button" {
+                return Some(el);
+            }
+            if let Some(found) = find_button(&el) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+fn page() -> impl RenderHtml<MockDom> {
+    main().child(Counter(CounterProps::builder().initial(3).build()))
+}
+
+#[test]
+fn islands_are_hydrated_from_their_props() {
+    testing::install();
+
+    assert_eq!(
+        page().to_html(),
+        "<main><tachy-island data-island=\"Counter\" \
+         data-props=\"{&quot;initial&quot;:3}\"><button>3</button></\
+         tachy-island></main>"
+    );
+
+    // the page as it arrives in the browser
+    let container = MockDom::create_element(Div);
+    let mut page_state = Root::new(|| page().build()).into_value();
+    page_state.mount(&container, None);
+
+    let registry = IslandRegistry::new().register("Counter", _island_Counter);
+    let Root(_owner, _islands) = Root::new(|| registry.hydrate(&container));
+
+    find_button(&container)
+        .unwrap()
+        .dispatch_event(event::click);
+    testing::run_until_stalled();
+    assert_eq!(
+        container.to_debug_html(),
+        "<div><main><tachy-island data-island=\"Counter\" \
+         data-props=\"{&quot;initial&quot;:3}\"><button>4</button></\
+         tachy-island></main></div>"
+    );
+}
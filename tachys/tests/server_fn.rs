@@ -0,0 +1,129 @@
+//! Server functions defined with `#[server]`. Run these both with and without
+//! the `ssr` feature, which builds them as the server and as the client would.
+
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tachys::{
+    prelude::*,
+    tachy_reaccy::{
+        server_fn::{
+            self, set_client, Client, Request, Response, ServerFnRegistry,
+        },
+        PinnedFuture,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Story {
+    id: usize,
+    title: String,
+}
+
+#[server(encoding = "Str")]
+pub async fn double(n: i32) -> Result<i32, ServerFnError> {
+    Ok(n * 2)
+}
+
+#[server(prefix = "/stories", endpoint = "get")]
+pub async fn get_story(id: usize) -> Result<Story, ServerFnError> {
+    if id == 0 {
+        Err(ServerFnError::new("there is no story 0"))
+    } else {
+        Ok(Story {
+            id,
+            title: format!("Story {id}"),
+        })
+    }
+}
+
+fn registry() -> &'static ServerFnRegistry {
+    static REGISTRY: OnceLock<ServerFnRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        ServerFnRegistry::new()
+            .register::<Double>()
+            .register::<GetStory>()
+    })
+}
+
+// stands in for an HTTP client and server, by handing requests to the
+// server in the same process
+struct InProcess;
+
+impl Client for InProcess {
+    fn send(
+        &self,
+        req: Request,
+    ) -> PinnedFuture<Result<Response, ServerFnError>> {
+        #[cfg(feature = "ssr")]
+        let res = tokio::spawn(registry().handle(req));
+        #[cfg(not(feature = "ssr"))]
+        let res = tokio::spawn(server_build(req));
+        Box::pin(async move {
+            res.await.map_err(|e| ServerFnError::Request(e.to_string()))
+        })
+    }
+}
+
+// without `ssr`, as in a client build, the bodies of the server functions are
+// not compiled, so this answers requests as a server build of this file would
+#[cfg(not(feature = "ssr"))]
+async fn server_build(req: Request) -> Response {
+    let (status, body) = match req.path.as_str() {
+        "/api/double" => {
+            let Double { n } = req.body.parse().unwrap();
+            (200, (n * 2).to_string())
+        }
+        "/stories/get" => match serde_json::from_str(&req.body).unwrap() {
+            GetStory { id: 0 } => (
+                500,
+                serde_json::to_string(&ServerFnError::new(
+                    "there is no story 0",
+                ))
+                .unwrap(),
+            ),
+            GetStory { id } => (
+                200,
+                serde_json::to_string(&Story {
+                    id,
+                    title: format!("Story {id}"),
+                })
+                .unwrap(),
+            ),
+        },
+        _ => unreachable!(),
+    };
+    Response { status, body }
+}
+
+#[tokio::test]
+async fn server_fns_are_called_through_the_client() {
+    _ = set_client(InProcess);
+
+    // with `ssr`, the function runs its body directly
+    assert_eq!(double(21).await, Ok(42));
+    assert_eq!(server_fn::call(Double { n: 21 }).await, Ok(42));
+    assert_eq!(
+        get_story(1).await,
+        Ok(Story {
+            id: 1,
+            title: "Story 1".to_string()
+        })
+    );
+    assert_eq!(
+        server_fn::call(GetStory { id: 1 }).await.unwrap().title,
+        "Story 1"
+    );
+    assert_eq!(
+        get_story(0).await,
+        Err(ServerFnError::ServerError(
+            "there is no story 0".to_string()
+        ))
+    );
+}
+
+#[test]
+fn server_fns_are_registered_at_their_paths() {
+    let mut paths = registry().paths().collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(paths, ["/api/double", "/stories/get"]);
+}
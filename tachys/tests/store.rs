@@ -0,0 +1,680 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+use tachys::{
+    tachy_reaccy::{
+        prelude::*,
+        store::{
+            JsonPatchError, Store, StoreChange, StoreFieldIndex, StoreFieldMap,
+        },
+        testing::{self, run_until_stalled},
+    },
+    JsonPatch, Patch, Store,
+};
+
+#[derive(
+    Store, Patch, JsonPatch, Debug, Clone, PartialEq, Serialize, Deserialize,
+)]
+struct Author {
+    name: String,
+}
+
+#[derive(
+    Store, Patch, JsonPatch, Debug, Clone, PartialEq, Serialize, Deserialize,
+)]
+struct Post {
+    title: String,
+    author: Author,
+}
+
+#[derive(
+    Store, Patch, JsonPatch, Debug, Clone, PartialEq, Serialize, Deserialize,
+)]
+enum Page<T> {
+    Loading,
+    Loaded(T),
+    Failed { error: String },
+}
+
+#[derive(
+    Store, Patch, JsonPatch, Debug, Clone, PartialEq, Serialize, Deserialize,
+)]
+struct State {
+    page: Page<Post>,
+}
+
+#[derive(
+    Store, Patch, JsonPatch, Debug, Clone, PartialEq, Serialize, Deserialize,
+)]
+struct Feed {
+    posts: Vec<Post>,
+}
+
+#[derive(Store, Patch, Debug, Clone, PartialEq)]
+struct Library {
+    posts: HashMap<usize, Post>,
+    tags: BTreeMap<String, usize>,
+}
+
+fn post() -> Post {
+    Post {
+        title: "Stores".to_string(),
+        author: Author {
+            name: "Greg".to_string(),
+        },
+    }
+}
+
+#[test]
+fn nested_stores_compose_subfields() {
+    let store = Store::new(post());
+    assert_eq!(store.author().name().get_untracked(), "Greg");
+
+    store.author().name().set("Ada".to_string());
+    assert_eq!(store.get_untracked().author.name, "Ada");
+}
+
+#[test]
+fn enum_fields_are_only_accessible_in_their_variant() {
+    let store = Store::new(State {
+        page: Page::Loading,
+    });
+    assert!(store.page().is_loading());
+    assert!(store.page().loaded_0().is_none());
+    assert!(store.page().failed_error().is_none());
+
+    store.page().set(Page::Loaded(post()));
+    assert!(!store.page().is_loading());
+    let post = store.page().loaded_0().unwrap();
+    assert_eq!(post.author().name().get_untracked(), "Greg");
+
+    store.page().set(Page::Failed {
+        error: "not found".to_string(),
+    });
+    assert_eq!(
+        store
+            .page()
+            .failed_error()
+            .map(|error| error.get_untracked()),
+        Some("not found".to_string())
+    );
+}
+
+#[test]
+fn enum_accessors_track_variant_changes() {
+    testing::install();
+
+    let store = Store::new(State {
+        page: Page::Loading,
+    });
+    let title = Arc::new(Mutex::new(None));
+    let effect = Effect::new({
+        let title = Arc::clone(&title);
+        move |_| {
+            *title.lock().unwrap() = store
+                .page()
+                .loaded_0()
+                .map(|post| post.title().get_untracked());
+        }
+    });
+    run_until_stalled();
+    assert_eq!(*title.lock().unwrap(), None);
+
+    store.page().set(Page::Loaded(post()));
+    run_until_stalled();
+    assert_eq!(title.lock().unwrap().as_deref(), Some("Stores"));
+    assert_eq!(testing::run_count(&effect), 2);
+}
+
+#[test]
+fn fields_of_a_previous_variant_read_as_none() {
+    testing::install();
+
+    let store = Store::new(State {
+        page: Page::Loaded(post()),
+    });
+    let loaded = store.page().loaded_0().unwrap();
+    let read = Arc::new(Mutex::new(None));
+    let effect = Effect::new({
+        let read = Arc::clone(&read);
+        move |_| {
+            *read.lock().unwrap() = Some(loaded.try_get());
+        }
+    });
+    run_until_stalled();
+    assert_eq!(*read.lock().unwrap(), Some(Some(post())));
+
+    store.page().set(Page::Failed {
+        error: "not found".to_string(),
+    });
+    run_until_stalled();
+    // the field tracks the enum, so it runs again and finds nothing
+    assert_eq!(testing::run_count(&effect), 2);
+    assert_eq!(*read.lock().unwrap(), Some(None));
+    assert_eq!(loaded.title().try_get_untracked(), None);
+    assert_eq!(loaded.try_update(|post| post.title.clear()), None);
+    assert!(store.page().is_failed());
+}
+
+// runs an effect that reads a field, returning a handle to count its runs
+fn watch<T>(read: impl Fn() -> T + Send + Sync + 'static) -> Effect<()> {
+    Effect::new(move |_| {
+        read();
+    })
+}
+
+#[test]
+fn patching_only_notifies_changed_fields() {
+    testing::install();
+
+    let store = Store::new(post());
+    let title = watch(move || store.title().get());
+    let name = watch(move || store.author().name().get());
+    let author = watch(move || store.author().get());
+    run_until_stalled();
+
+    let mut next = post();
+    next.title = "Patches".to_string();
+    store.patch(next.clone());
+    run_until_stalled();
+    assert_eq!(store.get_untracked(), next);
+    assert_eq!(testing::run_count(&title), 2);
+    assert_eq!(testing::run_count(&name), 1);
+    assert_eq!(testing::run_count(&author), 1);
+
+    // the fields that contain a changed field are notified, too
+    next.author.name = "Ada".to_string();
+    store.patch(next.clone());
+    run_until_stalled();
+    assert_eq!(testing::run_count(&title), 2);
+    assert_eq!(testing::run_count(&name), 2);
+    assert_eq!(testing::run_count(&author), 2);
+
+    // patching with an equal value notifies nothing
+    store.patch(next);
+    run_until_stalled();
+    assert_eq!(testing::run_count(&title), 2);
+    assert_eq!(testing::run_count(&name), 2);
+    assert_eq!(testing::run_count(&author), 2);
+}
+
+#[test]
+fn patching_rows_only_notifies_changed_rows() {
+    testing::install();
+
+    let store = Store::new(Feed {
+        posts: vec![post(), post()],
+    });
+    let first = watch(move || store.posts().index(0).title().get());
+    let second = watch(move || store.posts().index(1).title().get());
+    let len = watch(move || store.posts().with(Vec::len));
+    run_until_stalled();
+
+    let mut next = store.get_untracked();
+    next.posts[1].title = "Patches".to_string();
+    store.patch(next.clone());
+    run_until_stalled();
+    assert_eq!(testing::run_count(&first), 1);
+    assert_eq!(testing::run_count(&second), 2);
+    assert_eq!(testing::run_count(&len), 2);
+
+    next.posts.push(post());
+    store.patch(next.clone());
+    run_until_stalled();
+    assert_eq!(store.get_untracked(), next);
+    assert_eq!(testing::run_count(&first), 1);
+    assert_eq!(testing::run_count(&second), 2);
+    assert_eq!(testing::run_count(&len), 3);
+}
+
+#[test]
+fn patching_an_enum_replaces_other_variants() {
+    testing::install();
+
+    let store = Store::new(State {
+        page: Page::Loaded(post()),
+    });
+    let loaded = watch(move || store.page().is_loaded());
+    let title =
+        watch(move || store.page().loaded_0().map(|post| post.title().get()));
+    run_until_stalled();
+
+    // patching with the same variant and value notifies nothing
+    store.patch(State {
+        page: Page::Loaded(post()),
+    });
+    run_until_stalled();
+    assert_eq!(testing::run_count(&loaded), 1);
+    assert_eq!(testing::run_count(&title), 1);
+
+    store.patch(State {
+        page: Page::Failed {
+            error: "not found".to_string(),
+        },
+    });
+    run_until_stalled();
+    assert!(store.page().is_failed());
+    assert_eq!(testing::run_count(&loaded), 2);
+    assert_eq!(testing::run_count(&title), 2);
+}
+
+#[test]
+fn map_entries_are_tracked_by_key() {
+    testing::install();
+
+    let store = Store::new(Library {
+        posts: HashMap::from([(1, post()), (2, post())]),
+        tags: BTreeMap::new(),
+    });
+    let first = watch(move || store.posts().at_map_key(&1).get());
+    let second = watch(move || store.posts().at_map_key(&2).title().get());
+    let ids = watch(move || {
+        store
+            .posts()
+            .iter_map()
+            .map(|post| *post.map_key())
+            .collect::<Vec<_>>()
+    });
+    run_until_stalled();
+
+    store
+        .posts()
+        .at_map_key(&2)
+        .title()
+        .set("Patches".to_string());
+    run_until_stalled();
+    assert_eq!(testing::run_count(&first), 1);
+    assert_eq!(testing::run_count(&second), 2);
+    assert_eq!(testing::run_count(&ids), 1);
+
+    // replacing an existing entry does not change the set of keys
+    store.posts().insert_entry(1, post());
+    run_until_stalled();
+    assert_eq!(testing::run_count(&first), 2);
+    assert_eq!(testing::run_count(&second), 2);
+    assert_eq!(testing::run_count(&ids), 1);
+
+    store.posts().insert_entry(3, post());
+    run_until_stalled();
+    assert_eq!(testing::run_count(&first), 2);
+    assert_eq!(testing::run_count(&ids), 2);
+
+    assert_eq!(
+        store.posts().remove_entry(&3).map(|post| post.title),
+        Some("Stores".to_string())
+    );
+    assert_eq!(store.posts().remove_entry(&3), None);
+    run_until_stalled();
+    assert_eq!(testing::run_count(&ids), 3);
+    assert_eq!(testing::run_count(&second), 2);
+}
+
+#[test]
+fn missing_map_entries_can_be_inserted_later() {
+    testing::install();
+
+    let store = Store::new(Library {
+        posts: HashMap::new(),
+        tags: BTreeMap::new(),
+    });
+    let count = Arc::new(Mutex::new(None));
+    let rust = watch({
+        let count = Arc::clone(&count);
+        move || {
+            *count.lock().unwrap() =
+                store.tags().at_map_key(&"rust".to_string()).try_get();
+        }
+    });
+    let other =
+        watch(move || store.tags().at_map_key(&"other".to_string()).try_get());
+    run_until_stalled();
+    assert_eq!(*count.lock().unwrap(), None);
+    assert!(!store.tags().contains_map_key(&"rust".to_string()));
+
+    store.tags().insert_entry("rust".to_string(), 3);
+    run_until_stalled();
+    assert_eq!(*count.lock().unwrap(), Some(3));
+    assert_eq!(testing::run_count(&rust), 2);
+    assert_eq!(testing::run_count(&other), 1);
+
+    store
+        .tags()
+        .at_map_key(&"rust".to_string())
+        .update(|n| *n += 1);
+    run_until_stalled();
+    assert_eq!(*count.lock().unwrap(), Some(4));
+    assert_eq!(testing::run_count(&other), 1);
+}
+
+#[test]
+fn patching_maps_only_notifies_changed_entries() {
+    testing::install();
+
+    let store = Store::new(Library {
+        posts: HashMap::from([(1, post()), (2, post())]),
+        tags: BTreeMap::from([("rust".to_string(), 1)]),
+    });
+    let first = watch(move || store.posts().at_map_key(&1).title().get());
+    let second = watch(move || store.posts().at_map_key(&2).try_get());
+    let third = watch(move || store.posts().at_map_key(&3).try_get());
+    let posts = watch(move || store.posts().with(|posts| posts.len()));
+    let rust =
+        watch(move || store.tags().at_map_key(&"rust".to_string()).get());
+    run_until_stalled();
+
+    // matching keys are patched in place, notifying the map that holds them
+    let mut next = store.get_untracked();
+    next.posts.get_mut(&1).unwrap().title = "Patches".to_string();
+    store.patch(next.clone());
+    run_until_stalled();
+    assert_eq!(store.get_untracked(), next);
+    assert_eq!(testing::run_count(&first), 2);
+    assert_eq!(testing::run_count(&second), 1);
+    assert_eq!(testing::run_count(&third), 1);
+    assert_eq!(testing::run_count(&posts), 2);
+    assert_eq!(testing::run_count(&rust), 1);
+
+    // missing keys are removed and new keys are inserted
+    next.posts.remove(&2);
+    next.posts.insert(3, post());
+    store.patch(next.clone());
+    run_until_stalled();
+    assert_eq!(store.get_untracked(), next);
+    assert_eq!(store.posts().at_map_key(&2).try_get(), None);
+    assert_eq!(testing::run_count(&first), 2);
+    assert_eq!(testing::run_count(&second), 2);
+    assert_eq!(testing::run_count(&third), 2);
+    assert_eq!(testing::run_count(&posts), 3);
+    assert_eq!(testing::run_count(&rust), 1);
+
+    next.tags.insert("rust".to_string(), 2);
+    store.patch(next);
+    run_until_stalled();
+    assert_eq!(store.tags().at_map_key(&"rust".to_string()).get(), 2);
+    assert_eq!(testing::run_count(&first), 2);
+    assert_eq!(testing::run_count(&posts), 3);
+    assert_eq!(testing::run_count(&rust), 2);
+}
+
+#[test]
+fn writes_are_observed_as_json_patches() {
+    let store = Store::new(Feed {
+        posts: vec![post()],
+    });
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let observer = store
+        .on_change({
+            let changes = Arc::clone(&changes);
+            move |change| {
+                changes
+                    .lock()
+                    .unwrap()
+                    .push(serde_json::to_string(change).unwrap())
+            }
+        })
+        .unwrap();
+
+    store
+        .posts()
+        .index(0)
+        .author()
+        .name()
+        .set("Ada".to_string());
+    store.posts().update(|posts| posts.push(post()));
+    observer.stop();
+    store.posts().index(1).title().set("Unobserved".to_string());
+
+    assert_eq!(
+        *changes.lock().unwrap(),
+        [
+            r#"{"op":"replace","path":"/posts/0/author/name","value":"Ada"}"#
+                .to_string(),
+            serde_json::json!({
+                "op": "replace",
+                "path": "/posts",
+                "value": [
+                    { "title": "Stores", "author": { "name": "Ada" } },
+                    { "title": "Stores", "author": { "name": "Greg" } },
+                ]
+            })
+            .to_string()
+        ]
+    );
+}
+
+#[test]
+fn applying_patches_replays_changes() {
+    testing::install();
+
+    let local = Store::new(Feed {
+        posts: vec![post()],
+    });
+    let remote = Store::new(Feed {
+        posts: vec![post()],
+    });
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let _observer = local.on_change({
+        let changes = Arc::clone(&changes);
+        move |change| changes.lock().unwrap().push(change.clone())
+    });
+
+    let name = watch(move || remote.posts().index(0).author().name().get());
+    let title = watch(move || remote.posts().index(0).title().get());
+    let posts = watch(move || remote.posts().with(Vec::len));
+    run_until_stalled();
+
+    local
+        .posts()
+        .index(0)
+        .author()
+        .name()
+        .set("Ada".to_string());
+    let sent = serde_json::to_string(&*changes.lock().unwrap()).unwrap();
+    let received = serde_json::from_str::<Vec<StoreChange>>(&sent).unwrap();
+    remote.apply_patch(received).unwrap();
+    run_until_stalled();
+    assert_eq!(remote.get_untracked(), local.get_untracked());
+    assert_eq!(testing::run_count(&name), 2);
+    assert_eq!(testing::run_count(&title), 1);
+    assert_eq!(testing::run_count(&posts), 2);
+
+    // rows can be added and removed, as in any JSON Patch
+    let patch = serde_json::json!([
+        {
+            "op": "add",
+            "path": "/posts/0",
+            "value": { "title": "First", "author": { "name": "Greg" } }
+        },
+        { "op": "remove", "path": "/posts/1" },
+        {
+            "op": "add",
+            "path": "/posts/-",
+            "value": { "title": "Draft", "author": { "name": "Ada" } }
+        },
+        { "op": "replace", "path": "/posts/1/title", "value": "Second" },
+    ]);
+    remote
+        .apply_patch(serde_json::from_value::<Vec<StoreChange>>(patch).unwrap())
+        .unwrap();
+    assert_eq!(
+        remote
+            .get_untracked()
+            .posts
+            .into_iter()
+            .map(|post| post.title)
+            .collect::<Vec<_>>(),
+        ["First", "Second"]
+    );
+}
+
+#[test]
+fn invalid_patches_are_rejected() {
+    let store = Store::new(post());
+    let patch = |json| serde_json::from_value::<StoreChange>(json);
+
+    assert!(
+        patch(serde_json::json!({ "op": "replace", "path": "/title" }))
+            .is_err()
+    );
+    assert!(matches!(
+        store.apply_patch([patch(serde_json::json!({
+            "op": "replace",
+            "path": "/editor",
+            "value": "nothing"
+        }))
+        .unwrap()]),
+        Err(JsonPatchError::PathNotFound)
+    ));
+    assert!(matches!(
+        store.apply_patch([patch(serde_json::json!({
+            "op": "replace",
+            "path": "/title",
+            "value": 42
+        }))
+        .unwrap()]),
+        Err(JsonPatchError::Deserialization(_))
+    ));
+    assert!(matches!(
+        store.apply_patch([patch(
+            serde_json::json!({ "op": "remove", "path": "/title" })
+        )
+        .unwrap()]),
+        Err(JsonPatchError::CannotRemove)
+    ));
+}
+
+#[test]
+fn enum_fields_are_addressed_by_path() {
+    let store = Store::new(State {
+        page: Page::Loaded(post()),
+    });
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let _observer = store.on_change({
+        let changes = Arc::clone(&changes);
+        move |change| changes.lock().unwrap().push(change.clone())
+    });
+
+    // the variant is part of the path, as serde tags it
+    store
+        .page()
+        .loaded_0()
+        .unwrap()
+        .title()
+        .set("Patches".to_string());
+    let changes = changes.lock().unwrap().clone();
+    assert_eq!(
+        serde_json::to_value(&changes).unwrap(),
+        serde_json::json!([
+            { "op": "replace", "path": "/page/Loaded/title", "value": "Patches" }
+        ])
+    );
+
+    let other = Store::new(State {
+        page: Page::Loaded(post()),
+    });
+    other.apply_patch(changes.clone()).unwrap();
+    assert_eq!(other.get_untracked(), store.get_untracked());
+
+    // the path does not exist in another variant
+    let failed = Store::new(State {
+        page: Page::Failed {
+            error: "not found".to_string(),
+        },
+    });
+    assert!(matches!(
+        failed.apply_patch(changes),
+        Err(JsonPatchError::PathNotFound)
+    ));
+}
+
+#[test]
+fn patches_and_field_handles_are_observed() {
+    let store = Store::new(Feed {
+        posts: vec![post()],
+    });
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let _observer = store.on_change({
+        let changes = Arc::clone(&changes);
+        move |change| {
+            changes
+                .lock()
+                .unwrap()
+                .push(serde_json::to_value(change).unwrap())
+        }
+    });
+
+    let mut new = store.get_untracked();
+    new.posts[0].title = "Patched".to_string();
+    store.patch(new);
+    store
+        .posts()
+        .index(0)
+        .author()
+        .name()
+        .rw()
+        .set("Ada".to_string());
+    store
+        .posts()
+        .index(0)
+        .title()
+        .write()
+        .set("Written".to_string());
+
+    assert_eq!(
+        *changes.lock().unwrap(),
+        [
+            serde_json::json!({
+                "op": "replace",
+                "path": "/posts/0/title",
+                "value": "Patched"
+            }),
+            serde_json::json!({
+                "op": "replace",
+                "path": "/posts/0/author/name",
+                "value": "Ada"
+            }),
+            serde_json::json!({
+                "op": "replace",
+                "path": "/posts/0/title",
+                "value": "Written"
+            }),
+        ]
+    );
+}
+
+#[derive(
+    Store, Patch, JsonPatch, Debug, Clone, PartialEq, Serialize, Deserialize,
+)]
+struct Profile {
+    #[serde(rename = "displayName")]
+    display_name: String,
+}
+
+#[test]
+fn renamed_fields_are_addressed_by_their_serde_name() {
+    let store = Store::new(Profile {
+        display_name: "Greg".to_string(),
+    });
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let _observer = store.on_change({
+        let changes = Arc::clone(&changes);
+        move |change| changes.lock().unwrap().push(change.clone())
+    });
+
+    store.display_name().set("Ada".to_string());
+    let changes = changes.lock().unwrap().clone();
+    assert_eq!(
+        serde_json::to_value(&changes).unwrap(),
+        serde_json::json!([
+            { "op": "replace", "path": "/displayName", "value": "Ada" }
+        ])
+    );
+
+    let other = Store::new(Profile {
+        display_name: "Greg".to_string(),
+    });
+    other.apply_patch(changes).unwrap();
+    assert_eq!(other.get_untracked(), store.get_untracked());
+}